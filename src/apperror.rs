@@ -37,3 +37,16 @@ impl fmt::Display for AppError {
 }
 
 impl std::error::Error for AppError {}
+
+/// コンパイルは続けるが疑わしい箇所を、エラーと同じ形式で標準エラー出力に書き出す
+pub fn report_warning(message: &str, input: &str, filename: &str, pos: usize) {
+    eprintln!(
+        "{}",
+        AppError {
+            message: format!("警告: {message}"),
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos,
+        }
+    );
+}
@@ -1,4 +1,8 @@
-use crate::parse::{toplevel::TypeAndSize, typ::Type};
+use crate::parse::{
+    initializer::InitializedField,
+    toplevel::{BitField, TypeAndSize},
+    typ::Type,
+};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum BinaryOp {
@@ -7,6 +11,11 @@ pub enum BinaryOp {
     Mul,
     Div,
     Remainder,
+    BitAnd,
+    BitOr,
+    BitXor,
+    LeftShift,
+    RightShift,
     LessThan,
     LessThanOrEqual,
     Equal,
@@ -15,10 +24,37 @@ pub enum BinaryOp {
     Assign,
     AddAssign,
     SubAssign,
+    MulAssign,
+    DivAssign,
+    RemainderAssign,
+    BitAndAssign,
+    BitOrAssign,
+    BitXorAssign,
+    LeftShiftAssign,
+    RightShiftAssign,
     LogicalAnd,
     LogicalOr,
 }
 
+impl BinaryOp {
+    /// 複合代入演算子なら、代入の前に行う二項演算を返す
+    pub fn compound_assign_base(self) -> Option<BinaryOp> {
+        match self {
+            BinaryOp::AddAssign => Some(BinaryOp::Add),
+            BinaryOp::SubAssign => Some(BinaryOp::Sub),
+            BinaryOp::MulAssign => Some(BinaryOp::Mul),
+            BinaryOp::DivAssign => Some(BinaryOp::Div),
+            BinaryOp::RemainderAssign => Some(BinaryOp::Remainder),
+            BinaryOp::BitAndAssign => Some(BinaryOp::BitAnd),
+            BinaryOp::BitOrAssign => Some(BinaryOp::BitOr),
+            BinaryOp::BitXorAssign => Some(BinaryOp::BitXor),
+            BinaryOp::LeftShiftAssign => Some(BinaryOp::LeftShift),
+            BinaryOp::RightShiftAssign => Some(BinaryOp::RightShift),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum UnaryOp {
     Addr,
@@ -39,6 +75,12 @@ pub enum Expr {
         pos: usize,
         typ: Type,
     },
+    /// 浮動小数点定数。`bits` には、float なら `f32` の、double なら `f64` のビット列が入っている
+    FloatingNumeric {
+        bits: u64,
+        pos: usize,
+        typ: Type,
+    },
     NullPtr {
         pos: usize,
         typ: Type,
@@ -65,20 +107,86 @@ pub enum Expr {
         expr: Box<Expr>,
         typ: Type,
     },
+    /// 関数指示子。常に関数へのポインタに変換される
+    DecayedFunc {
+        ident: String,
+        pos: usize,
+        typ: Type,
+    },
+    /// 関数ポインタを通した呼び出し
+    IndirectCall {
+        callee: Box<Expr>,
+        pos: usize,
+        args: Vec<Expr>,
+        typ: Type,
+    },
+    /// 明示的な型変換 `(typ)expr`
+    Cast {
+        expr: Box<Expr>,
+        pos: usize,
+        typ: Type,
+    },
+    /// 条件演算子 `cond ? then : else_`。`then` と `else_` はどちらも `typ` に変換済み
+    Conditional {
+        cond: Box<Expr>,
+        then: Box<Expr>,
+        else_: Box<Expr>,
+        pos: usize,
+        typ: Type,
+    },
+    /// ブロックスコープでの複合リテラル `(typ){...}`。評価するたびに名前のないローカル変数 `local_var_id` を
+    /// 初期化し、その変数を指す左辺値となる
+    CompoundLiteral {
+        ident: String,
+        local_var_id: u64,
+        initializer: Vec<InitializedField>,
+        pos: usize,
+        typ: Type,
+    },
+    /// `__builtin_alloca(size)`。現在の関数のスタック上に `size` バイトの領域を確保する。
+    /// 確保した領域は関数から戻るときに解放される
+    Alloca {
+        size: Box<Expr>,
+        pos: usize,
+        typ: Type,
+    },
+    /// `va_start(ap, last)`。`ap` の指す `va_list` を、`va_arg` が現在の関数の可変長引数を
+    /// 読めるように初期化する
+    VaStart {
+        ap: Box<Expr>,
+        pos: usize,
+        typ: Type,
+    },
+    /// `va_arg(ap, typ)`。次の可変長引数を `typ` として読み、`ap` の指す `va_list` を
+    /// その次の引数へ進める
+    VaArg {
+        ap: Box<Expr>,
+        pos: usize,
+        typ: Type,
+    },
+    /// ビットフィールドのメンバー。`unit` はそれを含む記憶単位を指す左辺値
+    BitField {
+        unit: Box<Expr>,
+        bit_field: BitField,
+        pos: usize,
+        typ: Type,
+    },
 }
 
 pub fn decay_if_arr(expr: Expr) -> Box<Expr> {
     match expr.typ() {
-        Type::Arr(t, _) => Box::new(Expr::DecayedArr {
-            expr: Box::new(expr),
-            typ: Type::Ptr(t),
-        }),
+        Type::Arr(t, _) | Type::IncompleteArr(t) | Type::VarLenArr(t, _) => {
+            Box::new(Expr::DecayedArr {
+                expr: Box::new(expr),
+                typ: Type::Ptr(t),
+            })
+        }
         _ => Box::new(expr),
     }
 }
 
 pub fn throw_if_arr(expr: Expr) -> Box<Expr> {
-    if let Type::Arr(_, _) = expr.typ() {
+    if let Type::Arr(_, _) | Type::VarLenArr(..) = expr.typ() {
         panic!("配列型に対して適用できない操作があります: {expr:?}");
     }
     Box::new(expr)
@@ -89,15 +197,31 @@ pub fn no_decay_even_if_arr(expr: Expr) -> Box<Expr> {
 }
 
 impl Expr {
+    /// 式の値の型。左辺値の型に付いている一番外側の修飾子は取り除かれる
     pub fn typ(&self) -> Type {
+        self.lvalue_typ().unqualified().clone()
+    }
+
+    /// 修飾子も含めた、式の型
+    pub fn lvalue_typ(&self) -> &Type {
         match self {
             Expr::BinaryExpr { typ, .. }
             | Expr::Numeric { typ, .. }
+            | Expr::FloatingNumeric { typ, .. }
             | Expr::Identifier { typ, .. }
             | Expr::Call { typ, .. }
             | Expr::DecayedArr { typ, .. }
+            | Expr::DecayedFunc { typ, .. }
+            | Expr::IndirectCall { typ, .. }
+            | Expr::Cast { typ, .. }
+            | Expr::Conditional { typ, .. }
+            | Expr::BitField { typ, .. }
+            | Expr::CompoundLiteral { typ, .. }
+            | Expr::Alloca { typ, .. }
+            | Expr::VaStart { typ, .. }
+            | Expr::VaArg { typ, .. }
             | Expr::UnaryExpr { typ, .. }
-            | Expr::NullPtr { typ, .. } => (*typ).clone(),
+            | Expr::NullPtr { typ, .. } => typ,
         }
     }
 }
@@ -110,12 +234,26 @@ pub enum StatementOrDeclaration {
         id: u64,
         typ_and_size: TypeAndSize,
     },
+    /// 初期化子で明示されなかった部分は、ゼロで初期化される
     DeclarationWithInitializer {
         name: String,
         id: u64,
         typ_and_size: TypeAndSize,
-        initializer: Box<Expr>,
+        initializer: Vec<InitializedField>,
+    },
+    /// 可変長配列や、可変長配列へのポインタのように、大きさが実行時に決まる型を持つ変数の宣言。
+    /// まず `exprs`（配列の大きさの計算と、あればポインタの初期化）を評価し、
+    /// 変数が可変長配列なら、スタックに確保した領域の先頭のアドレスを変数に保存する
+    VarLenArrDeclaration {
+        name: String,
+        id: u64,
+        typ: Type,
+        exprs: Vec<Expr>,
     },
+    /// `enum E { A, B };` のように型だけを宣言しているもの。コードは生成されない
+    TypeDeclaration,
+    /// static なローカル変数や、ブロックスコープでの extern 宣言。記憶域は関数の外にあるので、コードは生成されない
+    StaticDeclaration,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -151,9 +289,51 @@ pub enum Statement {
         body: Box<StatementOrDeclaration>,
         pos: usize,
     },
+    DoWhile {
+        body: Box<StatementOrDeclaration>,
+        cond: Box<Expr>,
+        pos: usize,
+    },
+    Break {
+        pos: usize,
+    },
+    Continue {
+        pos: usize,
+    },
+    /// `cases` は、本体の中で（入れ子になった switch 文の中を除いて）現れた case の値を、現れた順に並べたもの
+    Switch {
+        cond: Box<Expr>,
+        body: Box<StatementOrDeclaration>,
+        cases: Vec<i32>,
+        has_default: bool,
+        pos: usize,
+    },
+    /// switch 文の本体の中の `case value: body`
+    Case {
+        value: i32,
+        body: Box<StatementOrDeclaration>,
+        pos: usize,
+    },
+    Default {
+        body: Box<StatementOrDeclaration>,
+        pos: usize,
+    },
+    /// `name: body`。関数の中のどこからでも、goto 文で飛んでこられる
+    Labeled {
+        name: String,
+        body: Box<StatementOrDeclaration>,
+        pos: usize,
+    },
+    Goto {
+        name: String,
+        pos: usize,
+    },
     Block {
         statements: Vec<StatementOrDeclaration>,
         pos: usize,
+        /// 可変長配列を宣言しているブロックでは、抜けるときにその領域を解放するため、
+        /// 入るときのスタックポインタをこの ID の隠れたローカル変数に保存しておく
+        saved_stack_pointer: Option<u64>,
     },
     BuiltinPopulateArgcArgv {
         pos: usize,
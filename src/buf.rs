@@ -2,6 +2,8 @@
 RopeのようなO(1)で連結できるバッファ
 */
 
+use std::collections::HashMap;
+
 #[must_use]
 #[derive(Debug, Clone)]
 pub enum Buf {
@@ -16,7 +18,22 @@ pub enum Buf {
     NamedHole {
         name: String,
         len: usize,
-    }
+    },
+    /// 長さ 0 の目印。同じ関数の中の JumpToLabel の飛び先になる
+    Label {
+        name: String,
+    },
+    /// ラベル name への相対ジャンプ。opcode の後に 4 バイトのオフセットが続く。
+    /// 飛び先の位置はまだ分からないので、関数全体を生成し終えてから resolve_labels で埋める
+    JumpToLabel {
+        name: String,
+        opcode: Vec<u8>,
+    },
+    /// ラベル name の位置からラベル base の位置を引いた、4 バイトの値。ジャンプテーブルの要素に使う
+    LabelOffset {
+        name: String,
+        base: String,
+    },
 }
 
 impl Default for Buf {
@@ -32,8 +49,11 @@ impl Buf {
 
     pub fn fill_hole_with_vec(&mut self, search_name: String, vec: Vec<u8>) {
         match *self {
-            Self::Leaf { .. } => {
-                // do nothing, as leaf cannot have holes
+            Self::Leaf { .. }
+            | Self::Label { .. }
+            | Self::JumpToLabel { .. }
+            | Self::LabelOffset { .. } => {
+                // do nothing, as these cannot have holes
             }
             Self::Branch { ref mut left, ref mut right, .. } => {
                 left.fill_hole_with_vec(search_name.clone(), vec.clone());
@@ -53,6 +73,82 @@ impl Buf {
         
     }
 
+    /// 名前が search_name の、まだ埋められていない穴があるかどうか
+    #[must_use]
+    pub fn has_hole(&self, search_name: &str) -> bool {
+        match self {
+            Self::Leaf { .. }
+            | Self::Label { .. }
+            | Self::JumpToLabel { .. }
+            | Self::LabelOffset { .. } => false,
+            Self::Branch { left, right, .. } => {
+                left.has_hole(search_name) || right.has_hole(search_name)
+            }
+            Self::NamedHole { name, .. } => name == search_name,
+        }
+    }
+
+    /// 中にある JumpToLabel と LabelOffset のオフセットを、同じバッファの中のラベルの位置から計算して埋める
+    pub fn resolve_labels(&mut self) {
+        let mut labels = HashMap::new();
+        self.collect_labels(0, &mut labels);
+        self.fill_jumps_to_labels(0, &labels);
+    }
+
+    fn collect_labels(&self, start: usize, labels: &mut HashMap<String, usize>) {
+        match self {
+            Self::Leaf { .. }
+            | Self::NamedHole { .. }
+            | Self::JumpToLabel { .. }
+            | Self::LabelOffset { .. } => {}
+            Self::Branch { left, right, .. } => {
+                left.collect_labels(start, labels);
+                right.collect_labels(start + left.len(), labels);
+            }
+            Self::Label { name } => {
+                if labels.insert(name.clone(), start).is_some() {
+                    panic!("ラベル {name} が重複しています");
+                }
+            }
+        }
+    }
+
+    fn fill_jumps_to_labels(&mut self, start: usize, labels: &HashMap<String, usize>) {
+        match self {
+            Self::Leaf { .. } | Self::NamedHole { .. } => {}
+            Self::Branch { left, right, .. } => {
+                let right_start = start + left.len();
+                left.fill_jumps_to_labels(start, labels);
+                right.fill_jumps_to_labels(right_start, labels);
+            }
+            Self::Label { .. } => {
+                *self = Buf::new();
+            }
+            Self::JumpToLabel { name, opcode } => {
+                let target = *labels
+                    .get(name)
+                    .unwrap_or_else(|| panic!("ラベル {name} が見つかりません"));
+                let end = start + opcode.len() + 4;
+                let offset = i32::try_from(target as i64 - end as i64)
+                    .expect("ラベルへのオフセットが i32 に収まりません");
+                let mut vec = opcode.clone();
+                vec.extend_from_slice(&offset.to_le_bytes());
+                *self = Buf::from(vec);
+            }
+            Self::LabelOffset { name, base } => {
+                let target = *labels
+                    .get(name)
+                    .unwrap_or_else(|| panic!("ラベル {name} が見つかりません"));
+                let base = *labels
+                    .get(base)
+                    .unwrap_or_else(|| panic!("ラベル {base} が見つかりません"));
+                let offset = i32::try_from(target as i64 - base as i64)
+                    .expect("ラベル間のオフセットが i32 に収まりません");
+                *self = Buf::from(offset.to_le_bytes());
+            }
+        }
+    }
+
     #[must_use]
     pub fn to_vec(&self) -> Vec<u8> {
         let mut vec = Vec::new();
@@ -66,6 +162,8 @@ impl Buf {
             Self::Leaf { buf } => buf.is_empty(),
             Self::Branch { len, .. } => *len == 0,
             Self::NamedHole { len, .. } => *len == 0,
+            Self::Label { .. } => true,
+            Self::JumpToLabel { .. } | Self::LabelOffset { .. } => false,
         }
     }
 
@@ -75,6 +173,9 @@ impl Buf {
             Self::Leaf { buf } => buf.len(),
             Self::Branch { len, .. } => *len,
             Self::NamedHole { len, .. } => *len,
+            Self::Label { .. } => 0,
+            Self::JumpToLabel { opcode, .. } => opcode.len() + 4,
+            Self::LabelOffset { .. } => 4,
         }
     }
 
@@ -111,6 +212,13 @@ impl Buf {
             Self::NamedHole { name, len } => {
                 panic!("長さ {len} の名前付き穴 {name} を埋める必要がありますが、まだ埋められていません");
             }
+            Self::Label { .. } => {}
+            Self::JumpToLabel { name, .. } => {
+                panic!("ラベル {name} へのジャンプのオフセットが、まだ埋められていません");
+            }
+            Self::LabelOffset { name, base } => {
+                panic!("ラベル {base} からラベル {name} へのオフセットが、まだ埋められていません");
+            }
         }
     }
 }
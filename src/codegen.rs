@@ -1,7 +1,12 @@
 use crate::{
     ast::*,
     parse::{
-        toplevel::{FunctionDefinition, TypeAndSize},
+        context::隠れたローカル変数の名前,
+        expression::隠れたローカル変数,
+        initializer::{InitializedField, RelocationTarget},
+        toplevel::{
+            BitField, FunctionDefinition, GlobalVariableDefinition, StructDefinition, TypeAndSize,
+        },
         typ::Type,
     },
     x86_64_no_arg::*,
@@ -152,15 +157,320 @@ impl LocalVarTable {
         self.offsets.push((ident.to_owned(), id, offset));
         offset
     }
+
+    /// 構造体の値のコピー先などに使う、名前を持たない領域を確保する
+    pub fn allocate_temporary(&mut self, size: i32) -> i32 {
+        self.allocate("", u64::MAX, size)
+    }
+}
+
+/// System V ABI における、eightbyte ごとの分類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum クラス {
+    /// 汎用レジスタで渡す
+    Integer,
+    /// xmm レジスタで渡す
+    Sse,
+}
+
+/// System V ABI における、引数や戻り値の渡し方の分類
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum 渡し方 {
+    /// レジスタで渡す。中身は eightbyte ごとの分類
+    Registers(Vec<クラス>),
+    /// メモリ上で渡す。引数ならスタックに積み、戻り値なら呼び出し元が確保した領域に書き込む
+    Memory,
+}
+
+impl 渡し方 {
+    /// 汎用レジスタと xmm レジスタをそれぞれいくつ使うか
+    fn 使うレジスタの数(&self) -> (usize, usize) {
+        match self {
+            渡し方::Registers(classes) => {
+                let sse = classes.iter().filter(|c| **c == クラス::Sse).count();
+                (classes.len() - sse, sse)
+            }
+            渡し方::Memory => (0, 0),
+        }
+    }
+}
+
+fn 渡し方を分類(typ: &Type, struct_names: &HashMap<String, StructDefinition>) -> 渡し方 {
+    match typ.unqualified() {
+        Type::Struct { .. } => {
+            let size = typ.sizeof(struct_names);
+            if size > 16 {
+                return 渡し方::Memory;
+            }
+            // eightbyte に重なるスカラーが全て浮動小数点数なら SSE、そうでなければ INTEGER
+            let mut scalars = vec![];
+            スカラーを列挙(typ, 0, struct_names, &mut scalars);
+            let classes = (0..(size as usize).div_ceil(WORD_SIZE as usize))
+                .map(|i| {
+                    if scalars
+                        .iter()
+                        .filter(|(offset, _)| *offset as usize / WORD_SIZE as usize == i)
+                        .all(|(_, t)| t.is_floating())
+                    {
+                        クラス::Sse
+                    } else {
+                        クラス::Integer
+                    }
+                })
+                .collect();
+            渡し方::Registers(classes)
+        }
+        t if t.is_floating() => 渡し方::Registers(vec![クラス::Sse]),
+        _ => 渡し方::Registers(vec![クラス::Integer]),
+    }
+}
+
+/// 構造体や配列に含まれるスカラーを、先頭からのオフセットと共に列挙する
+fn スカラーを列挙(
+    typ: &Type,
+    offset: i32,
+    struct_names: &HashMap<String, StructDefinition>,
+    out: &mut Vec<(i32, Type)>,
+) {
+    match typ.unqualified() {
+        Type::Struct { struct_name } => {
+            for member in struct_names[struct_name].members.values() {
+                スカラーを列挙(
+                    &member.member_type,
+                    offset + member.offset,
+                    struct_names,
+                    out,
+                );
+            }
+        }
+        Type::Arr(t, len) => {
+            for i in 0..*len {
+                スカラーを列挙(t, offset + i * t.sizeof(struct_names), struct_names, out);
+            }
+        }
+        t => out.push((offset, t.clone())),
+    }
+}
+
+/// グローバル変数のアドレスは、全ての関数のコード生成が終わるまで決まらないので、
+/// いったん名前付きの穴にしておき、`グローバル変数を配置` で埋める
+fn グローバル変数のアドレスの穴の名前(ident: &str) -> String {
+    format!("グローバル変数 {ident}")
+}
+
+fn グローバル変数のアドレスをediに代入(ident: &str) -> Buf {
+    Buf::from([0xbf]).join(Buf::NamedHole {
+        name: グローバル変数のアドレスの穴の名前(ident),
+        len: 4,
+    })
+}
+
+/// 関数のアドレスも、後ろで定義される関数を指していればその関数のコード生成が終わるまで決まらないので、
+/// いったん名前付きの穴にしておき、`関数のアドレスを埋める` で埋める
+fn 関数のアドレスの穴の名前(ident: &str) -> String {
+    format!("関数 {ident}")
+}
+
+fn 関数のアドレスをediに代入(ident: &str) -> Buf {
+    Buf::from([0xbf]).join(Buf::NamedHole {
+        name: 関数のアドレスの穴の名前(ident),
+        len: 4,
+    })
+}
+
+fn 関数のアドレスをeaxに代入(ident: &str) -> Buf {
+    Buf::from([0xb8]).join(Buf::NamedHole {
+        name: 関数のアドレスの穴の名前(ident),
+        len: 4,
+    })
+}
+
+/// 全ての関数のコード生成が終わった後に呼び、関数のアドレスの穴を埋める
+pub fn 関数のアドレスを埋める(
+    buf: &mut Buf,
+    global_function_table: &HashMap<String, u32>,
+) {
+    for (ident, pos) in global_function_table {
+        buf.fill_hole_with_vec(
+            関数のアドレスの穴の名前(ident),
+            (pos + 0x00400000).to_le_bytes().to_vec(),
+        );
+    }
+}
+
+/// 全ての関数のコード生成が終わった後に呼び、グローバル変数を割り付ける。
+/// グローバル変数 `ident` のアドレスを、生成したコードや静的な初期化子が使っているかどうか。
+/// 穴は `グローバル変数を配置` で埋まるので、それより前に呼ぶ
+pub fn グローバル変数が参照されている(
+    buf: &Buf,
+    global_vars: &[GlobalVariableDefinition],
+    ident: &str,
+) -> bool {
+    buf.has_hole(&グローバル変数のアドレスの穴の名前(ident))
+        || global_vars
+            .iter()
+            .filter_map(|var| var.initializer.as_ref())
+            .flat_map(|initializer| &initializer.relocations)
+            .any(|relocation| {
+                matches!(&relocation.target, RelocationTarget::GlobalVar(name) if name == ident)
+            })
+}
+
+/// 初期化子を持つ変数と、それらから指される文字列リテラルは、ファイルの末尾に続くデータ領域に書き出す。
+/// 初期化子を持たない変数はその後ろの .bss 領域に置く。.bss 領域はカーネルがゼロで埋めるので、ファイルには書き出さない。
+/// 戻り値は .bss 領域の終わりの位置（ファイルの先頭からのオフセット）
+pub fn グローバル変数を配置(
+    buf: &mut Buf,
+    global_vars: &[GlobalVariableDefinition],
+    global_function_table: &HashMap<String, u32>,
+    struct_names: &HashMap<String, StructDefinition>,
+) -> u32 {
+    let mut end = u32::try_from(buf.len()).expect("バッファの長さが u32 に収まりません");
+    let mut addresses: HashMap<String, u32> = HashMap::new();
+    let mut allocate = |end: &mut u32, var: &GlobalVariableDefinition| {
+        let align = var.typ.alignof(struct_names) as u32;
+        let pos = end.div_ceil(align) * align;
+        *end = pos + var.typ.sizeof(struct_names) as u32;
+        addresses.insert(var.name.clone(), pos + 0x00400000);
+        pos
+    };
+
+    let data_vars = global_vars
+        .iter()
+        .filter_map(|var| Some((var, var.initializer.as_ref()?)))
+        .map(|(var, initializer)| (allocate(&mut end, var), initializer))
+        .collect::<Vec<_>>();
+
+    let mut strlit_addresses: HashMap<&str, u32> = HashMap::new();
+    let mut strlit_data = Vec::new();
+    for (_, initializer) in &data_vars {
+        for relocation in &initializer.relocations {
+            if let RelocationTarget::StrLit(string) = &relocation.target {
+                strlit_addresses.entry(string).or_insert_with(|| {
+                    let pos = end;
+                    end += string.len() as u32 + 1; // +1 for null terminator
+                    strlit_data.extend_from_slice(string.as_bytes());
+                    strlit_data.push(0);
+                    pos + 0x00400000
+                });
+            }
+        }
+    }
+
+    for var in global_vars.iter().filter(|var| var.initializer.is_none()) {
+        allocate(&mut end, var);
+    }
+
+    for (pos, initializer) in data_vars {
+        let mut bytes = initializer.bytes.clone();
+        for relocation in &initializer.relocations {
+            let target = match &relocation.target {
+                RelocationTarget::GlobalVar(ident) => addresses[ident],
+                RelocationTarget::Func(ident) => {
+                    *global_function_table
+                        .get(ident)
+                        .unwrap_or_else(|| panic!("関数 {ident} が見つかりません"))
+                        + 0x00400000
+                }
+                RelocationTarget::StrLit(string) => strlit_addresses[string.as_str()],
+            };
+            let value = i64::from(target) + i64::from(relocation.addend);
+            let offset = relocation.offset as usize;
+            bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        }
+        buf.append(vec![0; pos as usize - buf.len()]);
+        buf.append(bytes);
+    }
+    buf.append(strlit_data);
+
+    for (ident, address) in addresses {
+        buf.fill_hole_with_vec(
+            グローバル変数のアドレスの穴の名前(&ident),
+            address.to_le_bytes().to_vec(),
+        );
+    }
+    end
+}
+
+/// 変数 `var` のうち、初期化子の `field` が初期化する部分を表す左辺値
+fn 初期化される部分の左辺値(var: &Expr, field: &InitializedField) -> Box<Expr> {
+    let unit = if field.offset == 0 && field.typ == var.typ() {
+        var.clone()
+    } else {
+        Expr::UnaryExpr {
+            op: UnaryOp::Deref,
+            op_pos: 0,
+            expr: Box::new(Expr::BinaryExpr {
+                op: BinaryOp::Add,
+                op_pos: 0,
+                左辺: Box::new(Expr::UnaryExpr {
+                    op: UnaryOp::Addr,
+                    op_pos: 0,
+                    expr: Box::new(var.clone()),
+                    typ: Type::Ptr(Box::new(var.typ())),
+                }),
+                右辺: Box::new(Expr::Numeric {
+                    val: field.offset,
+                    pos: 0,
+                    typ: Type::Int,
+                }),
+                typ: Type::Ptr(Box::new(field.typ.clone())),
+            }),
+            typ: field.typ.clone(),
+        }
+    };
+    Box::new(match field.bit_field {
+        Some(bit_field) => Expr::BitField {
+            unit: Box::new(unit),
+            bit_field,
+            pos: 0,
+            typ: Type::Int,
+        },
+        None => unit,
+    })
 }
 
 pub struct FunctionGen<'a> {
     local_var_table: LocalVarTable,
     stack_size: u32,
-    global_function_table: &'a HashMap<String, u32>,
     function_name: &'a str,
+    struct_names: &'a HashMap<String, StructDefinition>,
+    /// 戻り値をメモリで返す関数において、呼び出し元から渡された格納先のアドレスを保存している位置
+    戻り値の格納先: Option<i32>,
+    /// ラベルの名前を関数の中で一意にするための通し番号
+    ラベルの個数: usize,
+    /// 今いる、可変長配列を宣言しているブロックで、入るときのスタックポインタを保存した位置。外側のブロックから順に並ぶ
+    保存したスタックポインタ: Vec<i32>,
+    /// break と continue の飛び先のラベルと、ループに入ったときの `保存したスタックポインタ` の長さ。
+    /// 内側のループほど後ろにある
+    breakの飛び先: Vec<(String, usize)>,
+    continueの飛び先: Vec<(String, usize)>,
+    /// 今いる switch 文の case や default のラベルに付ける接頭辞。内側の switch 文ほど後ろにある
+    switchのラベル: Vec<String>,
+    /// goto 文の飛び先のラベルごとに、その位置での `保存したスタックポインタ`
+    ラベルの位置で保存したスタックポインタ: HashMap<String, Vec<i32>>,
+    /// 可変長引数をとる関数なら、va_start で va_list に書き込む値
+    可変長引数: Option<可変長引数の位置>,
+}
+
+/// 可変長引数をとる関数のスタックフレームの中で、可変長引数が置かれている位置
+#[derive(Debug, Clone, Copy)]
+struct 可変長引数の位置 {
+    /// 引数レジスタを全て保存した領域（レジスタ保存領域）の、rbp からのオフセット。
+    /// 先頭から 6 つの汎用レジスタを 8 バイトずつ、続いて 8 つの xmm レジスタを 16 バイトずつ保存する
+    レジスタ保存領域: i32,
+    /// 名前付きの仮引数で使い終わった分を飛ばした、レジスタ保存領域の中の汎用レジスタと xmm レジスタの位置
+    gp_offset: u32,
+    fp_offset: u32,
+    /// スタックで渡された可変長引数のうち、最初のものの rbp からのオフセット
+    スタック引数: i32,
 }
 
+/// レジスタ保存領域の中で、汎用レジスタを保存している部分の大きさ
+const 汎用レジスタの保存領域の大きさ: u32 = 6 * WORD_SIZE_AS_U32;
+/// レジスタ保存領域全体の大きさ
+const レジスタ保存領域の大きさ: u32 = 汎用レジスタの保存領域の大きさ + 8 * 16;
+
 impl<'a> FunctionGen<'a> {
     pub fn exprを左辺値として評価してアドレスをrdiレジスタへ(
         &mut self,
@@ -172,31 +482,16 @@ impl<'a> FunctionGen<'a> {
                 ident,
                 pos: _,
                 local_var_id: Some(local_var_id),
-                ..
+                typ,
             } => {
-                // If the name exists but the id does not match, report
-                let candidates = self
-                    .local_var_table
-                    .offsets
-                    .iter()
-                    .filter(|(i, _, _)| i == &ident.to_owned())
-                    .collect::<Vec<_>>();
-
-                let offset: i32 = self
-                    .local_var_table
-                    .offsets
-                    .iter()
-                    .find(|(i, l, _)| i == &ident.to_owned() && l == &local_var_id.to_owned())
-                    .unwrap_or_else(|| {
-                        panic!(
-                            "関数 {} 内で、変数 {ident} は id {local_var_id} で参照されているが、id の候補は {candidates:?} です",
-                            self.function_name
-                        )
-                    })
-                    .2;
+                let offset = self.ローカル変数のオフセット(ident, *local_var_id);
                 buf.append(rbpをプッシュ());
                 buf.append(rdiへとポップ());
                 buf.append(rdiから即値を引く(offset));
+                // 可変長配列の変数には、実行時に確保した領域の先頭のアドレスが入っている
+                if let Type::VarLenArr(..) = typ.unqualified() {
+                    buf.append(rdiを間接参照());
+                }
             }
             Expr::Identifier {
                 ident,
@@ -204,7 +499,7 @@ impl<'a> FunctionGen<'a> {
                 local_var_id: None,
                 ..
             } => {
-                panic!("グローバル変数 {ident} は今のところ左辺値として使用できません");
+                buf.append(グローバル変数のアドレスをediに代入(ident));
             }
             Expr::UnaryExpr {
                 op: UnaryOp::Deref,
@@ -213,17 +508,155 @@ impl<'a> FunctionGen<'a> {
             } => {
                 self.exprを評価してediレジスタへ(buf, expr);
             }
+            // 複合リテラルは、評価されるたびに名前のないローカル変数を初期化し直して、それを指す
+            Expr::CompoundLiteral {
+                ident,
+                local_var_id,
+                initializer,
+                pos,
+                typ,
+            } => {
+                let var = Expr::Identifier {
+                    ident: ident.clone(),
+                    pos: *pos,
+                    typ: typ.clone(),
+                    local_var_id: Some(*local_var_id),
+                };
+                let size = typ.sizeof(self.struct_names);
+                self.ローカル変数を初期化(buf, &var, size, initializer);
+                self.exprを左辺値として評価してアドレスをrdiレジスタへ(
+                    buf, &var,
+                );
+            }
+            // 関数の戻り値などの構造体の値は、一時領域に置かれているので、そのアドレスを使う
+            e if matches!(e.typ(), Type::Struct { .. }) => {
+                self.exprを評価してediレジスタへ(buf, e);
+            }
             e => panic!("代入式の左辺に左辺値以外が来ています: {e:?}"),
         }
     }
 
+    fn 新しいラベル(&mut self, 種類: &str) -> String {
+        self.ラベルの個数 += 1;
+        format!("{種類} {}", self.ラベルの個数)
+    }
+
+    /// ループの本体を生成する前に呼び、break と continue の飛び先のラベルを返す
+    fn ループに入る(&mut self) -> (String, String) {
+        let break_label = self.新しいラベル("break");
+        let continue_label = self.新しいラベル("continue");
+        let depth = self.保存したスタックポインタ.len();
+        self.breakの飛び先.push((break_label.clone(), depth));
+        self.continueの飛び先.push((continue_label.clone(), depth));
+        (break_label, continue_label)
+    }
+
+    fn ループを出る(&mut self) {
+        self.breakの飛び先.pop();
+        self.continueの飛び先.pop();
+    }
+
+    /// goto 文より後ろにあるラベルへも飛べるよう、関数の本体を生成する前に、
+    /// それぞれのラベルの位置での `保存したスタックポインタ` を調べておく
+    fn ラベルを調べる(&mut self, stmt: &StatementOrDeclaration, saved: &mut Vec<i32>) {
+        let StatementOrDeclaration::Statement(stmt) = stmt else {
+            return;
+        };
+        match stmt {
+            Statement::If { then, else_, .. } => {
+                self.ラベルを調べる(then, saved);
+                if let Some(else_) = else_ {
+                    self.ラベルを調べる(else_, saved);
+                }
+            }
+            Statement::For { init, body, .. } => {
+                self.ラベルを調べる(init, saved);
+                self.ラベルを調べる(body, saved);
+            }
+            Statement::While { body, .. }
+            | Statement::DoWhile { body, .. }
+            | Statement::Switch { body, .. }
+            | Statement::Case { body, .. }
+            | Statement::Default { body, .. } => {
+                self.ラベルを調べる(body, saved);
+            }
+            Statement::Labeled { name, body, .. } => {
+                self.ラベルの位置で保存したスタックポインタ
+                    .insert(name.clone(), saved.clone());
+                self.ラベルを調べる(body, saved);
+            }
+            Statement::Block {
+                statements,
+                saved_stack_pointer,
+                ..
+            } => {
+                let saved_offset = saved_stack_pointer.map(|id| {
+                    self.ローカル変数のオフセット(隠れたローカル変数の名前, id)
+                });
+                saved.extend(saved_offset);
+                for stmt in statements {
+                    self.ラベルを調べる(stmt, saved);
+                }
+                if saved_offset.is_some() {
+                    saved.pop();
+                }
+            }
+            Statement::Expr { .. }
+            | Statement::Throw { .. }
+            | Statement::Return { .. }
+            | Statement::Break { .. }
+            | Statement::Continue { .. }
+            | Statement::Goto { .. }
+            | Statement::BuiltinPopulateArgcArgv { .. } => {}
+        }
+    }
+
+    /// break や continue、goto で、`label` へ飛ぶ。
+    /// `depth` 番目より内側の、可変長配列を宣言しているブロックからは抜け出す
+    fn ブロックの外へのジャンプ(&self, (label, depth): &(String, usize)) -> Buf {
+        let mut buf = Buf::new();
+        // 抜けるブロックの中で確保された可変長配列の領域を解放するため、
+        // 抜けるブロックのうち一番外側のものに入ったときのスタックポインタに戻す
+        if let Some(offset) = self.保存したスタックポインタ.get(*depth) {
+            buf.append(rbpにoffsetを足した位置の値をraxに読み込む(
+                -offset,
+            ));
+            buf.append(raxをrspにコピー());
+        }
+        buf.append(ラベルへjmp(label));
+        buf
+    }
+
+    fn ローカル変数のオフセット(&self, ident: &str, local_var_id: u64) -> i32 {
+        self.local_var_table
+            .offsets
+            .iter()
+            .find(|(i, l, _)| i == ident && *l == local_var_id)
+            .unwrap_or_else(|| {
+                // If the name exists but the id does not match, report
+                let candidates = self
+                    .local_var_table
+                    .offsets
+                    .iter()
+                    .filter(|(i, _, _)| i == ident)
+                    .collect::<Vec<_>>();
+                panic!(
+                    "関数 {} 内で、変数 {ident} は id {local_var_id} で参照されているが、id の候補は {candidates:?} です",
+                    self.function_name
+                )
+            })
+            .2
+    }
+
     pub fn statement_or_declarationを評価(
         &mut self,
         stmt_or_decl: &StatementOrDeclaration,
     ) -> Buf {
         let stmt = match stmt_or_decl {
             StatementOrDeclaration::Statement(stmt) => stmt.to_owned(),
-            StatementOrDeclaration::Declaration { .. } => {
+            StatementOrDeclaration::Declaration { .. }
+            | StatementOrDeclaration::TypeDeclaration
+            | StatementOrDeclaration::StaticDeclaration => {
                 return Buf::new(); // declaration disappears in codegen
             }
             StatementOrDeclaration::DeclarationWithInitializer {
@@ -232,26 +665,79 @@ impl<'a> FunctionGen<'a> {
                 typ_and_size,
                 initializer,
             } => {
-                // compile to code that assigns the initializer to the variable
-                Statement::Expr {
-                    expr: Box::new(Expr::BinaryExpr {
-                        op: BinaryOp::Assign,
-                        左辺: Box::new(Expr::Identifier {
-                            ident: name.clone(),
+                let var = Expr::Identifier {
+                    ident: name.clone(),
+                    pos: 0, // pos is not used in codegen
+                    typ: typ_and_size.typ.clone(),
+                    local_var_id: Some(*id),
+                };
+                let mut buf = Buf::new();
+                self.ローカル変数を初期化(&mut buf, &var, typ_and_size.size, initializer);
+                return buf;
+            }
+            StatementOrDeclaration::VarLenArrDeclaration {
+                name,
+                id,
+                typ,
+                exprs,
+            } => {
+                let mut buf = Buf::new();
+                for expr in exprs {
+                    self.exprを評価してediレジスタへ(&mut buf, expr);
+                }
+                if let Type::VarLenArr(_, size_var) = typ.unqualified() {
+                    self.exprを評価してediレジスタへ(
+                        &mut buf,
+                        &Expr::Alloca {
+                            size: Box::new(隠れたローカル変数(*size_var, Type::Int, 0)),
                             pos: 0, // pos is not used in codegen
-                            typ: typ_and_size.typ.clone(),
-                            local_var_id: Some(*id),
-                        }),
-                        右辺: initializer.clone(),
-                        op_pos: 0, // op_pos is not used in codegen
-                        typ: typ_and_size.typ.clone(),
-                    }),
-                    semicolon_pos: 0, // semicolon_pos is not used in codegen
+                            typ: Type::Ptr(Box::new(Type::Void)),
+                        },
+                    );
+                    let offset = self.ローカル変数のオフセット(name, *id);
+                    buf.append(rbpにoffsetを足した位置にrdiを代入(-offset));
                 }
+                return buf;
             }
         };
         self.statementを評価(&stmt)
     }
+    /// 初期化子で明示されなかった部分をゼロで埋めてから、各部分に値を代入する
+    fn ローカル変数を初期化(
+        &mut self,
+        buf: &mut Buf,
+        var: &Expr,
+        size: i32,
+        initializer: &[InitializedField],
+    ) {
+        // 初期化子が変数全体を一つの値で初期化するのでなければ、まず全体をゼロで埋める
+        let covers_whole = matches!(
+            initializer,
+            [field] if field.offset == 0 && field.bit_field.is_none() && field.typ == var.typ()
+        );
+        if !covers_whole {
+            self.exprを左辺値として評価してアドレスをrdiレジスタへ(buf, var);
+            buf.append(eaxに即値をセット(0));
+            buf.append(ecxに即値をセット(size as u32));
+            buf.append(rep_stosb());
+        }
+
+        // compile to code that assigns each initializer to the corresponding part of the variable
+        for field in initializer {
+            let 左辺 = 初期化される部分の左辺値(var, field);
+            self.exprを評価してediレジスタへ(
+                buf,
+                &Expr::BinaryExpr {
+                    op: BinaryOp::Assign,
+                    typ: 左辺.typ(),
+                    左辺,
+                    右辺: field.expr.clone(),
+                    op_pos: 0, // op_pos is not used in codegen
+                },
+            );
+        }
+    }
+
     pub fn statementを評価(&mut self, stmt: &Statement) -> Buf {
         match stmt {
             Statement::BuiltinPopulateArgcArgv { .. } => {
@@ -287,9 +773,22 @@ impl<'a> FunctionGen<'a> {
                 let mut buf = Buf::new();
                 self.exprを評価してediレジスタへ(&mut buf, expr);
 
+                if let Type::Struct { .. } = return_type {
+                    self.構造体の値をrdiが指す位置から返す(&mut buf, return_type);
+                    buf.append(leave_ret());
+                    return buf;
+                }
+
+                if return_type.is_floating() {
+                    buf.append(rdiをxmm0にコピー());
+                    buf.append(leave_ret());
+                    return buf;
+                }
+
                 match return_type.sizeof_primitive("return") {
                     8 => buf.append(rdiをraxにコピー()),
                     4 => buf.append(ediをeaxにコピー()),
+                    1 if return_type.is_unsigned() => buf.append(dilをeaxにゼロ拡張してmov()),
                     1 => buf.append(dilをeaxに符号拡張してmov()),
                     _ => panic!("return の型のサイズがよろしくない"),
                 }
@@ -360,7 +859,10 @@ impl<'a> FunctionGen<'a> {
                 // const SHORTER_JMP: usize = 2;
                 const LONGER_JMP: usize = 5;
 
+                // break は BREAKING に、continue は CONTINUING に飛ぶ
+                let (break_label, continue_label) = self.ループに入る();
                 let body_buf = self.statement_or_declarationを評価(body.as_ref());
+                self.ループを出る();
 
                 let mut cond_buf = Buf::new();
                 self.exprを評価してediレジスタへ(&mut cond_buf, cond);
@@ -376,7 +878,10 @@ impl<'a> FunctionGen<'a> {
                 let buf_len = i32::try_from(-((buf.len() + LONGER_JMP) as i64)).unwrap_or_else(
                 |_| panic!("while 文の中でジャンプするためのバッファの長さが i32 に収まりません。バッファの長さは {}、中身は 0x[{}] です", buf.len(), buf.to_vec().iter().map(|a| format!("{a:02x}")).collect::<Vec<_>>().join(" "))
             );
-                buf.join(jmp(buf_len))
+                ラベル(&continue_label)
+                    .join(buf)
+                    .join(jmp(buf_len))
+                    .join(ラベル(&break_label))
             }
             Statement::For {
                 init,
@@ -385,64 +890,290 @@ impl<'a> FunctionGen<'a> {
                 body,
                 pos,
             } => {
-                let body: Box<StatementOrDeclaration> =
-                    Box::new(StatementOrDeclaration::Statement(Statement::Block {
-                        statements: vec![
-                            Some(body.as_ref().clone()),
-                            update.clone().map(|update| {
-                                StatementOrDeclaration::Statement(Statement::Expr {
-                                    expr: update,
-                                    semicolon_pos: *pos,
-                                })
-                            }),
-                        ]
-                        .into_iter()
-                        .flatten()
-                        .collect::<Vec<_>>(),
-                        pos: *pos,
-                    }));
-                let statements: Vec<StatementOrDeclaration> = vec![
-                    *init.clone(),
-                    StatementOrDeclaration::Statement(Statement::While {
-                        cond: cond.clone().unwrap_or_else(|| {
-                            Box::new(Expr::Numeric {
-                                val: 1,
-                                pos: *pos,
-                                typ: Type::Int,
-                            })
-                        }),
-                        body,
-                        pos: *pos,
-                    }),
-                ];
-                self.statementを評価(&Statement::Block {
-                    statements,
-                    pos: *pos,
-                })
+                // init_buf
+                // CONDITION:
+                // cond_buf
+                // je BREAKING
+                // body_buf
+                // CONTINUING:
+                // update_buf
+                // jmp CONDITION
+                // BREAKING:
+                //
+                // という構造。jmp は while 文と同じく、常に長い方でエンコードする
+                const LONGER_JMP: usize = 5;
+
+                let init_buf = self.statement_or_declarationを評価(init.as_ref());
+
+                let (break_label, continue_label) = self.ループに入る();
+                let body_buf = self.statement_or_declarationを評価(body.as_ref());
+                self.ループを出る();
+
+                let update_buf = update.as_ref().map_or_else(Buf::new, |update| {
+                    self.statementを評価(&Statement::Expr {
+                        expr: update.clone(),
+                        semicolon_pos: *pos,
+                    })
+                });
+
+                let mut cond_buf = Buf::new();
+                if let Some(cond) = cond {
+                    self.exprを評価してediレジスタへ(&mut cond_buf, cond);
+                    match cond.typ().sizeof_primitive("if") {
+                        8 => cond_buf.append(rdiが0かを確認()),
+                        4 => cond_buf.append(ediが0かを確認()),
+                        1 => cond_buf.append(dilが0かを確認()),
+                        _ => panic!("条件式の型のサイズがよろしくない: {cond:?}"),
+                    }
+                    cond_buf.append(je(i32::try_from(
+                        body_buf.len() + update_buf.len() + LONGER_JMP,
+                    )
+                    .expect("for 文の本体をコンパイルした長さが i32 に収まりません")));
+                }
+
+                let loop_len = cond_buf.len() + body_buf.len() + update_buf.len() + LONGER_JMP;
+                init_buf
+                    .join(cond_buf)
+                    .join(body_buf)
+                    .join(ラベル(&continue_label))
+                    .join(update_buf)
+                    .join(jmp(i32::try_from(-(loop_len as i64))
+                        .expect("for 文をコンパイルした長さが i32 に収まりません")))
+                    .join(ラベル(&break_label))
             }
-            Statement::Block { statements, .. } => {
-                statements.iter().fold(Buf::new(), |acc, stmt| {
-                    acc.join(self.statement_or_declarationを評価(stmt))
-                })
+            Statement::DoWhile { body, cond, .. } => {
+                // BODY:
+                // body_buf
+                // CONTINUING:
+                // cond_buf
+                // je BREAKING
+                // jmp BODY
+                // BREAKING:
+                const LONGER_JMP: usize = 5;
+
+                let (break_label, continue_label) = self.ループに入る();
+                let body_buf = self.statement_or_declarationを評価(body.as_ref());
+                self.ループを出る();
+
+                let mut cond_buf = Buf::new();
+                self.exprを評価してediレジスタへ(&mut cond_buf, cond);
+                match cond.typ().sizeof_primitive("if") {
+                    8 => cond_buf.append(rdiが0かを確認()),
+                    4 => cond_buf.append(ediが0かを確認()),
+                    1 => cond_buf.append(dilが0かを確認()),
+                    _ => panic!("条件式の型のサイズがよろしくない: {cond:?}"),
+                }
+                cond_buf.append(je(LONGER_JMP as i32));
+
+                let loop_len = body_buf.len() + cond_buf.len() + LONGER_JMP;
+                body_buf
+                    .join(ラベル(&continue_label))
+                    .join(cond_buf)
+                    .join(jmp(i32::try_from(-(loop_len as i64))
+                        .expect("do 文をコンパイルした長さが i32 に収まりません")))
+                    .join(ラベル(&break_label))
+            }
+            Statement::Switch {
+                cond,
+                body,
+                cases,
+                has_default,
+                ..
+            } => {
+                let switch_label = self.新しいラベル("switch");
+                let break_label = format!("{switch_label} break");
+                let default_label = if *has_default {
+                    format!("{switch_label} default")
+                } else {
+                    break_label.clone()
+                };
+
+                let mut buf = Buf::new();
+                self.exprを評価してediレジスタへ(&mut buf, cond);
+                buf.append(caseへ分岐(&switch_label, cases, &default_label));
+
+                self.switchのラベル.push(switch_label);
+                self.breakの飛び先
+                    .push((break_label.clone(), self.保存したスタックポインタ.len()));
+                buf.append(self.statement_or_declarationを評価(body.as_ref()));
+                self.breakの飛び先.pop();
+                self.switchのラベル.pop();
+
+                buf.join(ラベル(&break_label))
+            }
+            Statement::Case { value, body, .. } => {
+                let switch_label = self
+                    .switchのラベル
+                    .last()
+                    .expect("case が switch 文の外にあります");
+                ラベル(&format!("{switch_label} case {value}"))
+                    .join(self.statement_or_declarationを評価(body.as_ref()))
+            }
+            Statement::Default { body, .. } => {
+                let switch_label = self
+                    .switchのラベル
+                    .last()
+                    .expect("default が switch 文の外にあります");
+                ラベル(&format!("{switch_label} default"))
+                    .join(self.statement_or_declarationを評価(body.as_ref()))
+            }
+            Statement::Labeled { name, body, .. } => ラベル(&format!("label {name}"))
+                .join(self.statement_or_declarationを評価(body.as_ref())),
+            Statement::Goto { name, .. } => {
+                // 飛び先のラベルを囲んでいないブロックからは抜け出す
+                let depth = self
+                    .保存したスタックポインタ
+                    .iter()
+                    .zip(&self.ラベルの位置で保存したスタックポインタ[name])
+                    .take_while(|(here, there)| here == there)
+                    .count();
+                self.ブロックの外へのジャンプ(&(format!("label {name}"), depth))
+            }
+            Statement::Break { .. } => {
+                let target = self
+                    .breakの飛び先
+                    .last()
+                    .expect("break 文がループの外にあります");
+                self.ブロックの外へのジャンプ(target)
+            }
+            Statement::Continue { .. } => {
+                let target = self
+                    .continueの飛び先
+                    .last()
+                    .expect("continue 文がループの外にあります");
+                self.ブロックの外へのジャンプ(target)
+            }
+            Statement::Block {
+                statements,
+                saved_stack_pointer,
+                ..
+            } => {
+                let mut buf = Buf::new();
+                let saved_offset = saved_stack_pointer.map(|id| {
+                    self.ローカル変数のオフセット(隠れたローカル変数の名前, id)
+                });
+                if let Some(offset) = saved_offset {
+                    buf.append(rspをrdiにコピー());
+                    buf.append(rbpにoffsetを足した位置にrdiを代入(-offset));
+                    self.保存したスタックポインタ.push(offset);
+                }
+                for stmt in statements {
+                    buf.append(self.statement_or_declarationを評価(stmt));
+                }
+                // ブロックの中で確保した可変長配列の領域を解放する
+                if let Some(offset) = saved_offset {
+                    self.保存したスタックポインタ.pop();
+                    buf.append(rbpにoffsetを足した位置の値をraxに読み込む(
+                        -offset,
+                    ));
+                    buf.append(raxをrspにコピー());
+                }
+                buf
             }
         }
     }
 
     #[allow(clippy::too_many_lines)]
     pub fn exprを評価してediレジスタへ(&mut self, buf: &mut Buf, expr: &Expr) {
-        if matches!(expr.typ(), Type::Arr(_, _)) && !matches!(expr, Expr::Call { .. })
+        if matches!(
+            expr.typ(),
+            Type::Arr(_, _) | Type::IncompleteArr(_) | Type::VarLenArr(..)
+        ) && !matches!(expr, Expr::Call { .. })
         /* not a builtin string literal */
         {
             self.exprを左辺値として評価してアドレスをrdiレジスタへ(buf, expr);
             return;
         }
 
+        // 構造体の値は、それが置かれている位置のアドレスとして rdi に入れる
+        if matches!(expr.typ(), Type::Struct { .. })
+            && matches!(
+                expr,
+                Expr::Identifier { .. }
+                    | Expr::CompoundLiteral { .. }
+                    | Expr::UnaryExpr {
+                        op: UnaryOp::Deref,
+                        ..
+                    }
+            )
+        {
+            self.exprを左辺値として評価してアドレスをrdiレジスタへ(buf, expr);
+            return;
+        }
+
         match expr {
             Expr::NullPtr { .. } => {
                 // x64 なので、edi に 0 をセットすると rdi に 0 がセットされる
                 buf.append(ediに代入(0));
             }
 
+            // 浮動小数点数の値も、そのビット列を rdi (float なら edi) に入れて扱う
+            Expr::FloatingNumeric { bits, typ, .. } => {
+                if *typ == Type::Double {
+                    buf.append(rdiに代入(*bits));
+                } else {
+                    buf.append(ediに代入(*bits as u32));
+                }
+            }
+
+            Expr::BinaryExpr {
+                op: op @ (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div),
+                左辺,
+                右辺,
+                typ,
+                ..
+            } if typ.is_floating() => {
+                let 倍精度 = *typ == Type::Double;
+                self.exprを評価してediレジスタへ(buf, 左辺);
+                buf.append(rdiをプッシュ());
+                self.stack_size += WORD_SIZE_AS_U32;
+                self.exprを評価してediレジスタへ(buf, 右辺);
+                buf.append(rdiをxmm1にコピー());
+                buf.append(raxへとポップ());
+                self.stack_size -= WORD_SIZE_AS_U32;
+                buf.append(raxをxmm0にコピー());
+                match op {
+                    BinaryOp::Add => buf.append(xmm0にxmm1を足す(倍精度)),
+                    BinaryOp::Sub => buf.append(xmm0からxmm1を引く(倍精度)),
+                    BinaryOp::Mul => buf.append(xmm0にxmm1を掛ける(倍精度)),
+                    BinaryOp::Div => buf.append(xmm0をxmm1で割る(倍精度)),
+                    _ => unreachable!(),
+                }
+                buf.append(xmm0をrdiにコピー());
+            }
+
+            Expr::BinaryExpr {
+                op:
+                    op @ (BinaryOp::LessThan
+                    | BinaryOp::LessThanOrEqual
+                    | BinaryOp::Equal
+                    | BinaryOp::NotEqual),
+                左辺,
+                右辺,
+                ..
+            } if 左辺.typ().is_floating() => {
+                let 倍精度 = 左辺.typ() == Type::Double;
+                self.exprを評価してediレジスタへ(buf, 左辺);
+                buf.append(rdiをプッシュ());
+                self.stack_size += WORD_SIZE_AS_U32;
+                self.exprを評価してediレジスタへ(buf, 右辺);
+                buf.append(rdiをxmm1にコピー());
+                buf.append(raxへとポップ());
+                self.stack_size -= WORD_SIZE_AS_U32;
+                buf.append(raxをxmm0にコピー());
+                // NaN との比較は != だけが真になる述語を使う
+                let 述語 = match op {
+                    BinaryOp::Equal => 0,
+                    BinaryOp::LessThan => 1,
+                    BinaryOp::LessThanOrEqual => 2,
+                    BinaryOp::NotEqual => 4,
+                    _ => unreachable!(),
+                };
+                buf.append(xmm0とxmm1を比較した結果のマスクをxmm0にセット(倍精度, 述語));
+                buf.append(xmm0をrdiにコピー());
+                buf.append(ediと即値のandを取る(1));
+            }
+
             Expr::BinaryExpr {
                 op: BinaryOp::LogicalOr,
                 左辺,
@@ -520,8 +1251,61 @@ impl<'a> FunctionGen<'a> {
                 buf.append(cond_buf.join(then_buf).join(else_buf))
             }
 
-            Expr::DecayedArr { expr, .. } => {
-                self.exprを評価してediレジスタへ(buf, expr);
+            Expr::Conditional {
+                cond, then, else_, ..
+            } => {
+                let mut else_buf = Buf::new();
+                self.exprを評価してediレジスタへ(&mut else_buf, else_);
+
+                let mut then_buf = Buf::new();
+                self.exprを評価してediレジスタへ(&mut then_buf, then);
+                then_buf.append(jmp(i32::try_from(else_buf.len()).expect(
+                    "条件演算子の第三オペランドをコンパイルした長さが i32 に収まりません",
+                )));
+
+                let mut cond_buf = Buf::new();
+                self.exprを評価してediレジスタへ(&mut cond_buf, cond);
+                // 浮動小数点数の -0.0 も偽になるよう、いったん _Bool に変換してから判定する
+                cond_buf.append(値を真偽値に正規化(&cond.typ()));
+                cond_buf.append(ediが0かを確認());
+                cond_buf.append(je(i32::try_from(then_buf.len()).expect(
+                    "条件演算子の第二オペランドをコンパイルした長さが i32 に収まりません",
+                )));
+
+                buf.append(cond_buf.join(then_buf).join(else_buf));
+            }
+
+            Expr::DecayedArr { expr, .. } => {
+                self.exprを評価してediレジスタへ(buf, expr);
+            }
+            Expr::BitField {
+                unit, bit_field, ..
+            } => {
+                self.exprを左辺値として評価してアドレスをrdiレジスタへ(
+                    buf, unit,
+                );
+                記憶単位をrdiが指す位置から読み込む(buf, &unit.typ());
+                buf.append(ビットフィールドの値を取り出す(*bit_field));
+            }
+            Expr::BinaryExpr {
+                op, 左辺, 右辺, ..
+            } if matches!(**左辺, Expr::BitField { .. })
+                && (*op == BinaryOp::Assign || op.compound_assign_base().is_some()) =>
+            {
+                let Expr::BitField {
+                    unit, bit_field, ..
+                } = &**左辺
+                else {
+                    unreachable!()
+                };
+                self.ビットフィールドへの代入を評価してediレジスタへ(
+                    buf,
+                    *op,
+                    unit,
+                    *bit_field,
+                    &左辺.typ(),
+                    右辺,
+                );
             }
             Expr::BinaryExpr {
                 op: BinaryOp::Assign,
@@ -539,6 +1323,10 @@ impl<'a> FunctionGen<'a> {
 
                 buf.append(raxへとポップ()); // 左辺のアドレス
                 self.stack_size -= WORD_SIZE_AS_U32;
+                if let Type::Struct { .. } = typ {
+                    self.rdiが指す構造体をraxが指す位置にコピー(buf, typ);
+                    return;
+                }
                 match typ.sizeof_primitive("a") {
                     8 => buf.append(raxが指す位置にrdiを代入()),
                     4 => buf.append(raxが指す位置にediを代入()),
@@ -551,13 +1339,27 @@ impl<'a> FunctionGen<'a> {
             }
 
             Expr::BinaryExpr {
-                op: BinaryOp::AddAssign,
+                op:
+                    op @ (BinaryOp::AddAssign
+                    | BinaryOp::SubAssign
+                    | BinaryOp::MulAssign
+                    | BinaryOp::DivAssign
+                    | BinaryOp::RemainderAssign
+                    | BinaryOp::BitAndAssign
+                    | BinaryOp::BitOrAssign
+                    | BinaryOp::BitXorAssign
+                    | BinaryOp::LeftShiftAssign
+                    | BinaryOp::RightShiftAssign),
                 op_pos: _,
                 左辺,
                 右辺,
                 typ,
             } => {
                 self.exprを評価してediレジスタへ(buf, 右辺);
+                if let Type::Ptr(_) = typ.unqualified() {
+                    // ポインタに足し引きするバイト数を、符号に応じて 64 ビットに拡張する
+                    buf.append(rdiの値を型変換(&右辺.typ(), typ.unqualified()));
+                }
                 buf.append(rdiをプッシュ());
                 self.stack_size += WORD_SIZE_AS_U32;
 
@@ -569,7 +1371,7 @@ impl<'a> FunctionGen<'a> {
                 buf.append(rdiをプッシュ()); // 左辺のアドレス：rdi
                 self.stack_size += WORD_SIZE_AS_U32;
 
-                buf.append(rdiを間接参照()); // 左辺の値：rdi
+                buf.append(rdiが指す値を読み込む(typ)); // 左辺の値：rdi
 
                 buf.append(rsiへとポップ()); // 左辺のアドレス：rsi
                 self.stack_size -= WORD_SIZE_AS_U32;
@@ -577,7 +1379,7 @@ impl<'a> FunctionGen<'a> {
                 buf.append(raxへとポップ()); // 右辺の値：rax
                 self.stack_size -= WORD_SIZE_AS_U32;
 
-                buf.append(rdiにraxを足し合わせる());
+                buf.append(複合代入の演算(*op, typ, &右辺.typ()));
                 buf.append(rsiをraxにコピー());
 
                 match typ.sizeof_primitive("c") {
@@ -591,60 +1393,26 @@ impl<'a> FunctionGen<'a> {
                 };
             }
 
-            Expr::BinaryExpr {
-                op: BinaryOp::SubAssign,
-                op_pos: _,
-                左辺,
-                右辺,
-                typ,
-            } => {
-                self.exprを評価してediレジスタへ(buf, 右辺);
-                buf.append(rdiをプッシュ());
-                self.stack_size += WORD_SIZE_AS_U32;
-
-                // スタックトップ：右辺
-
-                self.exprを左辺値として評価してアドレスをrdiレジスタへ(
-                    buf, 左辺,
-                );
-                buf.append(rdiをプッシュ()); // 左辺のアドレス：rdi
-                self.stack_size += WORD_SIZE_AS_U32;
-
-                buf.append(rdiを間接参照()); // 左辺の値：rdi
-
-                buf.append(rsiへとポップ()); // 左辺のアドレス：rsi
-                self.stack_size -= WORD_SIZE_AS_U32;
-
-                buf.append(raxへとポップ()); // 右辺の値：rax
-                self.stack_size -= WORD_SIZE_AS_U32;
-
-                buf.append(rdiからraxを減じる());
-                buf.append(rsiをraxにコピー());
-
-                match typ.sizeof_primitive("e") {
-                    8 => buf.append(raxが指す位置にrdiを代入()),
-                    4 => buf.append(raxが指す位置にediを代入()),
-                    1 => buf.append(raxが指す位置にdilを代入()),
-                    _ => panic!(
-                        "size が {} な型への代入はできません",
-                        typ.sizeof_primitive("f")
-                    ),
-                };
+            Expr::Alloca { size, .. } => {
+                self.exprを評価してediレジスタへ(buf, size);
+                buf.append(ediをrdiに符号拡張してmov());
+                // rsp を 16 バイト境界に揃えたままにするため、大きさを 16 の倍数に切り上げる
+                buf.append(rdiから即値を引く(-15));
+                buf.append(ediと即値のandを取る(!15));
+                // 式の途中で積まれている値（保存した rbp より下にあるもの）は、新しい rsp の位置まで下にずらす。
+                // rep movsb を終えた rdi は、ずらした値のすぐ上、つまり確保した領域の先頭を指している
+                let 積まれている値の大きさ = self.stack_size - WORD_SIZE_AS_U32;
+                buf.append(rspをrsiにコピー());
+                buf.append(rspからrdiを引く());
+                buf.append(rspをrdiにコピー());
+                buf.append(ecxに即値をセット(積まれている値の大きさ));
+                buf.append(rep_movsb());
             }
-
-            Expr::Identifier { .. } => {
+            Expr::Identifier { .. } | Expr::CompoundLiteral { .. } => {
                 self.exprを左辺値として評価してアドレスをrdiレジスタへ(
                     buf, expr,
                 );
-                match expr.typ().sizeof_primitive("k") {
-                    8 => buf.append(rdiを間接参照()),
-                    4 => buf.append(rdiを間接参照()),
-                    1 => buf.append(rdiをmovzxで間接参照()),
-                    _ => panic!(
-                        "size が {} な型の参照はできません",
-                        expr.typ().sizeof_primitive("l")
-                    ),
-                };
+                buf.append(rdiが指す値を読み込む(&expr.typ()));
             }
             Expr::BinaryExpr {
                 op: BinaryOp::AndThen,
@@ -661,12 +1429,16 @@ impl<'a> FunctionGen<'a> {
                 op_pos: _,
                 左辺,
                 右辺,
-                typ: _,
+                typ,
             } => {
                 self.exprを評価してediレジスタへ(buf, 左辺);
                 buf.append(rdiをプッシュ());
                 self.stack_size += WORD_SIZE_AS_U32;
                 self.exprを評価してediレジスタへ(buf, 右辺);
+                if matches!(typ, Type::Ptr(_)) {
+                    // ポインタに足し引きするバイト数を、符号に応じて 64 ビットに拡張する
+                    buf.append(rdiの値を型変換(&右辺.typ(), typ));
+                }
                 buf.append(rdiをプッシュ());
                 self.stack_size += WORD_SIZE_AS_U32;
                 buf.append(raxへとポップ());
@@ -680,12 +1452,16 @@ impl<'a> FunctionGen<'a> {
                 op_pos: _,
                 左辺,
                 右辺,
-                typ: _,
+                typ,
             } => {
                 self.exprを評価してediレジスタへ(buf, 左辺);
                 buf.append(rdiをプッシュ());
                 self.stack_size += WORD_SIZE_AS_U32;
                 self.exprを評価してediレジスタへ(buf, 右辺);
+                if matches!(typ, Type::Ptr(_)) {
+                    // ポインタに足し引きするバイト数を、符号に応じて 64 ビットに拡張する
+                    buf.append(rdiの値を型変換(&右辺.typ(), typ));
+                }
                 buf.append(rdiをプッシュ());
                 self.stack_size += WORD_SIZE_AS_U32;
                 buf.append(raxへとポップ());
@@ -719,7 +1495,7 @@ impl<'a> FunctionGen<'a> {
                 op_pos: _,
                 左辺,
                 右辺,
-                typ: _,
+                typ,
             } => {
                 self.exprを評価してediレジスタへ(buf, 左辺);
                 buf.append(rdiをプッシュ());
@@ -734,8 +1510,13 @@ impl<'a> FunctionGen<'a> {
                 buf.append(raxへとポップ());
                 self.stack_size -= WORD_SIZE_AS_U32;
 
-                buf.append(eaxの符号ビットをedxへ拡張());
-                buf.append(edx_eaxをediで割る_商はeaxに_余りはedxに());
+                if *typ == Type::UInt {
+                    buf.append(edxをゼロクリア());
+                    buf.append(edx_eaxをediで符号なしで割る_商はeaxに_余りはedxに());
+                } else {
+                    buf.append(eaxの符号ビットをedxへ拡張());
+                    buf.append(edx_eaxをediで割る_商はeaxに_余りはedxに());
+                }
 
                 // 結果は eax レジスタに入るので、ediに移し替える
                 buf.append(raxをプッシュ());
@@ -746,7 +1527,7 @@ impl<'a> FunctionGen<'a> {
                 op_pos: _,
                 左辺,
                 右辺,
-                typ: _,
+                typ,
             } => {
                 self.exprを評価してediレジスタへ(buf, 左辺);
                 buf.append(rdiをプッシュ());
@@ -761,13 +1542,42 @@ impl<'a> FunctionGen<'a> {
                 buf.append(raxへとポップ());
                 self.stack_size -= WORD_SIZE_AS_U32;
 
-                buf.append(eaxの符号ビットをedxへ拡張());
-                buf.append(edx_eaxをediで割る_商はeaxに_余りはedxに());
+                if *typ == Type::UInt {
+                    buf.append(edxをゼロクリア());
+                    buf.append(edx_eaxをediで符号なしで割る_商はeaxに_余りはedxに());
+                } else {
+                    buf.append(eaxの符号ビットをedxへ拡張());
+                    buf.append(edx_eaxをediで割る_商はeaxに_余りはedxに());
+                }
 
                 // 結果は edx レジスタに入るので、ediに移し替える
                 buf.append(rdxをプッシュ());
                 buf.append(rdiへとポップ());
             }
+            Expr::BinaryExpr {
+                op:
+                    op @ (BinaryOp::BitAnd
+                    | BinaryOp::BitOr
+                    | BinaryOp::BitXor
+                    | BinaryOp::LeftShift
+                    | BinaryOp::RightShift),
+                op_pos: _,
+                左辺,
+                右辺,
+                typ,
+            } => {
+                self.exprを評価してediレジスタへ(buf, 左辺);
+                buf.append(rdiをプッシュ());
+                self.stack_size += WORD_SIZE_AS_U32;
+                self.exprを評価してediレジスタへ(buf, 右辺);
+                buf.append(rdiをプッシュ());
+                self.stack_size += WORD_SIZE_AS_U32;
+                buf.append(raxへとポップ());
+                self.stack_size -= WORD_SIZE_AS_U32;
+                buf.append(rdiへとポップ());
+                self.stack_size -= WORD_SIZE_AS_U32;
+                buf.append(整数の二項演算(*op, typ));
+            }
             Expr::BinaryExpr {
                 op: BinaryOp::Equal,
                 op_pos: _,
@@ -803,11 +1613,16 @@ impl<'a> FunctionGen<'a> {
                 右辺,
                 typ: _,
             } => {
+                let フラグをalに移す = if 左辺.typ() == Type::UInt {
+                    フラグを読んで符号なしで未満であるかどうかをalにセット()
+                } else {
+                    フラグを読んで未満であるかどうかをalにセット()
+                };
                 self.比較演算を評価してediレジスタへ(
                     buf,
                     左辺,
                     右辺,
-                    &フラグを読んで未満であるかどうかをalにセット(),
+                    &フラグをalに移す,
                 );
             }
             Expr::BinaryExpr {
@@ -817,11 +1632,16 @@ impl<'a> FunctionGen<'a> {
                 右辺,
                 typ: _,
             } => {
+                let フラグをalに移す = if 左辺.typ() == Type::UInt {
+                    フラグを読んで符号なしで以下であるかどうかをalにセット()
+                } else {
+                    フラグを読んで以下であるかどうかをalにセット()
+                };
                 self.比較演算を評価してediレジスタへ(
                     buf,
                     左辺,
                     右辺,
-                    &フラグを読んで以下であるかどうかをalにセット(),
+                    &フラグをalに移す,
                 );
             }
             Expr::Numeric {
@@ -838,73 +1658,74 @@ impl<'a> FunctionGen<'a> {
                 pos: _,
                 typ: return_type,
             } => {
-                let function = *self
-                    .global_function_table
-                    .get(ident)
-                    .unwrap_or_else(|| panic!("関数 {ident} が見つかりません"));
-
-                let stack_args_len = if args.len() > 6 { args.len() - 6 } else { 0 };
-
-                let stack_size_adjustment =
-                    self.stack_size + WORD_SIZE_AS_U32 * stack_args_len as u32 % 16;
-                buf.append(rspから即値を引く(stack_size_adjustment as i32).to_vec());
-                self.stack_size += stack_size_adjustment;
-
-                // 引数の評価順序変わるけど未規定のはずなのでよし
-                for arg in args.iter().rev() {
-                    self.exprを評価してediレジスタへ(buf, arg);
-                    buf.append(rdiをプッシュ());
-                    self.stack_size += WORD_SIZE_AS_U32;
-                }
-
-                #[allow(clippy::len_zero)]
-                if args.len() >= 1 {
-                    buf.append(rdiへとポップ());
-                    self.stack_size -= WORD_SIZE_AS_U32;
-                }
-
-                if args.len() >= 2 {
-                    buf.append(rsiへとポップ());
-                    self.stack_size -= WORD_SIZE_AS_U32;
-                }
-
-                if args.len() >= 3 {
-                    buf.append(rdxへとポップ());
-                    self.stack_size -= WORD_SIZE_AS_U32;
-                }
-
-                if args.len() >= 4 {
-                    buf.append(rcxへとポップ());
-                    self.stack_size -= WORD_SIZE_AS_U32;
-                }
-
-                if args.len() >= 5 {
-                    buf.append(r8へとポップ());
-                    self.stack_size -= WORD_SIZE_AS_U32;
-                }
-
-                if args.len() >= 6 {
-                    buf.append(r9へとポップ());
-                    self.stack_size -= WORD_SIZE_AS_U32;
-                }
-
-                buf.append(eaxに即値をセット(function + 0x00400000));
-                buf.append(call_rax());
-
-                if let Type::Arr { .. } = return_type {
-                    // これは「文字列リテラルを返すビルトイン関数」でしか起きない
-                    buf.append(raxをrdiにコピー());
-                } else {
-                    match return_type.sizeof_primitive("g") {
-                        8 => buf.append(raxをrdiにコピー()),
-                        4 => buf.append(eaxをediにコピー()),
-                        1 => buf.append(alをediに符号拡張してmov()),
-                        _ => panic!("関数の戻り値の型のサイズがよろしくない"),
-                    }
+                self.関数呼び出しを評価してediレジスタへ(
+                    buf,
+                    args,
+                    return_type,
+                    |_, buf| {
+                        buf.append(関数のアドレスをeaxに代入(ident));
+                    },
+                );
+            }
+            Expr::IndirectCall {
+                callee,
+                args,
+                pos: _,
+                typ: return_type,
+            } => {
+                self.関数呼び出しを評価してediレジスタへ(
+                    buf,
+                    args,
+                    return_type,
+                    |this, buf| {
+                        this.exprを評価してediレジスタへ(buf, callee);
+                        buf.append(rdiをraxにコピー());
+                    },
+                );
+            }
+            Expr::Cast {
+                expr: inner, typ, ..
+            } => {
+                self.exprを評価してediレジスタへ(buf, inner);
+                buf.append(rdiの値を型変換(&inner.typ(), typ));
+            }
+            Expr::VaStart { ap, .. } => {
+                let 可変長引数 = self
+                    .可変長引数
+                    .expect("va_start が、可変長引数をとらない関数の中で使われています");
+                self.exprを評価してediレジスタへ(buf, ap);
+                buf.append(
+                    rdiにoffsetを足した位置の4バイトに即値を代入(
+                        0,
+                        可変長引数.gp_offset,
+                    ),
+                );
+                buf.append(
+                    rdiにoffsetを足した位置の4バイトに即値を代入(
+                        4,
+                        可変長引数.fp_offset,
+                    ),
+                );
+                buf.append(rbpにoffsetを足したアドレスをraxに代入(
+                    可変長引数.スタック引数,
+                ));
+                buf.append(rdiにoffsetを足した位置にraxを代入(8)); // overflow_arg_area
+                buf.append(rbpにoffsetを足したアドレスをraxに代入(
+                    -可変長引数.レジスタ保存領域,
+                ));
+                buf.append(rdiにoffsetを足した位置にraxを代入(16)); // reg_save_area
+            }
+            Expr::VaArg { ap, typ, .. } => {
+                self.exprを評価してediレジスタへ(buf, ap);
+                self.va_argで取り出す引数のアドレスをrsiへ(buf, typ);
+                buf.append(rsiをrdiにコピー());
+                // 構造体の値は、そのアドレスで表す
+                if !matches!(typ, Type::Struct { .. }) {
+                    buf.append(rdiが指す値を読み込む(typ));
                 }
-
-                buf.append(rspに即値を足す(stack_size_adjustment as i32).to_vec());
-                self.stack_size -= stack_size_adjustment;
+            }
+            Expr::DecayedFunc { ident, .. } => {
+                buf.append(関数のアドレスをediに代入(ident));
             }
             Expr::UnaryExpr {
                 op: UnaryOp::Addr,
@@ -925,15 +1746,389 @@ impl<'a> FunctionGen<'a> {
                     buf, expr,
                 );
 
-                match typ.sizeof_primitive("k") {
-                    8 => buf.append(rdiを間接参照()),
-                    4 => buf.append(rdiを間接参照()),
-                    1 => buf.append(rdiをmovzxで間接参照()),
-                    _ => panic!(
-                        "size が {} な型の参照はできません",
-                        expr.typ().sizeof_primitive("l")
-                    ),
-                };
+                buf.append(rdiが指す値を読み込む(typ));
+            }
+        }
+    }
+
+    /// 引数をレジスタやスタックに積み、`呼び出し先をraxへ` で rax にセットしたアドレスを call する
+    #[allow(clippy::too_many_lines)]
+    fn 関数呼び出しを評価してediレジスタへ(
+        &mut self,
+        buf: &mut Buf,
+        args: &[Expr],
+        return_type: &Type,
+        呼び出し先をraxへ: impl FnOnce(&mut Self, &mut Buf),
+    ) {
+        // 構造体を返す関数の場合、戻り値を置く一時領域を確保する
+        let 戻り値の置き場所 = match return_type {
+            Type::Struct { .. } => Some((
+                渡し方を分類(return_type, self.struct_names),
+                self.local_var_table
+                    .allocate_temporary(return_type.sizeof(self.struct_names)),
+            )),
+            _ => None,
+        };
+
+        // 戻り値をメモリで返す関数には、格納先のアドレスを隠れた第一引数として渡す
+        // レジスタへとポップする eightbyte の分類を、ポップする順に並べたもの
+        let mut ポップする順 = vec![];
+        if let Some((渡し方::Memory, _)) = 戻り値の置き場所 {
+            ポップする順.push(クラス::Integer);
+        }
+        let mut 使う汎用レジスタの数 = ポップする順.len();
+        let mut 使うxmmレジスタの数 = 0;
+        let mut レジスタで渡す引数 = vec![];
+        let mut スタックで渡す引数 = vec![];
+        for arg in args {
+            let 渡し方 = 渡し方を分類(&arg.typ(), self.struct_names);
+            let (integer, sse) = 渡し方.使うレジスタの数();
+            match 渡し方 {
+                渡し方::Registers(classes)
+                    if 使う汎用レジスタの数 + integer <= 6 && 使うxmmレジスタの数 + sse <= 8 =>
+                {
+                    使う汎用レジスタの数 += integer;
+                    使うxmmレジスタの数 += sse;
+                    ポップする順.extend(classes);
+                    レジスタで渡す引数.push(arg);
+                }
+                _ => スタックで渡す引数.push(arg),
+            }
+        }
+        let スタックで渡す引数のサイズ: u32 = スタックで渡す引数
+            .iter()
+            .map(|arg| {
+                (arg.typ().sizeof(self.struct_names) as u32).div_ceil(WORD_SIZE_AS_U32)
+                    * WORD_SIZE_AS_U32
+            })
+            .sum();
+
+        // 保存した rbp の直下は 16 バイト境界に揃っているので、そこから積んだ値と
+        // スタックで渡す引数の大きさの合計が 16 の倍数になるよう、rsp を余分にずらす
+        let stack_size_adjustment =
+            (self.stack_size - WORD_SIZE_AS_U32 + スタックで渡す引数のサイズ) % 16;
+        buf.append(rspから即値を引く(stack_size_adjustment as i32).to_vec());
+        self.stack_size += stack_size_adjustment;
+
+        // 引数の評価順序変わるけど未規定のはずなのでよし
+        for arg in スタックで渡す引数.iter().rev() {
+            self.exprを評価してediレジスタへ(buf, arg);
+            if let Type::Struct { .. } = arg.typ() {
+                let size = (arg.typ().sizeof(self.struct_names) as u32).div_ceil(WORD_SIZE_AS_U32)
+                    * WORD_SIZE_AS_U32;
+                buf.append(rspから即値を引く(size as i32));
+                self.stack_size += size;
+                buf.append(rspをraxにコピー());
+                self.rdiが指す構造体をraxが指す位置にコピー(buf, &arg.typ());
+            } else {
+                buf.append(rdiをプッシュ());
+                self.stack_size += WORD_SIZE_AS_U32;
+            }
+        }
+
+        for arg in レジスタで渡す引数.iter().rev() {
+            self.exprを評価してediレジスタへ(buf, arg);
+            if let 渡し方::Registers(classes) = 渡し方を分類(&arg.typ(), self.struct_names)
+            {
+                if let Type::Struct { .. } = arg.typ() {
+                    // 構造体の末尾を越えて読まないよう、8 バイト単位の大きさを持つ一時領域にコピーしてから積む
+                    let temp = self
+                        .local_var_table
+                        .allocate_temporary(arg.typ().sizeof(self.struct_names));
+                    buf.append(rbpにoffsetを足したアドレスをraxに代入(-temp));
+                    self.rdiが指す構造体をraxが指す位置にコピー(buf, &arg.typ());
+                    for i in (0..classes.len()).rev() {
+                        buf.append(rdiにoffsetを足した位置の値をプッシュ(
+                            (i * WORD_SIZE as usize) as i8,
+                        ));
+                        self.stack_size += WORD_SIZE_AS_U32;
+                    }
+                    continue;
+                }
+            }
+            buf.append(rdiをプッシュ());
+            self.stack_size += WORD_SIZE_AS_U32;
+        }
+
+        if let Some((渡し方::Memory, offset)) = 戻り値の置き場所 {
+            buf.append(rbpにoffsetを足したアドレスをrdiに代入(-offset));
+            buf.append(rdiをプッシュ());
+            self.stack_size += WORD_SIZE_AS_U32;
+        }
+
+        // 引数をレジスタへとポップする前に、呼び出し先のアドレスを rax に入れておく
+        呼び出し先をraxへ(self, buf);
+
+        let mut 次の汎用レジスタ = 0;
+        let mut 次のxmmレジスタ = 0;
+        for class in ポップする順 {
+            match class {
+                クラス::Integer => {
+                    match 次の汎用レジスタ {
+                        0 => buf.append(rdiへとポップ()),
+                        1 => buf.append(rsiへとポップ()),
+                        2 => buf.append(rdxへとポップ()),
+                        3 => buf.append(rcxへとポップ()),
+                        4 => buf.append(r8へとポップ()),
+                        5 => buf.append(r9へとポップ()),
+                        _ => unreachable!(),
+                    }
+                    次の汎用レジスタ += 1;
+                }
+                クラス::Sse => {
+                    buf.append(xmmへとポップ(次のxmmレジスタ));
+                    次のxmmレジスタ += 1;
+                }
+            }
+            self.stack_size -= WORD_SIZE_AS_U32;
+        }
+
+        // 可変長引数の関数は、xmm レジスタで渡された引数の数を al で受け取る。
+        // 呼び出し先が可変長引数の関数かどうかによらずセットするので、呼び出し先のアドレスは r11 に移す
+        buf.append(raxをr11にコピー());
+        buf.append(eaxに即値をセット(使うxmmレジスタの数 as u32));
+        buf.append(call_r11());
+
+        match (return_type, 戻り値の置き場所) {
+            (_, Some((渡し方::Registers(classes), offset))) => {
+                buf.append(rbpにoffsetを足したアドレスをrdiに代入(-offset));
+                let mut 次の汎用レジスタ = 0;
+                let mut 次のxmmレジスタ = 0;
+                for (i, class) in classes.iter().enumerate() {
+                    let offset = (i * WORD_SIZE as usize) as i8;
+                    match class {
+                        クラス::Integer => {
+                            if 次の汎用レジスタ == 0 {
+                                buf.append(rdiにoffsetを足した位置にraxを代入(offset));
+                            } else {
+                                buf.append(rdiにoffsetを足した位置にrdxを代入(offset));
+                            }
+                            次の汎用レジスタ += 1;
+                        }
+                        クラス::Sse => {
+                            buf.append(rdiにoffsetを足した位置にxmmを代入(
+                                次のxmmレジスタ,
+                                offset,
+                            ));
+                            次のxmmレジスタ += 1;
+                        }
+                    }
+                }
+            }
+            (_, Some((渡し方::Memory, _))) => {
+                // 呼び出し先は、格納先のアドレスを rax に入れて返す
+                buf.append(raxをrdiにコピー());
+            }
+            (Type::Arr { .. }, None) => {
+                // これは「文字列リテラルを返すビルトイン関数」でしか起きない
+                buf.append(raxをrdiにコピー());
+            }
+            (typ, None) if typ.is_floating() => buf.append(xmm0をrdiにコピー()),
+            (_, None) => match return_type.sizeof_primitive("g") {
+                8 => buf.append(raxをrdiにコピー()),
+                4 => buf.append(eaxをediにコピー()),
+                1 if return_type.is_unsigned() => buf.append(alをゼロ拡張してediにセット()),
+                1 => buf.append(alをediに符号拡張してmov()),
+                _ => panic!("関数の戻り値の型のサイズがよろしくない"),
+            },
+        }
+
+        buf.append(
+            rspに即値を足す((stack_size_adjustment + スタックで渡す引数のサイズ) as i32).to_vec(),
+        );
+        self.stack_size -= stack_size_adjustment + スタックで渡す引数のサイズ;
+    }
+
+    /// rdi が指す va_list から、次の可変長引数を `typ` 型として取り出し、そのアドレスを rsi に入れる。
+    /// rdi は壊さない
+    fn va_argで取り出す引数のアドレスをrsiへ(&mut self, buf: &mut Buf, typ: &Type) {
+        let overflow_label = self.新しいラベル("va_arg overflow");
+        let done_label = self.新しいラベル("va_arg done");
+        // va_list の中の、レジスタ保存領域のどこまでを使ったかを表すメンバーのオフセットと、
+        // 一つのレジスタが占める大きさ
+        let 使った位置 = |class: クラス| match class {
+            クラス::Integer => (0, WORD_SIZE as i8),
+            クラス::Sse => (4, 16),
+        };
+
+        if let 渡し方::Registers(classes) = 渡し方を分類(typ, self.struct_names) {
+            // 残りのレジスタで渡しきれない引数は、スタックで渡されている
+            let (integer, sse) = 渡し方::Registers(classes.clone()).使うレジスタの数();
+            if integer > 0 {
+                buf.append(rdiにoffsetを足した位置からeaxに読み込む(0));
+                buf.append(eaxと即値を比較してフラグをセット(
+                    (汎用レジスタの保存領域の大きさ - integer as u32 * WORD_SIZE_AS_U32) as i32,
+                ));
+                buf.append(ラベルへja(&overflow_label));
+            }
+            if sse > 0 {
+                buf.append(rdiにoffsetを足した位置からeaxに読み込む(4));
+                buf.append(eaxと即値を比較してフラグをセット(
+                    (レジスタ保存領域の大きさ - sse as u32 * 16) as i32,
+                ));
+                buf.append(ラベルへja(&overflow_label));
+            }
+
+            let レジスタの位置をrsiへ = |buf: &mut Buf, class: クラス| {
+                let (offset, size) = 使った位置(class);
+                buf.append(rdiにoffsetを足した位置からeaxに読み込む(
+                    offset,
+                ));
+                buf.append(rdiにoffsetを足した位置からrsiに読み込む(16)); // reg_save_area
+                buf.append(rsiにraxを足す());
+                buf.append(rdiにoffsetを足した位置の4バイトに即値を足す(offset, size));
+            };
+            if let Type::Struct { .. } = typ {
+                // eightbyte ごとに別々の場所に保存されているので、一時領域に集める
+                let temp = self
+                    .local_var_table
+                    .allocate_temporary(typ.sizeof(self.struct_names));
+                for (k, class) in classes.into_iter().enumerate() {
+                    レジスタの位置をrsiへ(buf, class);
+                    buf.append(rsiにoffsetを足した位置からraxに読み込む(0));
+                    buf.append(rbpにoffsetを足した位置にraxを代入(
+                        -temp + (k * WORD_SIZE as usize) as i32,
+                    ));
+                }
+                buf.append(rbpにoffsetを足したアドレスをrsiに代入(-temp));
+            } else {
+                レジスタの位置をrsiへ(buf, classes[0]);
+            }
+            buf.append(ラベルへjmp(&done_label));
+        }
+
+        buf.append(ラベル(&overflow_label));
+        buf.append(rdiにoffsetを足した位置からrsiに読み込む(8)); // overflow_arg_area
+        let size =
+            (typ.sizeof(self.struct_names) as u32).div_ceil(WORD_SIZE_AS_U32) * WORD_SIZE_AS_U32;
+        buf.append(rdiにoffsetを足した位置の8バイトに即値を足す(8, size as i32));
+        buf.append(ラベル(&done_label));
+    }
+
+    /// ビットフィールドに対する `=` や複合代入を、記憶単位の読み込み・書き換え・書き戻しとして評価する。
+    /// `typ` はビットフィールドの型で、式の値は、ビットフィールドに格納された後の値
+    fn ビットフィールドへの代入を評価してediレジスタへ(
+        &mut self,
+        buf: &mut Buf,
+        op: BinaryOp,
+        unit: &Expr,
+        bit_field: BitField,
+        typ: &Type,
+        右辺: &Expr,
+    ) {
+        if op == BinaryOp::Assign {
+            self.exprを左辺値として評価してアドレスをrdiレジスタへ(buf, unit);
+            buf.append(rdiをプッシュ());
+            self.stack_size += WORD_SIZE_AS_U32;
+            self.exprを評価してediレジスタへ(buf, 右辺);
+            buf.append(raxへとポップ()); // 記憶単位のアドレス
+            self.stack_size -= WORD_SIZE_AS_U32;
+        } else {
+            self.exprを評価してediレジスタへ(buf, 右辺);
+            buf.append(rdiをプッシュ());
+            self.stack_size += WORD_SIZE_AS_U32;
+
+            self.exprを左辺値として評価してアドレスをrdiレジスタへ(buf, unit);
+            buf.append(rdiをプッシュ()); // 記憶単位のアドレス
+            self.stack_size += WORD_SIZE_AS_U32;
+
+            記憶単位をrdiが指す位置から読み込む(buf, &unit.typ());
+            buf.append(ビットフィールドの値を取り出す(bit_field)); // 左辺の値：rdi
+
+            buf.append(rsiへとポップ()); // 記憶単位のアドレス：rsi
+            self.stack_size -= WORD_SIZE_AS_U32;
+            buf.append(raxへとポップ()); // 右辺の値：rax
+            self.stack_size -= WORD_SIZE_AS_U32;
+
+            buf.append(複合代入の演算(op, typ, &右辺.typ()));
+            buf.append(rsiをraxにコピー());
+        }
+
+        // 新しい値を記憶単位の中の位置に合わせて esi に用意する
+        let width = bit_field.width;
+        let bit_offset = bit_field.bit_offset;
+        buf.append(ediをesiにコピー());
+        buf.append(esiを左シフト(32 - width));
+        buf.append(esiを論理右シフト(32 - width - bit_offset));
+
+        buf.append(raxをrdiにコピー());
+        記憶単位をrdiが指す位置から読み込む(buf, &unit.typ());
+        let mask = (((1_u64 << width) - 1) << bit_offset) as u32;
+        buf.append(ediと即値のandを取る(!mask));
+        buf.append(ediにesiをorする());
+        match unit.typ().sizeof_primitive("ビットフィールドの記憶単位") {
+            4 => buf.append(raxが指す位置にediを代入()),
+            1 => buf.append(raxが指す位置にdilを代入()),
+            size => panic!("size が {size} な記憶単位にはビットフィールドを置けません"),
+        }
+        buf.append(ビットフィールドの値を取り出す(bit_field));
+    }
+
+    /// rdi が指す構造体の値を、rax が指す位置にコピーする。コピー後、rdi はコピー先を指す
+    fn rdiが指す構造体をraxが指す位置にコピー(
+        &mut self,
+        buf: &mut Buf,
+        typ: &Type,
+    ) {
+        let size = typ.sizeof(self.struct_names);
+        buf.append(raxをプッシュ());
+        buf.append(rdiをrsiにコピー());
+        buf.append(raxをrdiにコピー());
+        buf.append(ecxに即値をセット(size as u32));
+        buf.append(rep_movsb());
+        buf.append(rdiへとポップ());
+    }
+
+    /// rdi が指す構造体の値を、System V ABI に従って関数の戻り値として rax, rdx や xmm0, xmm1 に入れる
+    fn 構造体の値をrdiが指す位置から返す(
+        &mut self,
+        buf: &mut Buf,
+        return_type: &Type,
+    ) {
+        match 渡し方を分類(return_type, self.struct_names) {
+            渡し方::Memory => {
+                let offset = self
+                    .戻り値の格納先
+                    .expect("戻り値の格納先のアドレスが保存されていません");
+                buf.append(rbpにoffsetを足した位置の値をraxに読み込む(
+                    -offset,
+                ));
+                self.rdiが指す構造体をraxが指す位置にコピー(buf, return_type);
+                buf.append(rdiをraxにコピー());
+            }
+            渡し方::Registers(classes) => {
+                // 構造体の末尾を越えて読まないよう、8 バイト単位の大きさを持つ一時領域にコピーしてから読む
+                let temp = self
+                    .local_var_table
+                    .allocate_temporary(return_type.sizeof(self.struct_names));
+                buf.append(rbpにoffsetを足したアドレスをraxに代入(-temp));
+                self.rdiが指す構造体をraxが指す位置にコピー(buf, return_type);
+                let mut 次の汎用レジスタ = 0;
+                let mut 次のxmmレジスタ = 0;
+                for (i, class) in classes.iter().enumerate() {
+                    let offset = (i * WORD_SIZE as usize) as i8;
+                    match class {
+                        クラス::Integer => {
+                            if 次の汎用レジスタ == 0 {
+                                buf.append(rdiにoffsetを足した位置からraxに読み込む(
+                                    offset,
+                                ));
+                            } else {
+                                buf.append(rdiにoffsetを足した位置からrdxに読み込む(
+                                    offset,
+                                ));
+                            }
+                            次の汎用レジスタ += 1;
+                        }
+                        クラス::Sse => {
+                            buf.append(rdiにoffsetを足した位置からxmmに読み込む(
+                                次のxmmレジスタ,
+                                offset,
+                            ));
+                            次のxmmレジスタ += 1;
+                        }
+                    }
+                }
             }
         }
     }
@@ -963,10 +2158,55 @@ impl<'a> FunctionGen<'a> {
     }
 }
 
+/// edi に入っている switch 文の条件式の値に応じて、`{switch_label} case {値}` のラベルへ飛ぶ。
+/// どの case にも当たらなければ `default_label` へ飛ぶ。
+/// case の値が十分に密に並んでいればジャンプテーブルを、そうでなければ比較の列を生成する
+fn caseへ分岐(switch_label: &str, cases: &[i32], default_label: &str) -> Buf {
+    let case_label = |value: i32| format!("{switch_label} case {value}");
+    let (Some(&min), Some(&max)) = (cases.iter().min(), cases.iter().max()) else {
+        return ラベルへjmp(default_label);
+    };
+    let range = i64::from(max) - i64::from(min) + 1;
+
+    if cases.len() < 4 || range > 3 * cases.len() as i64 {
+        let mut buf = Buf::new();
+        for &value in cases {
+            buf.append(ediと即値を比較してフラグをセット(value));
+            buf.append(ラベルへje(&case_label(value)));
+        }
+        return buf.join(ラベルへjmp(default_label));
+    }
+
+    // 値から min を引いたものを符号なしで比較すれば、min 未満と max 超過を一度に弾ける
+    let table_label = format!("{switch_label} table");
+    let mut buf = Buf::new();
+    buf.append(ediをeaxにコピー());
+    buf.append(eaxから即値を引く(min));
+    buf.append(eaxと即値を比較してフラグをセット(
+        i32::try_from(range - 1).unwrap(),
+    ));
+    buf.append(ラベルへja(default_label));
+    buf.append(ラベルのアドレスをrcxに代入(&table_label));
+    buf.append(rcxにraxの4倍を足した位置の4バイトをraxに符号拡張して読み込む());
+    buf.append(raxにrcxを足す());
+    buf.append(jmp_rax());
+    buf.append(ラベル(&table_label));
+    for value in min..=max {
+        let target = if cases.contains(&value) {
+            case_label(value)
+        } else {
+            default_label.to_string()
+        };
+        buf.append(ジャンプテーブルの要素(&target, &table_label));
+    }
+    buf
+}
+
 pub fn 関数をコード生成しメインバッファとグローバル関数テーブルに挿入(
     global_function_table: &mut HashMap<String, u32>,
     main_buf: &mut Buf,
     definition: &FunctionDefinition,
+    struct_names: &HashMap<String, StructDefinition>,
 ) -> u16 {
     let func_pos = u16::try_from(main_buf.len()).expect("バッファの長さが u16 に収まりません");
     global_function_table.insert(definition.func_name.clone(), u32::from(func_pos));
@@ -977,13 +2217,23 @@ pub fn 関数をコード生成しメインバッファとグローバル関数
             max_offset: 0,
         },
         stack_size: 0,
-        global_function_table,
         function_name: &definition.func_name,
+        struct_names,
+        戻り値の格納先: None,
+        ラベルの個数: 0,
+        保存したスタックポインタ: Vec::new(),
+        breakの飛び先: Vec::new(),
+        continueの飛び先: Vec::new(),
+        switchのラベル: Vec::new(),
+        ラベルの位置で保存したスタックポインタ: HashMap::new(),
+        可変長引数: None,
     };
 
     if let StatementOrDeclaration::Statement(Statement::BuiltinPopulateArgcArgv { .. }) =
         definition.statements[0]
     {
+        // プロセスの開始時点で rsp は 16 バイト境界に揃っているので、rbp を積んだ直後と同じ状態とみなす
+        function_gen.stack_size = WORD_SIZE_AS_U32;
         main_buf.append(ebpに即値をセット(0));
         main_buf.append(rspにoffsetを足したアドレスをrdiに代入(0));
         main_buf.append(rspにoffsetを足したアドレスをrsiに代入(8));
@@ -1003,86 +2253,121 @@ pub fn 関数をコード生成しメインバッファとグローバル関数
     main_buf.append(rspをrbpにコピー());
 
     let mut parameter_buf = Buf::new();
+    let mut 次のレジスタ = 0;
+    let mut 次のxmmレジスタ = 0;
+
+    // 戻り値をメモリで返す場合、格納先のアドレスが隠れた第一引数として rdi に入っている
+    if let (Type::Struct { .. }, 渡し方::Memory) = (
+        &definition.return_type,
+        渡し方を分類(&definition.return_type, struct_names),
+    ) {
+        let offset = function_gen
+            .local_var_table
+            .allocate_temporary(WORD_SIZE_AS_I32);
+        parameter_buf.append(rbpにoffsetを足した位置にrdiを代入(-offset));
+        function_gen.戻り値の格納先 = Some(offset);
+        次のレジスタ = 1;
+    }
+
+    // スタックで渡された引数は、レジスタで渡された引数を全て保存してからローカル変数の領域にコピーする
+    // （コピーに rdi, rsi, rcx を使うため）
+    let mut スタック引数のコピー = Buf::new();
+    let mut 次のスタック引数の位置 = 2 * WORD_SIZE_AS_I32; // 戻りアドレスと、保存した rbp の上
 
     // context.rs の実装詳細「param には 0 番から順番に ID が振られている」に依存
     for (i, (param_type, param)) in definition.params.iter().enumerate() {
-        let offset = function_gen.local_var_table.allocate(
-            param,
-            i as u64,
-            param_type.sizeof_primitive("m"),
-        );
+        let size = param_type.sizeof(struct_names);
+        let offset = function_gen.local_var_table.allocate(param, i as u64, size);
         // rbp から offset を引いた値のアドレスに、レジスタから読んできた値を入れる必要がある
         // （関数 `exprを左辺値として評価してアドレスをrdiレジスタへ` も参照）
         let negative_offset = -offset;
-        match (i, param_type.sizeof_primitive("n")) {
-            (0, 8) => parameter_buf.append(rbpにoffsetを足した位置にrdiを代入(
-                negative_offset,
-            )),
-            (1, 8) => parameter_buf.append(rbpにoffsetを足した位置にrsiを代入(
-                negative_offset,
-            )),
-            (2, 8) => parameter_buf.append(rbpにoffsetを足した位置にrdxを代入(
-                negative_offset,
-            )),
-            (3, 8) => parameter_buf.append(rbpにoffsetを足した位置にrcxを代入(
-                negative_offset,
-            )),
-            (4, 8) => parameter_buf.append(rbpにoffsetを足した位置にr8を代入(
-                negative_offset,
-            )),
-            (5, 8) => parameter_buf.append(rbpにoffsetを足した位置にr9を代入(
-                negative_offset,
-            )),
-            (0, 4) => parameter_buf.append(rbpにoffsetを足した位置にediを代入(
-                negative_offset,
-            )),
-            (1, 4) => parameter_buf.append(rbpにoffsetを足した位置にesiを代入(
-                negative_offset,
-            )),
-            (2, 4) => parameter_buf.append(rbpにoffsetを足した位置にedxを代入(
-                negative_offset,
-            )),
-            (3, 4) => parameter_buf.append(rbpにoffsetを足した位置にecxを代入(
-                negative_offset,
-            )),
-            (4, 4) => parameter_buf.append(rbpにoffsetを足した位置にr8dを代入(
-                negative_offset,
-            )),
-            (5, 4) => parameter_buf.append(rbpにoffsetを足した位置にr9dを代入(
-                negative_offset,
-            )),
-            (0, 1) => parameter_buf.append(rbpにoffsetを足した位置にdilを代入(
-                negative_offset,
-            )),
-            (1, 1) => parameter_buf.append(rbpにoffsetを足した位置にsilを代入(
-                negative_offset,
-            )),
-            (2, 1) => parameter_buf.append(rbpにoffsetを足した位置にdlを代入(
-                negative_offset,
-            )),
-            (3, 1) => parameter_buf.append(rbpにoffsetを足した位置にclを代入(
-                negative_offset,
-            )),
-            (4, 1) => parameter_buf.append(rbpにoffsetを足した位置にr8bを代入(
-                negative_offset,
-            )),
-            (5, 1) => parameter_buf.append(rbpにoffsetを足した位置にr9bを代入(
-                negative_offset,
-            )),
-            (0..=5, _) => {
-                todo!(
-                    "関数 `{}` の仮引数 {} の型の size {} は未対応です",
-                    definition.func_name,
-                    param,
-                    param_type.sizeof_primitive("o")
-                )
+        let 渡し方 = 渡し方を分類(param_type, struct_names);
+        let (integer, sse) = 渡し方.使うレジスタの数();
+        match 渡し方 {
+            渡し方::Registers(classes)
+                if 次のレジスタ + integer <= 6 && 次のxmmレジスタ + sse <= 8 =>
+            {
+                if let Type::Struct { .. } = param_type.unqualified() {
+                    // ローカル変数の領域は 8 バイト単位で確保されているので、eightbyte ごとに丸ごと書き込んでよい
+                    for (k, class) in classes.iter().enumerate() {
+                        let offset = negative_offset + (k * WORD_SIZE as usize) as i32;
+                        match class {
+                            クラス::Integer => {
+                                parameter_buf.append(引数レジスタの値をrbpにoffsetを足した位置に代入(
+                                    次のレジスタ,
+                                    8,
+                                    offset,
+                                ));
+                                次のレジスタ += 1;
+                            }
+                            クラス::Sse => {
+                                parameter_buf.append(rbpにoffsetを足した位置にxmmを代入(
+                                    次のxmmレジスタ as u8,
+                                    offset,
+                                ));
+                                次のxmmレジスタ += 1;
+                            }
+                        }
+                    }
+                } else if param_type.is_floating() {
+                    parameter_buf.append(rbpにoffsetを足した位置にxmmを代入(
+                        次のxmmレジスタ as u8,
+                        negative_offset,
+                    ));
+                    次のxmmレジスタ += 1;
+                } else {
+                    parameter_buf.append(
+                        引数レジスタの値をrbpにoffsetを足した位置に代入(
+                            次のレジスタ,
+                            param_type.sizeof_primitive("n"),
+                            negative_offset,
+                        ),
+                    );
+                    次のレジスタ += 1;
+                }
             }
-            (_, _) => panic!(
-                "関数 `{}` に 7 つ以上の仮引数があります",
-                definition.func_name
-            ),
-        };
+            _ => {
+                スタック引数のコピー.append(rbpにoffsetを足したアドレスをrsiに代入(
+                    次のスタック引数の位置,
+                ));
+                スタック引数のコピー.append(rbpにoffsetを足したアドレスをrdiに代入(
+                    negative_offset,
+                ));
+                スタック引数のコピー.append(ecxに即値をセット(size as u32));
+                スタック引数のコピー.append(rep_movsb());
+                次のスタック引数の位置 +=
+                    ((size as u32).div_ceil(WORD_SIZE_AS_U32) * WORD_SIZE_AS_U32) as i32;
+            }
+        }
+    }
+    if definition.is_variadic {
+        // 可変長引数がどのレジスタで渡されたかは va_arg で決まるので、引数レジスタを全て保存しておく
+        let offset = function_gen
+            .local_var_table
+            .allocate_temporary(レジスタ保存領域の大きさ as i32);
+        for レジスタ番号 in 0..6 {
+            parameter_buf.append(
+                引数レジスタの値をrbpにoffsetを足した位置に代入(
+                    レジスタ番号,
+                    8,
+                    -offset + (レジスタ番号 * WORD_SIZE as usize) as i32,
+                ),
+            );
+        }
+        for n in 0..8 {
+            parameter_buf.append(rbpにoffsetを足した位置にxmmを代入(
+                n,
+                -offset + (汎用レジスタの保存領域の大きさ + 16 * u32::from(n)) as i32,
+            ));
+        }
+        function_gen.可変長引数 = Some(可変長引数の位置 {
+            レジスタ保存領域: offset,
+            gp_offset: 次のレジスタ as u32 * WORD_SIZE_AS_U32,
+            fp_offset: 汎用レジスタの保存領域の大きさ + 次のxmmレジスタ as u32 * 16,
+            スタック引数: 次のスタック引数の位置,
+        });
     }
+    parameter_buf.append(スタック引数のコピー);
 
     for (
         local_var_name,
@@ -1098,17 +2383,245 @@ pub fn 関数をコード生成しメインバッファとグローバル関数
             .allocate(local_var_name, *id, *local_var_size);
     }
 
-    let content_buf = definition
+    for stmt in &definition.statements {
+        function_gen.ラベルを調べる(stmt, &mut Vec::new());
+    }
+    let mut content_buf = definition
         .statements
         .iter()
         .map(|stmt| function_gen.statement_or_declarationを評価(stmt))
         .fold(parameter_buf, Buf::join);
+    content_buf.resolve_labels();
 
+    // 関数の呼び出し時に rsp を 16 バイト境界に揃えられるよう、ローカル変数の領域の大きさを 16 の倍数に切り上げる
     main_buf.append(rspから即値を引く(
-        i32::try_from(function_gen.local_var_table.max_offset as usize)
+        i32::try_from((function_gen.local_var_table.max_offset as usize).next_multiple_of(16))
             .expect("ローカル変数のオフセットが i32 に収まりません"),
     ));
     main_buf.append(content_buf);
 
     func_pos
 }
+
+/// rdi が指す `typ` 型の値を rdi に読み込む。
+/// volatile なオブジェクトを読むときに隣のオブジェクトまで読んでしまわないよう、型の大きさちょうどのバイト数だけを読む
+fn rdiが指す値を読み込む(typ: &Type) -> Buf {
+    match typ.sizeof_primitive("読み込み") {
+        8 => Buf::from(rdiを間接参照()),
+        4 => Buf::from(ediを間接参照()),
+        1 if typ.is_unsigned() => Buf::from(rdiをmovzxで間接参照()),
+        1 => Buf::from(rdiをmovsxで間接参照()),
+        size => panic!("size が {size} な型の参照はできません"),
+    }
+}
+
+/// rdi が指すビットフィールドの記憶単位を、ゼロ拡張して edi に読み込む
+fn 記憶単位をrdiが指す位置から読み込む(buf: &mut Buf, unit_type: &Type) {
+    match unit_type.sizeof_primitive("ビットフィールドの記憶単位") {
+        4 => buf.append(ediを間接参照()),
+        1 => buf.append(rdiをmovzxで間接参照()),
+        size => panic!("size が {size} な記憶単位にはビットフィールドを置けません"),
+    }
+}
+
+/// rdi に入っている `from` 型の値を `_Bool` に変換する。つまり、0 と等しければ 0、そうでなければ 1 を edi に入れる
+fn 値を真偽値に正規化(from: &Type) -> Buf {
+    let mut buf = Buf::new();
+    if from.is_floating() {
+        // ビット列が 0 でない -0.0 も偽になるよう、0.0 と浮動小数点数として比較する
+        buf.append(rdiをxmm0にコピー());
+        buf.append(ediに代入(0));
+        buf.append(rdiをxmm1にコピー());
+        buf.append(
+            xmm0とxmm1を比較した結果のマスクをxmm0にセット(
+                *from == Type::Double,
+                4, // NEQ
+            ),
+        );
+        buf.append(xmm0をrdiにコピー());
+        buf.append(ediと即値のandを取る(1));
+    } else {
+        match from.sizeof_primitive("_Bool への変換") {
+            8 => buf.append(rdiが0かを確認()),
+            4 => buf.append(ediが0かを確認()),
+            1 => buf.append(dilが0かを確認()),
+            size => panic!("size が {size} な型は _Bool に変換できません"),
+        }
+        buf.append(フラグを読んで異なっているかどうかをalにセット());
+        buf.append(alをゼロ拡張してediにセット());
+    }
+    buf
+}
+
+/// rdi に入っている `from` 型の値を `to` 型に変換して rdi に入れる
+fn rdiの値を型変換(from: &Type, to: &Type) -> Buf {
+    let mut buf = Buf::new();
+    if *to == Type::Bool {
+        buf.append(値を真偽値に正規化(from));
+    } else if from.is_floating() && to.is_floating() {
+        if from != to {
+            buf.append(rdiをxmm0にコピー());
+            buf.append(xmm0の精度を変換(*to == Type::Double));
+            buf.append(xmm0をrdiにコピー());
+        }
+    } else if from.is_floating() {
+        buf.append(rdiをxmm0にコピー());
+        if *to == Type::UInt {
+            buf.append(xmm0を整数に切り捨ててrdiへ(*from == Type::Double));
+        } else {
+            buf.append(xmm0を整数に切り捨ててediへ(*from == Type::Double));
+        }
+        match to {
+            Type::Char => buf.append(dilをediに符号拡張してmov()),
+            Type::UChar => buf.append(dilをediにゼロ拡張してmov()),
+            _ => {}
+        }
+    } else if to.is_floating() {
+        if *from == Type::Char {
+            buf.append(dilをediに符号拡張してmov());
+        }
+        if *from == Type::UInt {
+            buf.append(ediをrdiにゼロ拡張してmov());
+            buf.append(rdiを浮動小数点数に変換してxmm0へ(
+                *to == Type::Double,
+            ));
+        } else {
+            buf.append(ediを浮動小数点数に変換してxmm0へ(
+                *to == Type::Double,
+            ));
+        }
+        buf.append(xmm0をrdiにコピー());
+    } else if *to != Type::Void {
+        match (from.sizeof_primitive("cast"), to.sizeof_primitive("cast")) {
+            (_, 1) if to.is_unsigned() => buf.append(dilをediにゼロ拡張してmov()),
+            (_, 1) => buf.append(dilをediに符号拡張してmov()),
+            (1 | 4, 8) if from.is_unsigned() => buf.append(ediをrdiにゼロ拡張してmov()),
+            (1 | 4, 8) => buf.append(ediをrdiに符号拡張してmov()),
+            _ => {} // int へのキャストでは edi だけが意味を持つので、何もしなくてよい
+        }
+    }
+    buf
+}
+
+/// rdi に入っている左辺の値と rax に入っている右辺の値に、`typ` 型の整数の二項演算 `op` を施した結果を rdi に入れる
+fn 整数の二項演算(op: BinaryOp, typ: &Type) -> Buf {
+    let mut buf = Buf::new();
+    match op {
+        // ポインタに対しても使われるので、64 ビットで計算する
+        BinaryOp::Add => buf.append(rdiにraxを足し合わせる()),
+        BinaryOp::Sub => buf.append(rdiからraxを減じる()),
+        BinaryOp::Mul => buf.append(rdiをrax倍にする()),
+        BinaryOp::Div | BinaryOp::Remainder => {
+            buf.append(eaxをecxにコピー());
+            buf.append(ediをeaxにコピー());
+            if *typ == Type::UInt {
+                buf.append(edxをゼロクリア());
+                buf.append(edx_eaxをecxで符号なしで割る_商はeaxに_余りはedxに());
+            } else {
+                buf.append(eaxの符号ビットをedxへ拡張());
+                buf.append(edx_eaxをecxで割る_商はeaxに_余りはedxに());
+            }
+            if op == BinaryOp::Div {
+                buf.append(eaxをediにコピー());
+            } else {
+                buf.append(edxをediにコピー());
+            }
+        }
+        BinaryOp::BitAnd => buf.append(ediとeaxのandを取る()),
+        BinaryOp::BitOr => buf.append(ediにeaxをorする()),
+        BinaryOp::BitXor => buf.append(ediとeaxのxorを取る()),
+        BinaryOp::LeftShift => {
+            buf.append(eaxをecxにコピー());
+            buf.append(ediをclだけ左シフト());
+        }
+        BinaryOp::RightShift => {
+            // 演算の型は左オペランドを整数拡張した型なので、符号なしなら論理シフト、そうでなければ算術シフトになる
+            buf.append(eaxをecxにコピー());
+            if *typ == Type::UInt {
+                buf.append(ediをclだけ論理右シフト());
+            } else {
+                buf.append(ediをclだけ算術右シフト());
+            }
+        }
+        _ => panic!("{op:?} は整数の二項演算ではありません"),
+    }
+    buf
+}
+
+/// 複合代入 `op` の演算部分。rdi に入っている `左辺の型` の値と、rax に入っている `演算の型` の右辺の値から、
+/// 代入する値を求めて rdi に入れる。左辺の値は演算の型に変換してから演算し、結果を左辺の型に戻す。rsi は書き換えない
+fn 複合代入の演算(op: BinaryOp, 左辺の型: &Type, 演算の型: &Type) -> Buf {
+    let mut buf = Buf::new();
+    let op = op
+        .compound_assign_base()
+        .unwrap_or_else(|| panic!("{op:?} は複合代入演算子ではありません"));
+    // ポインタへの `+=` と `-=` では、右辺はバイト数に換算済みの整数なので、変換は要らない
+    let 変換する = 左辺の型.is_arithmetic();
+    if 変換する {
+        buf.append(rdiの値を型変換(左辺の型, 演算の型));
+    }
+    if 演算の型.is_floating() {
+        let 倍精度 = *演算の型 == Type::Double;
+        buf.append(rdiをxmm0にコピー());
+        buf.append(raxをxmm1にコピー());
+        match op {
+            BinaryOp::Add => buf.append(xmm0にxmm1を足す(倍精度)),
+            BinaryOp::Sub => buf.append(xmm0からxmm1を引く(倍精度)),
+            BinaryOp::Mul => buf.append(xmm0にxmm1を掛ける(倍精度)),
+            BinaryOp::Div => buf.append(xmm0をxmm1で割る(倍精度)),
+            _ => panic!("浮動小数点数に対して {op:?} は使えません"),
+        }
+        buf.append(xmm0をrdiにコピー());
+    } else {
+        buf.append(整数の二項演算(op, 演算の型));
+    }
+    if 変換する {
+        buf.append(rdiの値を型変換(演算の型, 左辺の型));
+    }
+    buf
+}
+
+/// edi に入っている記憶単位の値から、ビットフィールドの値を取り出して edi に入れる
+fn ビットフィールドの値を取り出す(bit_field: BitField) -> Buf {
+    let width = bit_field.width;
+    let mut buf = Buf::new();
+    // 32 ビットの操作なので、rdi の上位 32 ビットに残っているごみも消える
+    buf.append(ediを左シフト(32 - width - bit_field.bit_offset));
+    if bit_field.is_signed {
+        buf.append(ediを算術右シフト(32 - width));
+    } else {
+        buf.append(ediを論理右シフト(32 - width));
+    }
+    buf
+}
+
+/// `レジスタ番号` 番目の引数レジスタ（rdi, rsi, rdx, rcx, r8, r9 の順）の下位 `size` バイトを、rbp + offset の位置に書き込む
+fn 引数レジスタの値をrbpにoffsetを足した位置に代入(
+    レジスタ番号: usize,
+    size: i32,
+    offset: i32,
+) -> Buf {
+    match (レジスタ番号, size) {
+        (0, 8) => rbpにoffsetを足した位置にrdiを代入(offset),
+        (1, 8) => rbpにoffsetを足した位置にrsiを代入(offset),
+        (2, 8) => rbpにoffsetを足した位置にrdxを代入(offset),
+        (3, 8) => rbpにoffsetを足した位置にrcxを代入(offset),
+        (4, 8) => rbpにoffsetを足した位置にr8を代入(offset),
+        (5, 8) => rbpにoffsetを足した位置にr9を代入(offset),
+        (0, 4) => rbpにoffsetを足した位置にediを代入(offset),
+        (1, 4) => rbpにoffsetを足した位置にesiを代入(offset),
+        (2, 4) => rbpにoffsetを足した位置にedxを代入(offset),
+        (3, 4) => rbpにoffsetを足した位置にecxを代入(offset),
+        (4, 4) => rbpにoffsetを足した位置にr8dを代入(offset),
+        (5, 4) => rbpにoffsetを足した位置にr9dを代入(offset),
+        (0, 1) => rbpにoffsetを足した位置にdilを代入(offset),
+        (1, 1) => rbpにoffsetを足した位置にsilを代入(offset),
+        (2, 1) => rbpにoffsetを足した位置にdlを代入(offset),
+        (3, 1) => rbpにoffsetを足した位置にclを代入(offset),
+        (4, 1) => rbpにoffsetを足した位置にr8bを代入(offset),
+        (5, 1) => rbpにoffsetを足した位置にr9bを代入(offset),
+        // 整数レジスタで渡されるのは、1, 4, 8 バイトのスカラーと、8 バイトごとに分けた構造体だけ
+        (0..=5, _) => unreachable!("{size} バイトの値は引数レジスタで渡されません"),
+        (_, _) => panic!("引数レジスタは 6 つしかありません"),
+    }
+}
@@ -1,11 +1,13 @@
 #![warn(clippy::pedantic)]
 use c_to_elf_compiler::apperror::AppError;
 use c_to_elf_compiler::codegen;
+use c_to_elf_compiler::parse::context::Context;
 use c_to_elf_compiler::parse::toplevel;
 use c_to_elf_compiler::parse::toplevel::parse_toplevel_definition;
 use c_to_elf_compiler::parse::toplevel::FunctionDefinition;
 use c_to_elf_compiler::parse::toplevel::FunctionSignature;
 use c_to_elf_compiler::parse::toplevel::GlobalDeclarations;
+use c_to_elf_compiler::parse::toplevel::Linkage;
 use c_to_elf_compiler::parse::toplevel::SymbolDeclaration;
 use c_to_elf_compiler::parse::toplevel::ToplevelDefOrDecl;
 use c_to_elf_compiler::parse::typ::Type;
@@ -13,7 +15,7 @@ use c_to_elf_compiler::strlit_collector::StrLitCollector;
 use c_to_elf_compiler::token::Token;
 use c_to_elf_compiler::tokenize;
 use c_to_elf_compiler::Buf;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 
 fn main() -> std::io::Result<()> {
@@ -37,36 +39,59 @@ int atoi(const char *s) {
     return n;
 }
 
-int printf(const char *fmt, void *arg1) {
-    while (*fmt) {
-        if (*fmt == '%') {
-            fmt++;
-            if (*fmt == 'd') {
-                void *arg = &arg1;
-                int *p = arg;
-                int n = *p;
-                // convert to decimal string
-                char buf[20];
-                int i = 0;
-                if (n < 0) {
-                    __builtin_putchar('-');
-                    n = -n;
-                }
-                buf[i++] = '0' + n % 10;
-                n = n / 10;
-                while (n) {
-                    buf[i++] = '0' + n % 10;
-                    n = n / 10;
-                }
-                while (i) {
-                    __builtin_putchar(buf[--i]);
-                }
-            }
-        } else {
+typedef struct __va_list_tag {
+    int gp_offset;
+    int fp_offset;
+    void *overflow_arg_area;
+    void *reg_save_area;
+} va_list[1];
+
+int printf(const char *fmt, ...) {
+    va_list ap;
+    va_start(ap, fmt);
+    for (; *fmt; fmt++) {
+        if (*fmt != '%') {
             __builtin_putchar(*fmt);
+            continue;
         }
         fmt++;
+        switch (*fmt) {
+        case 'd': {
+            int n = va_arg(ap, int);
+            // convert to decimal string. n is kept negative so that INT_MIN does not overflow
+            char buf[20];
+            int i = 0;
+            if (n < 0) {
+                __builtin_putchar('-');
+            } else {
+                n = -n;
+            }
+            do {
+                buf[i++] = '0' - n % 10;
+                n = n / 10;
+            } while (n);
+            while (i) {
+                __builtin_putchar(buf[--i]);
+            }
+            break;
+        }
+        case 'c':
+            __builtin_putchar(va_arg(ap, int));
+            break;
+        case 's': {
+            const char *s = va_arg(ap, const char *);
+            while (*s) {
+                __builtin_putchar(*s);
+                s++;
+            }
+            break;
+        }
+        case '%':
+            __builtin_putchar('%');
+            break;
+        }
     }
+    va_end(ap);
     return 0;
 }
     ",
@@ -97,6 +122,7 @@ fn parse_and_codegen(tokens: &[Token], input: &str, filename: &str) -> Result<Ve
             "__builtin_three".to_string(),
             FunctionSignature {
                 params: Some(Vec::new()),
+                is_variadic: false,
                 pos: 0,
                 return_type: Type::Int,
             },
@@ -105,6 +131,7 @@ fn parse_and_codegen(tokens: &[Token], input: &str, filename: &str) -> Result<Ve
             "__builtin_putchar".to_string(),
             FunctionSignature {
                 params: Some(vec![Type::Int]),
+                is_variadic: false,
                 pos: 0,
                 return_type: Type::Int,
             },
@@ -113,6 +140,7 @@ fn parse_and_codegen(tokens: &[Token], input: &str, filename: &str) -> Result<Ve
             "__builtin_alloc4".to_string(),
             FunctionSignature {
                 params: Some(vec![Type::Int, Type::Int, Type::Int, Type::Int]),
+                is_variadic: false,
                 pos: 0,
                 return_type: Type::Ptr(Box::new(Type::Int)),
             },
@@ -124,16 +152,18 @@ fn parse_and_codegen(tokens: &[Token], input: &str, filename: &str) -> Result<Ve
     let mut global_declarations = GlobalDeclarations {
         symbols: HashMap::new(),
         struct_names: HashMap::new(),
+        enum_tags: HashSet::new(),
+        file_scope_tags: HashSet::new(),
     };
     global_declarations.symbols.extend(
         signatures_of_builtin_functions
             .into_iter()
-            .map(|(name, signature)| (name, SymbolDeclaration::Func(signature))),
+            .map(|(name, signature)| (name, SymbolDeclaration::Func(signature, Linkage::External))),
     );
 
     let mut strlit_collector: StrLitCollector = StrLitCollector::new();
 
-    let function_definitions = toplevel::parse_all(
+    let (function_definitions, global_variable_definitions) = toplevel::parse_all(
         &mut strlit_collector,
         &mut global_declarations,
         &mut tokens,
@@ -173,6 +203,7 @@ fn parse_and_codegen(tokens: &[Token], input: &str, filename: &str) -> Result<Ve
             &mut global_function_table,
             &mut buf,
             &definition,
+            &global_declarations.struct_names,
         );
     }
 
@@ -186,20 +217,26 @@ fn parse_and_codegen(tokens: &[Token], input: &str, filename: &str) -> Result<Ve
         let mut tokens = tokens.iter().peekable();
         let previous_symbol_declarations: HashMap<String, SymbolDeclaration> = [(
             "main".to_string(),
-            SymbolDeclaration::Func(FunctionSignature {
-                params: Some(vec![]), // todo: possible argc and argv
-                pos: 0,
-                return_type: Type::Int,
-            }),
+            SymbolDeclaration::Func(
+                FunctionSignature {
+                    params: Some(vec![]), // todo: possible argc and argv
+                    is_variadic: false,
+                    pos: 0,
+                    return_type: Type::Int,
+                },
+                Linkage::External,
+            ),
         )]
         .into_iter()
         .collect();
-        if let ToplevelDefOrDecl::FuncDef(entry) = parse_toplevel_definition(
+        if let (_, ToplevelDefOrDecl::FuncDef(entry)) = parse_toplevel_definition(
             &mut StrLitCollector::new(), // do not collect any string literals from the startup code
-            &GlobalDeclarations {
+            &mut Context::new_file_scope(GlobalDeclarations {
                 symbols: previous_symbol_declarations,
                 struct_names: HashMap::new(),
-            },
+                enum_tags: HashSet::new(),
+                file_scope_tags: HashSet::new(),
+            }),
             &mut tokens,
             filename,
             input,
@@ -213,9 +250,54 @@ fn parse_and_codegen(tokens: &[Token], input: &str, filename: &str) -> Result<Ve
         &mut global_function_table,
         &mut buf,
         &entry,
+        &global_declarations.struct_names,
+    );
+
+    codegen::関数のアドレスを埋める(&mut buf, &global_function_table);
+
+    // 翻訳単位は一つだけなので、extern で宣言された変数は、使われていればこのファイルの中で定義されていなければならない
+    if let Some((name, decl)) = global_declarations
+        .symbols
+        .iter()
+        .filter_map(|(name, decl)| match decl {
+            SymbolDeclaration::GVar(decl) if !decl.is_defined => Some((name, decl)),
+            _ => None,
+        })
+        .filter(|(name, _)| {
+            codegen::グローバル変数が参照されている(
+                &buf,
+                &global_variable_definitions,
+                name,
+            )
+        })
+        .min_by_key(|(_, decl)| decl.pos)
+    {
+        return Err(AppError {
+            message: format!(
+                "変数 {name} は extern で宣言されて使われていますが、このファイルの中で定義されていません"
+            ),
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos: decl.pos,
+        });
+    }
+
+    let bss_end = codegen::グローバル変数を配置(
+        &mut buf,
+        &global_variable_definitions,
+        &global_function_table,
+        &global_declarations.struct_names,
     );
 
     let mut buf = buf.to_vec();
+
+    // プログラムヘッダの p_filesz と p_memsz を書き換え。
+    // セグメントはファイルの 0x78 バイト目から始まり、p_memsz が p_filesz を超えた部分 (.bss) はゼロで埋められる
+    let filesz = (buf.len() - 0x78) as u64;
+    let memsz = u64::from(bss_end) - 0x78;
+    buf[0x60..0x68].copy_from_slice(&filesz.to_le_bytes());
+    buf[0x68..0x70].copy_from_slice(&memsz.to_le_bytes());
+
     // エントリポイント書き換え
     let entry_pos_buf = entry_pos.to_le_bytes();
     buf[0x18] = entry_pos_buf[0];
@@ -1,14 +1,42 @@
-use std::{collections::HashMap, vec};
+use std::{
+    collections::{HashMap, HashSet},
+    vec,
+};
 
+use crate::ast::Expr;
 use crate::parse::{
-    toplevel::{GlobalDeclarations, SymbolDeclaration, TypeAndSize},
+    statement::仮引数,
+    toplevel::{GlobalDeclarations, GlobalVariableDefinition, SymbolDeclaration, TypeAndSize},
     typ::Type,
 };
 
 type ID = u64;
 
+/// ファイルスコープで確保する隠れたローカル変数の ID の始まり。
+/// 仮引数の型に現れた可変長配列の大きさを保存する変数は、関数定義の本体に引き継がれるので、
+/// 本体で 0 から順に振られる仮引数やローカル変数の ID と重ならないよう、大きな値から振る
+const FIRST_LOCAL_VAR_ID_IN_FILE_SCOPE: ID = 1 << 32;
+
+/// 可変長配列の大きさや、スタックポインタの保存先のように、コンパイラが内部で使うローカル変数の名前。
+/// 識別子としては書けない名前なので、ソースコード中の変数とは衝突しない
+pub const 隠れたローカル変数の名前: &str = "(隠れたローカル変数)";
+
+/// ブロックスコープで宣言された通常の識別子
+#[derive(Debug, Clone)]
+enum LocalDeclaration {
+    Var(ID, TypeAndSize),
+    /// static なローカル変数や、ブロックスコープでの extern 宣言。記憶域は関数の外にあり、グローバル変数として名前で参照される
+    StaticVar(String, TypeAndSize),
+    EnumConst(i32),
+    Typedef(Type),
+}
+
 pub struct Context {
-    currently_active_local_var_and_param_declarations: Vec<HashMap<String, (ID, TypeAndSize)>>,
+    /// 空である場合、この Context はファイルスコープを表す
+    currently_active_local_var_and_param_declarations: Vec<HashMap<String, LocalDeclaration>>,
+    /// ブロックスコープで宣言された構造体・共用体のタグから、struct_names に登録される名前への対応。
+    /// currently_active_local_var_and_param_declarations と同じ深さで積まれる
+    currently_active_struct_tags: Vec<HashMap<String, String>>,
     pub return_type: Type,
     pub global_declarations: GlobalDeclarations,
 
@@ -16,6 +44,43 @@ pub struct Context {
     // This is used for codegen.
     all_local_var_declarations: Vec<(String, ID, TypeAndSize)>,
     next_local_var_id: ID,
+
+    /// 関数の中で定義された static なローカル変数や、ファイルスコープの複合リテラル。
+    /// 宣言のパースが終わったら、グローバル変数の定義に加えられる
+    pub static_local_vars: Vec<GlobalVariableDefinition>,
+
+    /// 宣言子の中に現れた可変長配列の大きさを計算して、隠れたローカル変数に保存する式。
+    /// 宣言や sizeof など、その型を使う側が取り出して、先に評価されるようにする
+    pub var_len_arr_sizes: Vec<Expr>,
+
+    /// パース中の関数が可変長引数をとるなら true。va_start はその中でしか使えない
+    pub is_variadic: bool,
+
+    /// パース中の関数の仮引数の数。仮引数の ID は 0 から順に振られるので、最後の仮引数の ID はこれより 1 小さい
+    pub param_count: ID,
+
+    /// パース中の文を囲んでいるループの数。break や continue はループの中にしか書けない
+    pub loop_depth: usize,
+
+    /// パース中の文を囲んでいる switch 文。内側のものほど後ろにある
+    pub switches: Vec<SwitchLabels>,
+
+    /// 関数の中でこれまでに定義されたラベル
+    pub labels: HashSet<String>,
+    /// 関数の中の goto 文の飛び先と、goto 文の位置。
+    /// 後ろで定義されるラベルにも飛べるので、飛び先があるかは関数を全て読み終えてから確かめる
+    pub gotos: Vec<(String, usize)>,
+
+    /// 仮引数の列をパースしている間は、これまでに読んだ仮引数。後ろの仮引数の宣言子から名前で参照でき、
+    /// 関数定義の本体と同じく、列の中の位置を ID とするローカル変数になる。仮引数の列の外では `None`
+    pub preceding_params: Option<Vec<仮引数>>,
+}
+
+/// switch 文の本体に、これまでに現れた case と default
+#[derive(Debug, Default)]
+pub struct SwitchLabels {
+    pub cases: Vec<i32>,
+    pub has_default: bool,
 }
 
 impl Context {
@@ -30,30 +95,71 @@ impl Context {
         let mut next_local_var_id = 0;
         let mut param_declarations_with_ids = HashMap::new();
         for (ident, typ_and_size) in param_declarations.iter() {
-            param_declarations_with_ids
-                .insert(ident.clone(), (next_local_var_id, typ_and_size.clone()));
+            param_declarations_with_ids.insert(
+                ident.clone(),
+                LocalDeclaration::Var(next_local_var_id, typ_and_size.clone()),
+            );
             next_local_var_id += 1;
         }
 
         Self {
             currently_active_local_var_and_param_declarations: vec![param_declarations_with_ids],
+            currently_active_struct_tags: vec![HashMap::new()],
             global_declarations,
             all_local_var_declarations: vec![],
             next_local_var_id,
             return_type,
+            static_local_vars: vec![],
+            var_len_arr_sizes: vec![],
+            is_variadic: false,
+            param_count: next_local_var_id,
+            loop_depth: 0,
+            switches: vec![],
+            labels: HashSet::new(),
+            gotos: vec![],
+            preceding_params: None,
+        }
+    }
+
+    /// 関数の外側で、グローバルな宣言をパースするための Context
+    pub fn new_file_scope(global_declarations: GlobalDeclarations) -> Self {
+        Self {
+            currently_active_local_var_and_param_declarations: vec![],
+            currently_active_struct_tags: vec![],
+            global_declarations,
+            all_local_var_declarations: vec![],
+            next_local_var_id: FIRST_LOCAL_VAR_ID_IN_FILE_SCOPE,
+            return_type: Type::Void,
+            static_local_vars: vec![],
+            var_len_arr_sizes: vec![],
+            is_variadic: false,
+            param_count: 0,
+            loop_depth: 0,
+            switches: vec![],
+            labels: HashSet::new(),
+            gotos: vec![],
+            preceding_params: None,
         }
     }
 
+    /// 関数の外側にいるなら true
+    pub fn is_file_scope(&self) -> bool {
+        self.currently_active_local_var_and_param_declarations
+            .is_empty()
+    }
+
     pub fn push_new_scope(&mut self) {
         // push a new scope for local variable declarations
         self.currently_active_local_var_and_param_declarations
             .push(HashMap::new());
+        self.currently_active_struct_tags.push(HashMap::new());
     }
 
     pub fn pop_scope(&mut self) {
         // pop the most recent scope for local variable declarations
         if self.currently_active_local_var_and_param_declarations.len() > 1 {
             self.currently_active_local_var_and_param_declarations.pop();
+            self.currently_active_struct_tags.pop();
         } else {
             panic!("スコープをこれ以上ポップできません。");
         }
@@ -78,14 +184,240 @@ impl Context {
         self.all_local_var_declarations
             .push((ident.clone(), id, typ_and_size.clone()));
 
-        current_scope.insert(ident, (id, typ_and_size));
+        current_scope.insert(ident, LocalDeclaration::Var(id, typ_and_size));
+        id
+    }
+
+    /// これまでに確保したローカル変数の宣言を取り出す。ファイルスコープでは、仮引数の型に現れた可変長配列の
+    /// 大きさを保存する隠れたローカル変数が確保されるので、関数定義ならそれを本体の Context に引き継ぐ
+    pub fn take_local_var_declarations(&mut self) -> Vec<(String, ID, TypeAndSize)> {
+        std::mem::take(&mut self.all_local_var_declarations)
+    }
+
+    /// `take_local_var_declarations` で取り出した宣言を、この関数のローカル変数として加える
+    pub fn inherit_local_var_declarations(
+        &mut self,
+        mut declarations: Vec<(String, ID, TypeAndSize)>,
+    ) {
+        self.all_local_var_declarations.append(&mut declarations);
+    }
+
+    /// どのスコープからも名前で参照できない、コンパイラが内部で使うローカル変数を確保する
+    #[must_use]
+    pub fn insert_hidden_local_var(&mut self, typ_and_size: TypeAndSize) -> u64 {
+        let id = self.next_local_var_id;
+        self.next_local_var_id += 1;
+        self.all_local_var_declarations.push((
+            隠れたローカル変数の名前.to_string(),
+            id,
+            typ_and_size,
+        ));
         id
     }
 
+    /// 記憶域が関数の外にある変数を、現在のスコープで宣言する。`global_name` はコード生成でその変数を指す名前
+    pub fn insert_static_local_var(
+        &mut self,
+        ident: String,
+        global_name: String,
+        typ_and_size: TypeAndSize,
+    ) -> Result<(), String> {
+        let current_scope = self
+            .currently_active_local_var_and_param_declarations
+            .last_mut()
+            .expect("現在のスコープが存在しません");
+        if current_scope.contains_key(&ident) {
+            return Err(format!(
+                "変数 {ident} と同じ名前の識別子が既にこのスコープで宣言されています"
+            ));
+        }
+        current_scope.insert(
+            ident,
+            LocalDeclaration::StaticVar(global_name, typ_and_size),
+        );
+        Ok(())
+    }
+
+    pub fn insert_enum_const(&mut self, ident: String, value: i32) -> Result<(), String> {
+        // We insert the enumerator into the most recent scope, or into the file scope if we are not in a function
+        if let Some(current_scope) = self
+            .currently_active_local_var_and_param_declarations
+            .last_mut()
+        {
+            if current_scope.contains_key(&ident) {
+                return Err(format!(
+                    "列挙子 {ident} と同じ名前の識別子が既にこのスコープで宣言されています"
+                ));
+            }
+            current_scope.insert(ident, LocalDeclaration::EnumConst(value));
+            return Ok(());
+        }
+
+        match self.global_declarations.symbols.get(&ident) {
+            None => {
+                self.global_declarations
+                    .symbols
+                    .insert(ident, SymbolDeclaration::EnumConst(value));
+                Ok(())
+            }
+            Some(_) => Err(format!(
+                "列挙子 {ident} と同じ名前の識別子が既にファイルスコープで宣言されています"
+            )),
+        }
+    }
+
+    pub fn insert_typedef(&mut self, ident: String, typ: Type) -> Result<(), String> {
+        // We insert the typedef name into the most recent scope, or into the file scope if we are not in a function
+        if let Some(current_scope) = self
+            .currently_active_local_var_and_param_declarations
+            .last_mut()
+        {
+            match current_scope.get(&ident) {
+                None => {}
+                Some(LocalDeclaration::Typedef(prev)) if *prev == typ => return Ok(()),
+                Some(_) => {
+                    return Err(format!(
+                        "typedef 名 {ident} と同じ名前の識別子が既にこのスコープで宣言されています"
+                    ))
+                }
+            }
+            current_scope.insert(ident, LocalDeclaration::Typedef(typ));
+            return Ok(());
+        }
+
+        match self.global_declarations.symbols.get(&ident) {
+            None => {
+                self.global_declarations
+                    .symbols
+                    .insert(ident, SymbolDeclaration::Typedef(typ));
+                Ok(())
+            }
+            // 同じ型への typedef の再定義は許される
+            Some(SymbolDeclaration::Typedef(prev)) if *prev == typ => Ok(()),
+            Some(_) => Err(format!(
+                "typedef 名 {ident} と同じ名前の識別子が既にファイルスコープで宣言されています"
+            )),
+        }
+    }
+
+    /// 構造体・共用体のタグを現在のスコープで宣言し、struct_names に登録するための名前を返す。
+    /// ファイルスコープのタグはそのままの名前で、ブロックスコープのタグは宣言位置を付けた名前で登録する
+    pub fn declare_struct_tag(&mut self, tag: &str, pos: usize) -> String {
+        match self.currently_active_struct_tags.last_mut() {
+            None => {
+                self.global_declarations
+                    .file_scope_tags
+                    .insert(tag.to_string());
+                tag.to_string()
+            }
+            Some(current_scope) => current_scope
+                .entry(tag.to_string())
+                .or_insert_with(|| format!("{tag}@{pos}"))
+                .clone(),
+        }
+    }
+
+    /// 構造体・共用体のタグを、内側のスコープから順に探して struct_names での名前に解決する。
+    /// どのスコープでも宣言されていなければ、現在のスコープで不完全型のタグとして宣言する
+    pub fn resolve_struct_tag(&mut self, tag: &str, pos: usize) -> String {
+        for scope in self.currently_active_struct_tags.iter().rev() {
+            if let Some(struct_name) = scope.get(tag) {
+                return struct_name.clone();
+            }
+        }
+        if self.global_declarations.file_scope_tags.contains(tag) {
+            return tag.to_string();
+        }
+        self.declare_struct_tag(tag, pos)
+    }
+
+    /// 定義が見つかっていない構造体（やその配列）のような不完全型であれば、エラーメッセージを返す
+    pub fn ensure_complete_type(&self, typ: &Type) -> Result<(), String> {
+        match typ.unqualified() {
+            Type::Struct { struct_name }
+                if !self
+                    .global_declarations
+                    .struct_names
+                    .contains_key(struct_name) =>
+            {
+                Err(format!(
+                    "構造体 {} は不完全型なので、ここでは使えません",
+                    tag_of_struct_name(struct_name)
+                ))
+            }
+            Type::Arr(t, _) | Type::VarLenArr(t, _) => self.ensure_complete_type(t),
+            Type::IncompleteArr(_) => {
+                Err("大きさの分からない配列は不完全型なので、ここでは使えません".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 識別子が typedef 名を指しているなら、その型を返す
+    pub fn resolve_typedef(&self, ident: &str) -> Option<Type> {
+        for scope in self
+            .currently_active_local_var_and_param_declarations
+            .iter()
+            .rev()
+        {
+            match scope.get(ident) {
+                Some(LocalDeclaration::Typedef(typ)) => return Some(typ.clone()),
+                Some(
+                    LocalDeclaration::Var(..)
+                    | LocalDeclaration::StaticVar(..)
+                    | LocalDeclaration::EnumConst(_),
+                ) => return None,
+                None => {}
+            }
+        }
+
+        match self.global_declarations.symbols.get(ident) {
+            Some(SymbolDeclaration::Typedef(typ)) => Some(typ.clone()),
+            _ => None,
+        }
+    }
+
+    /// 識別子が列挙子を指しているなら、その値を返す
+    pub fn resolve_enum_const(&self, ident: &str) -> Option<i32> {
+        for scope in self
+            .currently_active_local_var_and_param_declarations
+            .iter()
+            .rev()
+        {
+            match scope.get(ident) {
+                Some(LocalDeclaration::EnumConst(value)) => return Some(*value),
+                Some(
+                    LocalDeclaration::Var(..)
+                    | LocalDeclaration::StaticVar(..)
+                    | LocalDeclaration::Typedef(_),
+                ) => return None,
+                None => {}
+            }
+        }
+
+        match self.global_declarations.symbols.get(ident) {
+            Some(SymbolDeclaration::EnumConst(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// 変数の型を返す。あわせて、ローカル変数ならその ID を、そうでなければコード生成で使うグローバルな名前を返す
     pub fn resolve_type_and_size_as_var(
         &self,
         ident: &str,
-    ) -> Result<(Option<ID>, TypeAndSize), String> {
+    ) -> Result<(Option<ID>, String, TypeAndSize), String> {
+        if let Some(i) = self
+            .preceding_params
+            .iter()
+            .flatten()
+            .position(|(_, name)| matches!(name, Some((name, _)) if name == ident))
+        {
+            let typ = self.preceding_params.as_ref().unwrap()[i].0.clone();
+            let size = self
+                .ensure_complete_type(&typ)
+                .map_or(0, |()| typ.sizeof(&self.global_declarations.struct_names));
+            return Ok((Some(i as ID), ident.to_string(), TypeAndSize { typ, size }));
+        }
         // loop from the most recent scope to the oldest scope
         // thus, an inverse iteration of Vec
         for scope in self
@@ -93,8 +425,22 @@ impl Context {
             .iter()
             .rev()
         {
-            if let Some((id, typ_and_size)) = scope.get(ident) {
-                return Ok((Some(*id), typ_and_size.clone()));
+            match scope.get(ident) {
+                Some(LocalDeclaration::Var(id, typ_and_size)) => {
+                    return Ok((Some(*id), ident.to_string(), typ_and_size.clone()));
+                }
+                Some(LocalDeclaration::StaticVar(global_name, typ_and_size)) => {
+                    return Ok((None, global_name.clone(), typ_and_size.clone()));
+                }
+                Some(LocalDeclaration::EnumConst(_)) => {
+                    return Err(format!("識別子 {ident} は列挙子であり、変数ではありません"));
+                }
+                Some(LocalDeclaration::Typedef(_)) => {
+                    return Err(format!(
+                        "識別子 {ident} は typedef 名であり、変数ではありません"
+                    ));
+                }
+                None => {}
             }
         }
 
@@ -105,15 +451,25 @@ impl Context {
         }
 
         match self.global_declarations.symbols.get(ident) {
-            Some(SymbolDeclaration::GVar(t)) => Ok((
+            Some(SymbolDeclaration::GVar(decl)) => Ok((
                 None,
+                ident.to_string(),
                 TypeAndSize {
-                    typ: t.clone(),
-                    size: t.sizeof(&self.global_declarations.struct_names),
+                    typ: decl.typ.clone(),
+                    // extern 宣言された変数の型は、`int a[]` のように不完全型のこともある
+                    size: self.ensure_complete_type(&decl.typ).map_or(0, |()| {
+                        decl.typ.sizeof(&self.global_declarations.struct_names)
+                    }),
                 },
             )),
-            Some(SymbolDeclaration::Func(_u)) => Err(format!(
-                "識別子 {ident} は関数であり、現在関数ポインタは実装されていません",
+            Some(SymbolDeclaration::Func(..)) => {
+                Err(format!("識別子 {ident} は関数であり、変数ではありません",))
+            }
+            Some(SymbolDeclaration::EnumConst(_)) => {
+                Err(format!("識別子 {ident} は列挙子であり、変数ではありません",))
+            }
+            Some(SymbolDeclaration::Typedef(_)) => Err(format!(
+                "識別子 {ident} は typedef 名であり、変数ではありません",
             )),
             None => Err(format!(
                 "識別子 {ident} は定義されておらず、型が分かりません",
@@ -121,3 +477,8 @@ impl Context {
         }
     }
 }
+
+/// struct_names に登録された名前から、ソースコード上のタグを取り出す
+pub fn tag_of_struct_name(struct_name: &str) -> &str {
+    struct_name.split('@').next().unwrap_or(struct_name)
+}
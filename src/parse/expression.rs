@@ -9,13 +9,15 @@ use std::{iter::Peekable, slice::Iter};
 
 use super::combinator::recover;
 use super::combinator::satisfy;
-use super::context::Context;
-use super::toplevel::SymbolDeclaration;
-use super::typ::parse_type;
-use super::typ::Type;
+use super::context::{tag_of_struct_name, Context, 隠れたローカル変数の名前};
+use super::initializer::{parse_initializer, 初期化子を平坦化};
+use super::statement::parse_type_name;
+use super::toplevel::{define_static_variable, SymbolDeclaration};
+use super::typ::is_type_name_start;
+use super::typ::{Qualifiers, Type};
 fn parse_primary(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
@@ -32,6 +34,30 @@ fn parse_primary(
             };
             Ok(expr)
         }
+        Token {
+            tok: tok @ (Tok::True | Tok::False),
+            pos,
+        } => Ok(Expr::Numeric {
+            val: i32::from(*tok == Tok::True),
+            pos: *pos,
+            typ: Type::Bool,
+        }),
+        Token {
+            tok: Tok::FloatNum(bits),
+            pos,
+        } => Ok(Expr::FloatingNumeric {
+            bits: u64::from(*bits),
+            pos: *pos,
+            typ: Type::Float,
+        }),
+        Token {
+            tok: Tok::DoubleNum(bits),
+            pos,
+        } => Ok(Expr::FloatingNumeric {
+            bits: *bits,
+            pos: *pos,
+            typ: Type::Double,
+        }),
         Token {
             tok: Tok::StringLiteral(val),
             pos,
@@ -55,17 +81,52 @@ fn parse_primary(
             関数呼び出しはsuffix opとして処理したいが、`識別子(...)` の形に限られるかつ、関数呼び出しか変数参照かで `識別子` の意味が大きく異なる為ここで処理する
             構文解析と意味解析が分かれていれば問題ないが、ここで `識別子` の型を決めなければいけないため
             */
-            let open_pos = tokens.peek().unwrap().pos;
-            if (recover(tokens, |tokens| {
-                satisfy(
+            if let Some(val) = context.resolve_enum_const(ident) {
+                return Ok(Expr::Numeric {
+                    val,
+                    pos: *ident_pos,
+                    typ: Type::Int,
+                });
+            }
+
+            if let Some(name) = stdarg_builtin_name(ident) {
+                return parse_builtin_va(
+                    strlit_collector,
+                    context,
                     tokens,
                     filename,
                     input,
-                    |tok| tok == &Tok::開き丸括弧,
-                    "開き丸括弧ではありません",
-                )
-            })?)
-            .is_some()
+                    name,
+                    *ident_pos,
+                );
+            }
+
+            if ident == "__builtin_alloca" {
+                return parse_builtin_alloca(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                    *ident_pos,
+                );
+            }
+
+            let open_pos = tokens.peek().unwrap().pos;
+
+            // 関数ポインタ型の変数の呼び出しは、関数呼び出しの後置演算子として parse_suffix_op で処理する
+            let is_variable = context.resolve_type_and_size_as_var(ident).is_ok();
+            if !is_variable
+                && (recover(tokens, |tokens| {
+                    satisfy(
+                        tokens,
+                        filename,
+                        input,
+                        |tok| tok == &Tok::開き丸括弧,
+                        "開き丸括弧ではありません",
+                    )
+                })?)
+                .is_some()
             {
                 let mut args = Vec::new();
 
@@ -99,6 +160,7 @@ fn parse_primary(
 
                             FunctionSignature {
                                 params: Some(Vec::new()),
+                                is_variadic: false,
                                 pos: *ident_pos,
                                 return_type: Type::Arr(
                                     Box::new(Type::Char),
@@ -107,7 +169,7 @@ fn parse_primary(
                             }
                         } else {
                             match context.global_declarations.symbols.get(ident) {
-                                Some(SymbolDeclaration::Func(f)) => f.clone(),
+                                Some(SymbolDeclaration::Func(f, _)) => f.clone(),
                                 Some(SymbolDeclaration::GVar(_)) => {
                                     return Err(AppError {
                                         message: format!(
@@ -118,6 +180,26 @@ fn parse_primary(
                                         pos: *ident_pos,
                                     })
                                 }
+                                Some(SymbolDeclaration::EnumConst(_)) => {
+                                    return Err(AppError {
+                                        message: format!(
+                                            "{ident} は関数ではなく列挙子であり、呼び出せません",
+                                        ),
+                                        input: input.to_string(),
+                                        filename: filename.to_string(),
+                                        pos: *ident_pos,
+                                    })
+                                }
+                                Some(SymbolDeclaration::Typedef(_)) => {
+                                    return Err(AppError {
+                                        message: format!(
+                                    "{ident} は関数ではなく typedef 名であり、呼び出せません",
+                                ),
+                                        input: input.to_string(),
+                                        filename: filename.to_string(),
+                                        pos: *ident_pos,
+                                    })
+                                }
                                 None => {
                                     return Err(AppError {
                                         message: format!(
@@ -131,16 +213,30 @@ fn parse_primary(
                             }
                         }
                     };
+                    context
+                        .ensure_complete_type(&func_decl.return_type)
+                        .map_err(|message| AppError {
+                            message,
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos: *ident_pos,
+                        })?;
                     let expr = Expr::Call {
                         ident: ident.clone(),
-                        args,
+                        args: convert_args(
+                            args,
+                            func_decl.params.as_ref(),
+                            input,
+                            filename,
+                            *ident_pos,
+                        )?,
                         pos: *ident_pos,
                         typ: func_decl.return_type,
                     };
                     return Ok(expr);
                 } else {
-                    let expr = parse_expr(strlit_collector, context, tokens, filename, input)?;
-                    args.push(*decay_if_arr(expr));
+                    let expr = parse_argument(strlit_collector, context, tokens, filename, input)?;
+                    args.push(expr);
                 }
 
                 loop {
@@ -156,7 +252,7 @@ fn parse_primary(
                     .is_some()
                     {
                         let func_decl = match context.global_declarations.symbols.get(ident) {
-                            Some(SymbolDeclaration::Func(f)) => f.clone(),
+                            Some(SymbolDeclaration::Func(f, _)) => f.clone(),
                             Some(SymbolDeclaration::GVar(_)) => {
                                 return Err(AppError {
                                     message: format!(
@@ -167,6 +263,26 @@ fn parse_primary(
                                     pos: *ident_pos,
                                 })
                             }
+                            Some(SymbolDeclaration::EnumConst(_)) => {
+                                return Err(AppError {
+                                    message: format!(
+                                        "{ident} は関数ではなく列挙子であり、呼び出せません",
+                                    ),
+                                    input: input.to_string(),
+                                    filename: filename.to_string(),
+                                    pos: *ident_pos,
+                                })
+                            }
+                            Some(SymbolDeclaration::Typedef(_)) => {
+                                return Err(AppError {
+                                    message: format!(
+                                        "{ident} は関数ではなく typedef 名であり、呼び出せません",
+                                    ),
+                                    input: input.to_string(),
+                                    filename: filename.to_string(),
+                                    pos: *ident_pos,
+                                })
+                            }
                             None => {
                                 return Err(AppError {
                                     message: format!(
@@ -178,9 +294,23 @@ fn parse_primary(
                                 })
                             }
                         };
+                        context
+                            .ensure_complete_type(&func_decl.return_type)
+                            .map_err(|message| AppError {
+                                message,
+                                input: input.to_string(),
+                                filename: filename.to_string(),
+                                pos: *ident_pos,
+                            })?;
                         let expr = Expr::Call {
                             ident: ident.clone(),
-                            args,
+                            args: convert_args(
+                                args,
+                                func_decl.params.as_ref(),
+                                input,
+                                filename,
+                                *ident_pos,
+                            )?,
                             pos: *ident_pos,
                             typ: func_decl.return_type,
                         };
@@ -196,8 +326,9 @@ fn parse_primary(
                     })?)
                     .is_some()
                     {
-                        let expr = parse_expr(strlit_collector, context, tokens, filename, input)?;
-                        args.push(*decay_if_arr(expr));
+                        let expr =
+                            parse_argument(strlit_collector, context, tokens, filename, input)?;
+                        args.push(expr);
                     } else {
                         break Err(AppError {
                             message: "閉じ丸括弧かカンマが期待されていました".to_string(),
@@ -208,7 +339,29 @@ fn parse_primary(
                     }
                 }
             } else {
-                let (local_var_id, TypeAndSize { typ, .. }) =
+                if !is_variable {
+                    if let Some(SymbolDeclaration::Func(
+                        FunctionSignature {
+                            params,
+                            is_variadic,
+                            return_type,
+                            ..
+                        },
+                        _,
+                    )) = context.global_declarations.symbols.get(ident)
+                    {
+                        return Ok(Expr::DecayedFunc {
+                            ident: ident.clone(),
+                            pos: *ident_pos,
+                            typ: Type::Ptr(Box::new(Type::Func {
+                                return_type: Box::new(return_type.clone()),
+                                params: params.clone(),
+                                is_variadic: *is_variadic,
+                            })),
+                        });
+                    }
+                }
+                let (local_var_id, name, TypeAndSize { typ, .. }) =
                     match context.resolve_type_and_size_as_var(ident) {
                         Ok(t) => t.clone(),
                         Err(message) => {
@@ -221,7 +374,7 @@ fn parse_primary(
                         }
                     };
                 let expr = Expr::Identifier {
-                    ident: ident.clone(),
+                    ident: name,
                     pos: *ident_pos,
                     local_var_id,
                     typ,
@@ -252,6 +405,218 @@ fn parse_primary(
     }
 }
 
+/// `__builtin_alloca` の後の `(size)` をパースする。
+/// 呼び出し元のスタックフレームに領域を確保する必要があるので、関数呼び出しではなく専用の式にする
+fn parse_builtin_alloca(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    pos: usize,
+) -> Result<Expr, AppError> {
+    satisfy(
+        tokens,
+        filename,
+        input,
+        |tok| tok == &Tok::開き丸括弧,
+        "__builtin_alloca の後に開き丸括弧がありません",
+    )?;
+    let size_pos = tokens.peek().unwrap().pos;
+    let size = parse_assignment_expr(strlit_collector, context, tokens, filename, input)?;
+    if !size.typ().is_arithmetic() || size.typ().is_floating() {
+        return Err(AppError {
+            message: "__builtin_alloca の引数は整数でなければなりません".to_string(),
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos: size_pos,
+        });
+    }
+    satisfy(
+        tokens,
+        filename,
+        input,
+        |tok| tok == &Tok::閉じ丸括弧,
+        "__builtin_alloca の引数の後に閉じ丸括弧がありません",
+    )?;
+    Ok(Expr::Alloca {
+        size: convert_arithmetic(Box::new(size), &Type::Int, size_pos),
+        pos,
+        typ: Type::Ptr(Box::new(Type::Void)),
+    })
+}
+
+/// `va_list` の要素の構造体のタグ。プリプロセッサがないので、`va_list` は `<stdarg.h>` の代わりに
+/// main.rs の前置きで、この構造体の要素一つの配列として typedef される
+const VA_LIST_TAG: &str = "__va_list_tag";
+
+/// `va_start` などのマクロ名か、それに対応する `__builtin_va_start` などの名前なら、マクロ名を返す
+fn stdarg_builtin_name(ident: &str) -> Option<&str> {
+    let name = ident.strip_prefix("__builtin_").unwrap_or(ident);
+    matches!(name, "va_start" | "va_arg" | "va_end" | "va_copy").then_some(name)
+}
+
+/// `va_start(ap, last)`・`va_arg(ap, type)`・`va_end(ap)`・`va_copy(dest, src)` の、名前の後をパースする
+#[allow(clippy::too_many_arguments)]
+fn parse_builtin_va(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    name: &str,
+    pos: usize,
+) -> Result<Expr, AppError> {
+    let error_at = |message: String, pos: usize| AppError {
+        message,
+        input: input.to_string(),
+        filename: filename.to_string(),
+        pos,
+    };
+    satisfy(
+        tokens,
+        filename,
+        input,
+        |tok| tok == &Tok::開き丸括弧,
+        &format!("{name} の後に開き丸括弧がありません"),
+    )?;
+    let mut parse_va_list = |context: &mut Context,
+                             tokens: &mut Peekable<Iter<Token>>|
+     -> Result<Box<Expr>, AppError> {
+        let arg_pos = tokens.peek().unwrap().pos;
+        let ap = decay_if_arr(parse_assignment_expr(
+            strlit_collector,
+            context,
+            tokens,
+            filename,
+            input,
+        )?);
+        match ap.typ() {
+            Type::Ptr(t)
+                if *t.unqualified()
+                    == (Type::Struct {
+                        struct_name: VA_LIST_TAG.to_string(),
+                    }) =>
+            {
+                Ok(ap)
+            }
+            t => Err(error_at(
+                format!("{name} の引数の型が {t:?} ですが、va_list でなければなりません"),
+                arg_pos,
+            )),
+        }
+    };
+    let comma = |tokens: &mut Peekable<Iter<Token>>| {
+        satisfy(
+            tokens,
+            filename,
+            input,
+            |tok| tok == &Tok::Comma,
+            &format!("{name} の引数の間にカンマがありません"),
+        )
+    };
+
+    let ap = parse_va_list(context, tokens)?;
+    let expr = match name {
+        "va_start" => {
+            if !context.is_variadic {
+                return Err(error_at(
+                    "va_start が、可変長引数をとらない関数の中で使われています".to_string(),
+                    pos,
+                ));
+            }
+            // 最後の名前付きの仮引数は、可変長引数の位置を知るのには要らないので、確かめた後は読み捨てる
+            comma(tokens)?;
+            let param_pos = tokens.peek().unwrap().pos;
+            let param = parse_assignment_expr(
+                &mut StrLitCollector::new(),
+                context,
+                tokens,
+                filename,
+                input,
+            )?;
+            let is_last_param = match param {
+                Expr::Identifier {
+                    local_var_id: Some(id),
+                    ..
+                } => id + 1 == context.param_count,
+                _ => false,
+            };
+            if !is_last_param {
+                return Err(error_at(
+                    "va_start の二つ目の引数は、最後の名前付きの仮引数でなければなりません"
+                        .to_string(),
+                    param_pos,
+                ));
+            }
+            Expr::VaStart {
+                ap,
+                pos,
+                typ: Type::Void,
+            }
+        }
+        "va_arg" => {
+            comma(tokens)?;
+            let type_pos = tokens.peek().unwrap().pos;
+            let typ = parse_type_name(context, tokens, filename, input)?;
+            context
+                .ensure_complete_type(&typ)
+                .map_err(|message| error_at(message, type_pos))?;
+            let typ = typ.unqualified().clone();
+            if !(typ.is_arithmetic() || matches!(typ, Type::Ptr(_) | Type::Struct { .. }))
+                || typ == Type::Float
+            {
+                return Err(error_at(
+                    format!("va_arg で {typ:?} 型の値は取り出せません"),
+                    type_pos,
+                ));
+            }
+            Expr::VaArg { ap, pos, typ }
+        }
+        "va_end" => Expr::Cast {
+            expr: ap,
+            pos,
+            typ: Type::Void,
+        },
+        "va_copy" => {
+            comma(tokens)?;
+            let src = parse_va_list(context, tokens)?;
+            let deref = |expr: Box<Expr>| {
+                Box::new(Expr::UnaryExpr {
+                    op: UnaryOp::Deref,
+                    op_pos: pos,
+                    expr,
+                    typ: Type::Struct {
+                        struct_name: VA_LIST_TAG.to_string(),
+                    },
+                })
+            };
+            Expr::Cast {
+                expr: Box::new(Expr::BinaryExpr {
+                    op: BinaryOp::Assign,
+                    op_pos: pos,
+                    左辺: deref(ap),
+                    右辺: deref(src),
+                    typ: Type::Struct {
+                        struct_name: VA_LIST_TAG.to_string(),
+                    },
+                }),
+                pos,
+                typ: Type::Void,
+            }
+        }
+        _ => unreachable!("{name} は stdarg_builtin_name が返す名前ではありません"),
+    };
+    satisfy(
+        tokens,
+        filename,
+        input,
+        |tok| tok == &Tok::閉じ丸括弧,
+        &format!("{name} の引数の後に閉じ丸括弧がありません"),
+    )?;
+    Ok(expr)
+}
+
 fn search_struct_member<'a>(
     context: &'a Context,
     struct_name: &str,
@@ -264,11 +629,24 @@ fn search_struct_member<'a>(
         .global_declarations
         .struct_names
         .get(struct_name)
-        .and_then(|s| s.members.get(ident))
+        .ok_or_else(|| AppError {
+            message: format!(
+                "構造体 {} は不完全型なので、メンバー {ident} にアクセスできません",
+                tag_of_struct_name(struct_name)
+            ),
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos: op_pos,
+        })?
+        .members
+        .get(ident)
         .map_or_else(
             || {
                 Err(AppError {
-                    message: format!("構造体 {struct_name} にフィールド {ident} がありません",),
+                    message: format!(
+                        "構造体 {} にフィールド {ident} がありません",
+                        tag_of_struct_name(struct_name)
+                    ),
                     input: input.to_string(),
                     filename: filename.to_string(),
                     pos: op_pos,
@@ -279,7 +657,34 @@ fn search_struct_member<'a>(
     Ok(member)
 }
 
-fn arrow_expr(op_pos: usize, expr: Expr, offset: i32, typ_of_member: Type) -> Expr {
+/// `expr` が指す構造体のメンバーを表す式を作る。`qualifiers` は構造体そのものに付いている修飾子で、メンバーにも及ぶ
+fn arrow_expr(op_pos: usize, expr: Expr, member: &StructMember, qualifiers: Qualifiers) -> Expr {
+    let unit = member_unit_expr(
+        op_pos,
+        expr,
+        member.offset,
+        member.member_type.clone().qualified(qualifiers),
+    );
+    match member.bit_field {
+        // ビットフィールドの値は、int で表せるなら int に、そうでなければ unsigned int に格上げされる。
+        // `_Bool` のビットフィールドは、代入する値が正規化されるよう `_Bool` のままにしておく
+        Some(bit_field) => Expr::BitField {
+            unit: Box::new(unit),
+            bit_field,
+            pos: op_pos,
+            typ: if *member.member_type.unqualified() == Type::Bool {
+                Type::Bool
+            } else if !bit_field.is_signed && bit_field.width == 32 {
+                Type::UInt
+            } else {
+                Type::Int
+            },
+        },
+        None => unit,
+    }
+}
+
+fn member_unit_expr(op_pos: usize, expr: Expr, offset: i32, typ_of_member: Type) -> Expr {
     Expr::UnaryExpr {
         op: UnaryOp::Deref,
         op_pos,
@@ -300,13 +705,24 @@ fn arrow_expr(op_pos: usize, expr: Expr, offset: i32, typ_of_member: Type) -> Ex
 
 fn parse_suffix_op(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
 ) -> Result<Expr, AppError> {
-    let mut expr = parse_primary(strlit_collector, context, tokens, filename, input)?;
+    let expr = parse_primary(strlit_collector, context, tokens, filename, input)?;
+    parse_suffix_op_after(strlit_collector, context, tokens, filename, input, expr)
+}
 
+/// 既にパースされた `expr` に続く後置演算子をパースする
+fn parse_suffix_op_after(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    mut expr: Expr,
+) -> Result<Expr, AppError> {
     loop {
         match tokens.peek().unwrap() {
             Token {
@@ -325,12 +741,20 @@ fn parse_suffix_op(
                     typ: Type::Int,
                 };
 
-                let incremented_expr = add_assign_with_potential_scaling(
+                let incremented_expr = compound_assign_expr(
                     context,
+                    BinaryOp::AddAssign,
+                    "++",
                     op_pos,
                     Box::new(expr),
                     Box::new(one.clone()),
-                );
+                )
+                .map_err(|message| AppError {
+                    message,
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: op_pos,
+                })?;
 
                 expr = subtract_with_potential_scaling_by_sizeof(
                     context,
@@ -362,12 +786,20 @@ fn parse_suffix_op(
                     typ: Type::Int,
                 };
 
-                let decremented_expr = sub_assign_with_potential_scaling(
+                let decremented_expr = compound_assign_expr(
                     context,
+                    BinaryOp::SubAssign,
+                    "--",
                     op_pos,
                     Box::new(expr),
                     Box::new(one.clone()),
-                );
+                )
+                .map_err(|message| AppError {
+                    message,
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: op_pos,
+                })?;
 
                 expr = add_with_potential_scaling_by_sizeof(
                     context,
@@ -405,7 +837,8 @@ fn parse_suffix_op(
                             }
                         };
 
-                        let Type::Struct { struct_name } = (*typ_lhs_points_to).clone() else {
+                        let Type::Struct { struct_name } = typ_lhs_points_to.unqualified().clone()
+                        else {
                             return Err(AppError {
                                 message: "-> のオペランドが構造体へのポインタではありません"
                                     .to_string(),
@@ -426,10 +859,7 @@ fn parse_suffix_op(
                             op_pos,
                         )?;
 
-                        let typ_of_member = member.member_type.clone();
-                        let offset = member.offset;
-
-                        expr = arrow_expr(op_pos, expr, offset, typ_of_member);
+                        expr = arrow_expr(op_pos, expr, member, typ_lhs_points_to.qualifiers());
                     }
                     _ => {
                         return Err(AppError {
@@ -471,17 +901,17 @@ fn parse_suffix_op(
                             op_pos,
                         )?;
 
-                        let typ_of_member = member.member_type.clone();
-                        let offset = member.offset;
+                        let member = member.clone();
+                        let qualifiers = expr.lvalue_typ().qualifiers();
 
                         let ptr = Expr::UnaryExpr {
                             op: UnaryOp::Addr,
                             op_pos,
-                            typ: Type::Ptr(Box::new(typ_of_member.clone())),
+                            typ: Type::Ptr(Box::new(member.member_type.clone())),
                             expr: Box::new(expr),
                         };
 
-                        expr = arrow_expr(op_pos, ptr, offset, typ_of_member);
+                        expr = arrow_expr(op_pos, ptr, &member, qualifiers);
                     }
                     _ => {
                         return Err(AppError {
@@ -494,6 +924,91 @@ fn parse_suffix_op(
                 }
             }
 
+            Token {
+                tok: Tok::開き丸括弧,
+                pos: open_pos,
+            } => {
+                tokens.next();
+                let Type::Ptr(callee_type) = expr.typ() else {
+                    return Err(AppError {
+                        message: format!(
+                            "型 {:?} の値は関数ではないので、呼び出せません",
+                            expr.typ()
+                        ),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *open_pos,
+                    });
+                };
+                let Type::Func {
+                    return_type,
+                    params,
+                    ..
+                } = *callee_type
+                else {
+                    return Err(AppError {
+                        message: format!(
+                            "型 {:?} の値は関数ポインタではないので、呼び出せません",
+                            expr.typ()
+                        ),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *open_pos,
+                    });
+                };
+
+                let mut args = Vec::new();
+                if (recover(tokens, |tokens| {
+                    satisfy(
+                        tokens,
+                        filename,
+                        input,
+                        |tok| tok == &Tok::閉じ丸括弧,
+                        "閉じ丸括弧ではありません",
+                    )
+                })?)
+                .is_none()
+                {
+                    loop {
+                        let arg =
+                            parse_argument(strlit_collector, context, tokens, filename, input)?;
+                        args.push(arg);
+                        match tokens.next().unwrap() {
+                            Token {
+                                tok: Tok::閉じ丸括弧,
+                                ..
+                            } => break,
+                            Token {
+                                tok: Tok::Comma, ..
+                            } => {}
+                            Token { pos, .. } => {
+                                return Err(AppError {
+                                    message: "閉じ丸括弧かカンマが期待されていました".to_string(),
+                                    input: input.to_string(),
+                                    filename: filename.to_string(),
+                                    pos: *pos,
+                                })
+                            }
+                        }
+                    }
+                }
+
+                context
+                    .ensure_complete_type(&return_type)
+                    .map_err(|message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *open_pos,
+                    })?;
+                expr = Expr::IndirectCall {
+                    callee: Box::new(expr),
+                    pos: *open_pos,
+                    args: convert_args(args, params.as_ref(), input, filename, *open_pos)?,
+                    typ: *return_type,
+                };
+            }
+
             Token {
                 tok: Tok::開き角括弧,
                 ..
@@ -520,12 +1035,29 @@ fn parse_suffix_op(
                         })
                     }
                 };
+                context
+                    .ensure_complete_type(&typ)
+                    .map_err(|message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: op_pos,
+                    })?;
+                let 右辺 = decay_if_arr(右辺);
+                if !右辺.typ().is_integer() {
+                    return Err(AppError {
+                        message: format!("添字の型が {:?} で、整数型ではありません", 右辺.typ()),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: op_pos,
+                    });
+                }
                 expr = Expr::UnaryExpr {
                     op_pos,
                     op: UnaryOp::Deref,
                     expr: Box::new(
-                        add_with_potential_scaling_by_sizeof(context, 左辺, Box::new(右辺), op_pos)
-                            .unwrap(),
+                        add_with_potential_scaling_by_sizeof(context, 左辺, 右辺, op_pos)
+                            .expect("完全型へのポインタと整数は足し合わせられる"),
                     ),
                     typ,
                 };
@@ -537,42 +1069,255 @@ fn parse_suffix_op(
     }
 }
 
+/// 関数呼び出しの実引数を一つパースする。実引数は完全型でなければならない
+fn parse_argument(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Expr, AppError> {
+    let pos = tokens.peek().unwrap().pos;
+    let arg = *decay_if_arr(parse_assignment_expr(
+        strlit_collector,
+        context,
+        tokens,
+        filename,
+        input,
+    )?);
+    context
+        .ensure_complete_type(&arg.typ())
+        .map_err(|message| AppError {
+            message,
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos,
+        })?;
+    Ok(arg)
+}
+
+/// `(typ)` に続く `{1, 2}` をパースし、複合リテラルを作る。
+/// ブロックスコープでは名前のないローカル変数を、ファイルスコープでは名前のない静的な変数を確保する
+fn parse_compound_literal(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    typ: Type,
+    pos: usize,
+) -> Result<Expr, AppError> {
+    // 初期化子があるので、配列の最初の次元の大きさは省略できる
+    let typ_to_be_complete = match typ.unqualified() {
+        Type::IncompleteArr(elem) => elem,
+        t => t,
+    };
+    context
+        .ensure_complete_type(typ_to_be_complete)
+        .map_err(|message| AppError {
+            message,
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos,
+        })?;
+    let init = parse_initializer(strlit_collector, context, tokens, filename, input)?;
+    let name = format!("(複合リテラル)@{pos}");
+
+    if context.is_file_scope() {
+        let definition = define_static_variable(
+            strlit_collector,
+            context,
+            filename,
+            input,
+            name.clone(),
+            &typ,
+            &init,
+            pos,
+        )?;
+        let typ = definition.typ.clone();
+        context.static_local_vars.push(definition);
+        return Ok(Expr::Identifier {
+            ident: name,
+            pos,
+            typ,
+            local_var_id: None,
+        });
+    }
+
+    let (typ, initializer) =
+        初期化子を平坦化(context, strlit_collector, &typ, &init, filename, input)?;
+    let size = typ.sizeof(&context.global_declarations.struct_names);
+    let local_var_id = context.insert_local_var(
+        name.clone(),
+        TypeAndSize {
+            typ: typ.clone(),
+            size,
+        },
+    );
+    Ok(Expr::CompoundLiteral {
+        ident: name,
+        local_var_id,
+        initializer,
+        pos,
+        typ,
+    })
+}
+
 fn parse_unary(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
 ) -> Result<Expr, AppError> {
+    // `(` の後に型名が続くならキャスト
+    let mut t2 = tokens.clone();
+    t2.next();
+    let is_cast = t2
+        .peek()
+        .is_some_and(|tok| is_type_name_start(context, &tok.tok));
+
     match tokens.peek() {
-        Some(Token { tok: Tok::Add, pos }) => {
+        Some(Token {
+            tok: Tok::開き丸括弧,
+            pos,
+        }) if is_cast => {
             tokens.next();
-            let expr = parse_suffix_op(strlit_collector, context, tokens, filename, input)?;
-            Ok(Expr::BinaryExpr {
-                op: BinaryOp::Add,
-                op_pos: *pos,
-                typ: Type::Int,
-                左辺: decay_if_arr(Expr::Numeric {
+            let typ = parse_type_name(context, tokens, filename, input)?;
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::閉じ丸括弧,
+                "キャストの型名の後に閉じ丸括弧がありません",
+            )?;
+            // `(int (*)[n])p` では、型名の中の配列の大きさを計算してから変換する
+            let sizes = std::mem::take(&mut context.var_len_arr_sizes);
+            if tokens.peek().unwrap().tok == Tok::開き波括弧 {
+                if typ.is_variably_modified() {
+                    return Err(AppError {
+                        message: "可変長配列の複合リテラルは作れません".to_string(),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *pos,
+                    });
+                }
+                let literal = parse_compound_literal(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                    typ,
+                    *pos,
+                )?;
+                return parse_suffix_op_after(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                    literal,
+                );
+            }
+            // キャストの結果は左辺値ではないので、一番外側の修飾子は意味を持たない
+            let typ = typ.unqualified().clone();
+            let expr = decay_if_arr(parse_unary(
+                strlit_collector,
+                context,
+                tokens,
+                filename,
+                input,
+            )?);
+            let is_scalar = |t: &Type| t.is_arithmetic() || matches!(t, Type::Ptr(_));
+            if typ != Type::Void && !(is_scalar(&typ) && is_scalar(&expr.typ())) {
+                return Err(AppError {
+                    message: "スカラー型から、スカラー型か void へのキャストしかできません"
+                        .to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                });
+            }
+            let from = expr.typ();
+            if matches!((&typ, &from), (Type::Ptr(_), t) | (t, Type::Ptr(_)) if t.is_floating()) {
+                return Err(AppError {
+                    message: "ポインタと浮動小数点数の間のキャストはできません".to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                });
+            }
+            Ok(順に評価(
+                sizes,
+                Expr::Cast {
+                    expr,
+                    pos: *pos,
+                    typ,
+                },
+            ))
+        }
+        Some(Token { tok: Tok::Add, pos }) => {
+            tokens.next();
+            let expr = parse_suffix_op(strlit_collector, context, tokens, filename, input)?;
+            Ok(usual_arithmetic_binary_expr(
+                BinaryOp::Add,
+                *pos,
+                decay_if_arr(Expr::Numeric {
                     val: 0,
                     pos: *pos,
                     typ: Type::Int,
                 }),
-                右辺: decay_if_arr(expr),
-            })
+                decay_if_arr(expr),
+            ))
         }
         Some(Token { tok: Tok::Sub, pos }) => {
             tokens.next();
             let expr = parse_suffix_op(strlit_collector, context, tokens, filename, input)?;
-            Ok(Expr::BinaryExpr {
-                op: BinaryOp::Sub,
-                op_pos: *pos,
-                typ: Type::Int,
-                左辺: decay_if_arr(Expr::Numeric {
+            Ok(usual_arithmetic_binary_expr(
+                BinaryOp::Sub,
+                *pos,
+                decay_if_arr(Expr::Numeric {
                     val: 0,
                     pos: *pos,
                     typ: Type::Int,
                 }),
-                右辺: decay_if_arr(expr),
+                decay_if_arr(expr),
+            ))
+        }
+        Some(Token {
+            tok: Tok::Tilde,
+            pos,
+        }) => {
+            tokens.next();
+            let expr = parse_unary(strlit_collector, context, tokens, filename, input)?;
+            if !expr.typ().is_integer() {
+                return Err(AppError {
+                    message: format!(
+                        "オペランドの型が {:?} ですが、~ は整数型のオペランドにしか使えません",
+                        expr.typ()
+                    ),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                });
+            }
+            // ~E は、すべてのビットが立った -1 との排他的論理和として計算する
+            let typ = integer_promotion(&expr.typ());
+            Ok(Expr::BinaryExpr {
+                op: BinaryOp::BitXor,
+                op_pos: *pos,
+                左辺: convert_arithmetic(
+                    Box::new(Expr::Numeric {
+                        val: -1,
+                        pos: *pos,
+                        typ: Type::Int,
+                    }),
+                    &typ,
+                    *pos,
+                ),
+                右辺: convert_arithmetic(Box::new(expr), &typ, *pos),
+                typ,
             })
         }
         Some(Token {
@@ -598,13 +1343,12 @@ fn parse_unary(
                 }
             };
 
-            Ok(Expr::BinaryExpr {
-                op: BinaryOp::Equal,
-                op_pos: *pos,
-                左辺: Box::new(zero),
-                右辺: decay_if_arr(expr),
-                typ: Type::Int,
-            })
+            Ok(comparison_expr(
+                BinaryOp::Equal,
+                *pos,
+                Box::new(zero),
+                decay_if_arr(expr),
+            ))
         }
         Some(Token {
             tok: Tok::Asterisk,
@@ -612,6 +1356,12 @@ fn parse_unary(
         }) => {
             tokens.next();
             let expr = parse_unary(strlit_collector, context, tokens, filename, input)?;
+            if let Type::Ptr(t) = expr.typ() {
+                if let Type::Func { .. } = *t {
+                    // 関数ポインタを deref して得られる関数指示子は、即座に関数ポインタへと戻る
+                    return Ok(expr);
+                }
+            }
             Ok(Expr::UnaryExpr {
                 op: UnaryOp::Deref,
                 op_pos: *pos,
@@ -630,10 +1380,22 @@ fn parse_unary(
         }) => {
             tokens.next();
             let expr = parse_unary(strlit_collector, context, tokens, filename, input)?;
+            if let Expr::DecayedFunc { .. } = expr {
+                // &f は f そのものと同じく関数ポインタ
+                return Ok(expr);
+            }
+            if let Expr::BitField { .. } = expr {
+                return Err(AppError {
+                    message: "ビットフィールドのアドレスは取れません".to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                });
+            }
             Ok(Expr::UnaryExpr {
                 op: UnaryOp::Addr,
                 op_pos: *pos,
-                typ: Type::Ptr(Box::new(expr.typ())),
+                typ: Type::Ptr(Box::new(expr.lvalue_typ().clone())),
                 expr: no_decay_even_if_arr(expr),
             })
         }
@@ -648,12 +1410,20 @@ fn parse_unary(
                 pos: *pos,
                 typ: Type::Int,
             };
-            Ok(add_assign_with_potential_scaling(
+            compound_assign_expr(
                 context,
+                BinaryOp::AddAssign,
+                "++",
                 *pos,
                 Box::new(expr),
-                Box::new(one.clone()),
-            ))
+                Box::new(one),
+            )
+            .map_err(|message| AppError {
+                message,
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos: *pos,
+            })
         }
         Some(Token {
             tok: Tok::Decrement,
@@ -667,12 +1437,20 @@ fn parse_unary(
                 typ: Type::Int,
             };
 
-            Ok(sub_assign_with_potential_scaling(
+            compound_assign_expr(
                 context,
+                BinaryOp::SubAssign,
+                "--",
                 *pos,
                 Box::new(expr),
-                Box::new(one.clone()),
-            ))
+                Box::new(one),
+            )
+            .map_err(|message| AppError {
+                message,
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos: *pos,
+            })
         }
         Some(Token {
             tok: Tok::Sizeof,
@@ -680,6 +1458,16 @@ fn parse_unary(
         }) => {
             tokens.next();
 
+            // ビットフィールドの大きさはバイト単位では表せないので、sizeof のオペランドにはできない
+            let typ_of_operand = |expr: Expr| match expr {
+                Expr::BitField { .. } => Err(AppError {
+                    message: "ビットフィールドには sizeof を使えません".to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                }),
+                _ => Ok(expr.typ()),
+            };
             let typ = if (recover(tokens, |tokens| {
                 satisfy(
                     tokens,
@@ -691,12 +1479,18 @@ fn parse_unary(
             })?)
             .is_some()
             {
-                let typ = if let Some(typ) =
-                    recover(tokens, |tokens| parse_type(tokens, filename, input))?
-                {
+                let typ = if let Some(typ) = recover(tokens, |tokens| {
+                    parse_type_name(context, tokens, filename, input)
+                })? {
                     typ
                 } else {
-                    parse_expr(strlit_collector, context, tokens, filename, input)?.typ()
+                    typ_of_operand(parse_expr(
+                        strlit_collector,
+                        context,
+                        tokens,
+                        filename,
+                        input,
+                    )?)?
                 };
                 satisfy(
                     tokens,
@@ -707,14 +1501,26 @@ fn parse_unary(
                 )?;
                 typ
             } else {
-                parse_unary(strlit_collector, context, tokens, filename, input)?.typ()
+                typ_of_operand(parse_unary(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                )?)?
             };
 
-            Ok(Expr::Numeric {
-                val: typ.sizeof(&context.global_declarations.struct_names),
-                pos: *pos,
-                typ: Type::Int,
-            })
+            context
+                .ensure_complete_type(&typ)
+                .map_err(|message| AppError {
+                    message,
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                })?;
+            // `sizeof(int[n])` では、型名の中の配列の大きさを計算してから、それを読み出す
+            let sizes = std::mem::take(&mut context.var_len_arr_sizes);
+            Ok(順に評価(sizes, sizeof_expr(context, &typ, *pos)))
         }
         Some(Token {
             tok: Tok::Alignof,
@@ -733,9 +1539,9 @@ fn parse_unary(
             })?)
             .is_some()
             {
-                let typ = if let Some(typ) =
-                    recover(tokens, |tokens| parse_type(tokens, filename, input))?
-                {
+                let typ = if let Some(typ) = recover(tokens, |tokens| {
+                    parse_type_name(context, tokens, filename, input)
+                })? {
                     typ
                 } else {
                     // The use of _Alignof with expressions is allowed by some C compilers as a non-standard extension.
@@ -753,6 +1559,16 @@ fn parse_unary(
                 parse_unary(strlit_collector, context, tokens, filename, input)?.typ()
             };
 
+            context
+                .ensure_complete_type(&typ)
+                .map_err(|message| AppError {
+                    message,
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                })?;
+            // 配列の大きさは整列には影響しないので、計算する必要はない
+            context.var_len_arr_sizes.clear();
             Ok(Expr::Numeric {
                 val: typ.alignof(&context.global_declarations.struct_names),
                 pos: *pos,
@@ -765,7 +1581,7 @@ fn parse_unary(
 
 fn parse_multiplicative(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
@@ -786,13 +1602,7 @@ fn parse_multiplicative(
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::Mul,
-                    op_pos: *op_pos,
-                    左辺,
-                    右辺,
-                    typ: Type::Int,
-                };
+                expr = usual_arithmetic_binary_expr(BinaryOp::Mul, *op_pos, 左辺, 右辺);
             }
             Some(Token {
                 tok: Tok::Div,
@@ -807,13 +1617,7 @@ fn parse_multiplicative(
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::Div,
-                    op_pos: *op_pos,
-                    左辺,
-                    右辺,
-                    typ: Type::Int,
-                };
+                expr = usual_arithmetic_binary_expr(BinaryOp::Div, *op_pos, 左辺, 右辺);
             }
             Some(Token {
                 tok: Tok::Percent,
@@ -828,13 +1632,15 @@ fn parse_multiplicative(
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::Remainder,
-                    op_pos: *op_pos,
-                    左辺,
-                    右辺,
-                    typ: Type::Int,
-                };
+                if 左辺.typ().is_floating() || 右辺.typ().is_floating() {
+                    return Err(AppError {
+                        message: "浮動小数点数に対して % は使えません".to_string(),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *op_pos,
+                    });
+                }
+                expr = usual_arithmetic_binary_expr(BinaryOp::Remainder, *op_pos, 左辺, 右辺);
             }
 
             _ => {
@@ -844,6 +1650,308 @@ fn parse_multiplicative(
     }
 }
 
+/// 浮動小数点数が関わる算術型どうしの変換と、スカラー型から `_Bool` への変換には、明示的な変換の式を挟む。
+/// それ以外の整数型どうしの変換は、値を edi にそのまま置いておけばよいので何もしない。
+/// ただし unsigned int への変換では、符号なしで演算すべきことが式の型から分かるよう、変換の式を挟む
+pub fn convert_arithmetic(expr: Box<Expr>, typ: &Type, pos: usize) -> Box<Expr> {
+    let from = expr.typ();
+    let typ = typ.unqualified();
+    let needs_cast = if *typ == Type::Bool {
+        from != Type::Bool && (from.is_arithmetic() || matches!(from, Type::Ptr(_)))
+    } else {
+        from != *typ
+            && from.is_arithmetic()
+            && typ.is_arithmetic()
+            && (from.is_floating() || typ.is_floating() || *typ == Type::UInt)
+    };
+    if needs_cast {
+        Box::new(Expr::Cast {
+            expr,
+            pos,
+            typ: typ.clone(),
+        })
+    } else {
+        expr
+    }
+}
+
+/// 代入や return、引数渡しで、値を変換先の型に変換できるかを確かめる。
+/// 構造体は同じ構造体にしか変換できず、構造体でない型から構造体への変換もできない
+pub fn ensure_assignable(to: &Type, from: &Type) -> Result<(), String> {
+    match (to.unqualified(), from.unqualified()) {
+        (Type::Struct { struct_name: to }, Type::Struct { struct_name: from }) if to != from => {
+            Err(format!(
+                "構造体 {} の値は、構造体 {} には変換できません",
+                tag_of_struct_name(from),
+                tag_of_struct_name(to)
+            ))
+        }
+        (Type::Struct { struct_name }, from) if !matches!(from, Type::Struct { .. }) => {
+            Err(format!(
+                "{from:?} 型の値は、構造体 {} には変換できません",
+                tag_of_struct_name(struct_name)
+            ))
+        }
+        (to, Type::Struct { struct_name }) if !matches!(to, Type::Struct { .. }) => Err(format!(
+            "構造体 {} の値は、{to:?} 型には変換できません",
+            tag_of_struct_name(struct_name)
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// ポインタの変換で、指す先の型に付いていた修飾子が失われるなら警告する
+pub fn warn_if_qualifiers_discarded(
+    to: &Type,
+    from: &Type,
+    input: &str,
+    filename: &str,
+    pos: usize,
+) {
+    let (Type::Ptr(to), Type::Ptr(from)) = (to.unqualified(), from.unqualified()) else {
+        return;
+    };
+    let (to, from) = (to.qualifiers(), from.qualifiers());
+    let discarded = [
+        (from.is_const && !to.is_const, "const"),
+        (from.is_volatile && !to.is_volatile, "volatile"),
+    ]
+    .into_iter()
+    .filter_map(|(discarded, name)| discarded.then_some(name))
+    .collect::<Vec<_>>();
+    if !discarded.is_empty() {
+        report_warning(
+            &format!(
+                "ポインタの指す先の型から {} 修飾子が失われます",
+                discarded.join(" ")
+            ),
+            input,
+            filename,
+            pos,
+        );
+    }
+}
+
+/// 代入や `++` などで書き換えてよい左辺値かどうかを確かめる
+fn ensure_modifiable_lvalue(context: &Context, 左辺: &Expr) -> Result<(), String> {
+    if matches!(
+        左辺,
+        Expr::Conditional { .. }
+            | Expr::BinaryExpr {
+                op: BinaryOp::AndThen,
+                ..
+            }
+    ) {
+        return Err(
+            "条件演算子やコンマ演算子の結果は左辺値ではないので、書き換えられません".to_string(),
+        );
+    }
+    // ビットフィールドの修飾子は、それを含む記憶単位の型に付いている
+    let typ = match 左辺 {
+        Expr::BitField { unit, .. } => unit.lvalue_typ(),
+        _ => 左辺.lvalue_typ(),
+    };
+    // 変更可能な左辺値は完全型でなければならない
+    context.ensure_complete_type(typ)?;
+    if typ.qualifiers().is_const {
+        return Err("const で修飾された左辺値は書き換えられません".to_string());
+    }
+    if has_const_member(context, typ) {
+        return Err("const なメンバーを持つ構造体は書き換えられません".to_string());
+    }
+    Ok(())
+}
+
+fn has_const_member(context: &Context, typ: &Type) -> bool {
+    match typ.unqualified() {
+        Type::Struct { struct_name } => context
+            .global_declarations
+            .struct_names
+            .get(struct_name)
+            .is_some_and(|def| {
+                def.members.values().any(|member| {
+                    member.member_type.qualifiers().is_const
+                        || has_const_member(context, &member.member_type)
+                })
+            }),
+        Type::Arr(t, _) => t.qualifiers().is_const || has_const_member(context, t),
+        _ => false,
+    }
+}
+
+/// 整数拡張の結果の型。int より小さい整数型は int になる
+fn integer_promotion(typ: &Type) -> Type {
+    if *typ.unqualified() == Type::UInt {
+        Type::UInt
+    } else {
+        Type::Int
+    }
+}
+
+/// 通常の算術型変換で、二項演算の両辺が揃えられる先の型
+fn common_arithmetic_type(左辺: &Type, 右辺: &Type) -> Type {
+    if *左辺 == Type::Double || *右辺 == Type::Double {
+        Type::Double
+    } else if *左辺 == Type::Float || *右辺 == Type::Float {
+        Type::Float
+    } else if integer_promotion(左辺) == Type::UInt || integer_promotion(右辺) == Type::UInt {
+        Type::UInt
+    } else {
+        Type::Int
+    }
+}
+
+/// 通常の算術型変換を施した二項演算の式を作る
+fn usual_arithmetic_binary_expr(
+    op: BinaryOp,
+    op_pos: usize,
+    左辺: Box<Expr>,
+    右辺: Box<Expr>,
+) -> Expr {
+    let typ = common_arithmetic_type(&左辺.typ(), &右辺.typ());
+    Expr::BinaryExpr {
+        op,
+        op_pos,
+        左辺: convert_arithmetic(左辺, &typ, op_pos),
+        右辺: convert_arithmetic(右辺, &typ, op_pos),
+        typ,
+    }
+}
+
+/// 整数型のオペランドだけを取る、ビット演算やシフト演算の式を作る。
+/// ビット演算では両辺に通常の算術型変換を施し、シフト演算では整数拡張した左辺の型が式の型になる
+fn integer_binary_expr(
+    op: BinaryOp,
+    symbol: &str,
+    op_pos: usize,
+    左辺: Box<Expr>,
+    右辺: Box<Expr>,
+) -> Result<Expr, String> {
+    if !左辺.typ().is_integer() || !右辺.typ().is_integer() {
+        return Err(format!(
+            "左辺の型が {:?}、右辺の型が {:?} ですが、{symbol} は整数型のオペランドにしか使えません",
+            左辺.typ(),
+            右辺.typ()
+        ));
+    }
+    if matches!(op, BinaryOp::LeftShift | BinaryOp::RightShift) {
+        let typ = integer_promotion(&左辺.typ());
+        Ok(Expr::BinaryExpr {
+            op,
+            op_pos,
+            左辺: convert_arithmetic(左辺, &typ, op_pos),
+            右辺,
+            typ,
+        })
+    } else {
+        Ok(usual_arithmetic_binary_expr(op, op_pos, 左辺, 右辺))
+    }
+}
+
+/// 比較演算の式を作る。両辺が算術型なら、通常の算術型変換で揃えてから比較する
+fn comparison_expr(op: BinaryOp, op_pos: usize, 左辺: Box<Expr>, 右辺: Box<Expr>) -> Expr {
+    let (左辺, 右辺) = if 左辺.typ().is_arithmetic() && 右辺.typ().is_arithmetic() {
+        let typ = common_arithmetic_type(&左辺.typ(), &右辺.typ());
+        (
+            convert_arithmetic(左辺, &typ, op_pos),
+            convert_arithmetic(右辺, &typ, op_pos),
+        )
+    } else {
+        (左辺, 右辺)
+    };
+    Expr::BinaryExpr {
+        op,
+        op_pos,
+        左辺,
+        右辺,
+        typ: Type::Int,
+    }
+}
+
+/// 引数を仮引数の型に変換する。仮引数の型が分からない引数には、既定の実引数拡張を施す
+fn convert_args(
+    args: Vec<Expr>,
+    params: Option<&Vec<Type>>,
+    input: &str,
+    filename: &str,
+    pos: usize,
+) -> Result<Vec<Expr>, AppError> {
+    args.into_iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let typ = match params.and_then(|params| params.get(i)) {
+                Some(param_type) => {
+                    ensure_assignable(param_type, &arg.typ()).map_err(|message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos,
+                    })?;
+                    warn_if_qualifiers_discarded(param_type, &arg.typ(), input, filename, pos);
+                    param_type.clone()
+                }
+                None if arg.typ() == Type::Float => Type::Double,
+                None => arg.typ(),
+            };
+            Ok(*convert_arithmetic(Box::new(arg), &typ, pos))
+        })
+        .collect()
+}
+
+/// `typ` 型の大きさを表す int 型の式。可変長配列型なら、実行時に計算された大きさを読み出す
+pub fn sizeof_expr(context: &Context, typ: &Type, pos: usize) -> Expr {
+    match typ.unqualified() {
+        Type::VarLenArr(_, size_var) => 隠れたローカル変数(*size_var, Type::Int, pos),
+        t => Expr::Numeric {
+            val: t.sizeof(&context.global_declarations.struct_names),
+            pos,
+            typ: Type::Int,
+        },
+    }
+}
+
+/// `Context::insert_hidden_local_var` で確保した変数を指す式
+pub fn 隠れたローカル変数(id: u64, typ: Type, pos: usize) -> Expr {
+    Expr::Identifier {
+        ident: 隠れたローカル変数の名前.to_string(),
+        pos,
+        typ,
+        local_var_id: Some(id),
+    }
+}
+
+/// `exprs` を順に評価して値を捨ててから、`last` を評価する式
+pub fn 順に評価(exprs: Vec<Expr>, last: Expr) -> Expr {
+    exprs
+        .into_iter()
+        .rev()
+        .fold(last, |acc, expr| Expr::BinaryExpr {
+            op: BinaryOp::AndThen,
+            op_pos: 0,
+            typ: acc.typ(),
+            左辺: Box::new(expr),
+            右辺: Box::new(acc),
+        })
+}
+
+/// ポインタに足し引きする整数 `offset` を、指す先の型 `pointee` の大きさ倍したバイト数にする。
+/// `offset` は整数拡張した型のまま掛け、ポインタの幅への拡張はコード生成で行う
+fn scale_by_sizeof(
+    context: &Context,
+    pointee: &Type,
+    offset: Box<Expr>,
+    op_pos: usize,
+) -> Box<Expr> {
+    let typ = integer_promotion(&offset.typ());
+    Box::new(Expr::BinaryExpr {
+        op: BinaryOp::Mul,
+        op_pos,
+        左辺: Box::new(sizeof_expr(context, pointee, op_pos)),
+        右辺: convert_arithmetic(offset, &typ, op_pos),
+        typ,
+    })
+}
+
 fn add_with_potential_scaling_by_sizeof(
     context: &Context,
     左辺: Box<Expr>,
@@ -851,31 +1959,24 @@ fn add_with_potential_scaling_by_sizeof(
     op_pos: usize,
 ) -> Option<Expr> {
     match (左辺.typ(), 右辺.typ()) {
-        (Type::Int | Type::Char, Type::Int | Type::Char) => Some(Expr::BinaryExpr {
-            op: BinaryOp::Add,
+        (l, r) if l.is_arithmetic() && r.is_arithmetic() => Some(usual_arithmetic_binary_expr(
+            BinaryOp::Add,
             op_pos,
-            typ: Type::Int,
             左辺,
             右辺,
-        }),
-        (Type::Ptr(t), Type::Int) => Some(Expr::BinaryExpr {
-            op: BinaryOp::Add,
-            op_pos,
-            左辺,
-            右辺: decay_if_arr(Expr::BinaryExpr {
-                op: BinaryOp::Mul,
+        )),
+        (Type::Ptr(t), r) if r.is_integer() && context.ensure_complete_type(&t).is_ok() => {
+            Some(Expr::BinaryExpr {
+                op: BinaryOp::Add,
                 op_pos,
-                左辺: decay_if_arr(Expr::Numeric {
-                    val: t.sizeof(&context.global_declarations.struct_names),
-                    pos: op_pos,
-                    typ: Type::Int,
-                }),
-                右辺,
-                typ: Type::Int,
-            }),
-            typ: Type::Ptr(t),
-        }),
-        (Type::Int, _) => add_with_potential_scaling_by_sizeof(context, 右辺, 左辺, op_pos),
+                左辺,
+                右辺: scale_by_sizeof(context, &t, 右辺, op_pos),
+                typ: Type::Ptr(t),
+            })
+        }
+        (l, Type::Ptr(_)) if l.is_integer() => {
+            add_with_potential_scaling_by_sizeof(context, 右辺, 左辺, op_pos)
+        }
         _ => None,
     }
 }
@@ -887,54 +1988,46 @@ fn subtract_with_potential_scaling_by_sizeof(
     op_pos: usize,
 ) -> Option<Expr> {
     match (左辺.typ(), 右辺.typ()) {
-        (Type::Int | Type::Char, Type::Int | Type::Char) => Some(Expr::BinaryExpr {
-            op: BinaryOp::Sub,
+        (l, r) if l.is_arithmetic() && r.is_arithmetic() => Some(usual_arithmetic_binary_expr(
+            BinaryOp::Sub,
             op_pos,
-            typ: Type::Int,
             左辺,
             右辺,
-        }),
-        (Type::Ptr(t), Type::Int) => Some(Expr::BinaryExpr {
-            op: BinaryOp::Sub,
-            op_pos,
-            左辺,
-            右辺: decay_if_arr(Expr::BinaryExpr {
-                op: BinaryOp::Mul,
-                op_pos,
-                左辺: decay_if_arr(Expr::Numeric {
-                    val: t.sizeof(&context.global_declarations.struct_names),
-                    pos: op_pos,
-                    typ: Type::Int,
-                }),
-                右辺,
-                typ: Type::Int,
-            }),
-            typ: Type::Ptr(t),
-        }),
-        (Type::Ptr(t1), Type::Ptr(t2)) if t1 == t2 => Some(Expr::BinaryExpr {
-            op: BinaryOp::Div,
-            op_pos,
-            左辺: decay_if_arr(Expr::BinaryExpr {
-                op: BinaryOp::Sub,
+        )),
+        (Type::Ptr(t), r) if r.is_integer() && context.ensure_complete_type(&t).is_ok() => {
+            Some(Expr::BinaryExpr {
+                op: BinaryOp::Sub,
                 op_pos,
                 左辺,
-                右辺,
-                typ: Type::Int,
-            }),
-            右辺: decay_if_arr(Expr::Numeric {
-                val: t1.sizeof(&context.global_declarations.struct_names),
-                pos: op_pos,
+                右辺: scale_by_sizeof(context, &t, 右辺, op_pos),
+                typ: Type::Ptr(t),
+            })
+        }
+        (Type::Ptr(t1), Type::Ptr(t2))
+            if t1.unqualified() == t2.unqualified()
+                && context.ensure_complete_type(&t1).is_ok() =>
+        {
+            Some(Expr::BinaryExpr {
+                op: BinaryOp::Div,
+                op_pos,
+                左辺: decay_if_arr(Expr::BinaryExpr {
+                    op: BinaryOp::Sub,
+                    op_pos,
+                    左辺,
+                    右辺,
+                    typ: Type::Int,
+                }),
+                右辺: Box::new(sizeof_expr(context, &t1, op_pos)),
                 typ: Type::Int,
-            }),
-            typ: Type::Int,
-        }),
+            })
+        }
         _ => None,
     }
 }
 
 fn parse_additive(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
@@ -1004,9 +2097,9 @@ fn parse_additive(
     }
 }
 
-fn parse_relational(
+fn parse_shift(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
@@ -1016,7 +2109,7 @@ fn parse_relational(
         let tok = tokens.peek().unwrap();
         match tok {
             Token {
-                tok: Tok::LessThan,
+                tok: Tok::LeftShift,
                 pos: op_pos,
             } => {
                 tokens.next();
@@ -1028,16 +2121,16 @@ fn parse_relational(
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::LessThan,
-                    op_pos: *op_pos,
-                    左辺,
-                    右辺,
-                    typ: Type::Int,
-                }
+                expr = integer_binary_expr(BinaryOp::LeftShift, "<<", *op_pos, 左辺, 右辺)
+                    .map_err(|message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *op_pos,
+                    })?;
             }
             Token {
-                tok: Tok::LessThanOrEqual,
+                tok: Tok::RightShift,
                 pos: op_pos,
             } => {
                 tokens.next();
@@ -1049,13 +2142,61 @@ fn parse_relational(
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::LessThanOrEqual,
-                    op_pos: *op_pos,
-                    左辺,
-                    右辺,
-                    typ: Type::Int,
-                }
+                expr = integer_binary_expr(BinaryOp::RightShift, ">>", *op_pos, 左辺, 右辺)
+                    .map_err(|message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *op_pos,
+                    })?;
+            }
+            _ => {
+                return Ok(expr);
+            }
+        }
+    }
+}
+
+fn parse_relational(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Expr, AppError> {
+    let mut expr = parse_shift(strlit_collector, context, tokens, filename, input)?;
+    loop {
+        let tok = tokens.peek().unwrap();
+        match tok {
+            Token {
+                tok: Tok::LessThan,
+                pos: op_pos,
+            } => {
+                tokens.next();
+                let 左辺 = decay_if_arr(expr);
+                let 右辺 = decay_if_arr(parse_shift(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                )?);
+                expr = comparison_expr(BinaryOp::LessThan, *op_pos, 左辺, 右辺);
+            }
+            Token {
+                tok: Tok::LessThanOrEqual,
+                pos: op_pos,
+            } => {
+                tokens.next();
+                let 左辺 = decay_if_arr(expr);
+                let 右辺 = decay_if_arr(parse_shift(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                )?);
+                expr = comparison_expr(BinaryOp::LessThanOrEqual, *op_pos, 左辺, 右辺);
             }
             Token {
                 tok: Tok::GreaterThan,
@@ -1063,20 +2204,15 @@ fn parse_relational(
             } => {
                 tokens.next();
                 let 左辺 = decay_if_arr(expr);
-                let 右辺 = decay_if_arr(parse_additive(
+                let 右辺 = decay_if_arr(parse_shift(
                     strlit_collector,
                     context,
                     tokens,
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::LessThan, // ここを逆転させ、
-                    op_pos: *op_pos,
-                    左辺: 右辺, // ここを逆転させればよい
-                    右辺: 左辺,
-                    typ: Type::Int,
-                }
+                // 演算子を逆転させ、両辺を入れ替えればよい
+                expr = comparison_expr(BinaryOp::LessThan, *op_pos, 右辺, 左辺);
             }
             Token {
                 tok: Tok::GreaterThanOrEqual,
@@ -1084,20 +2220,15 @@ fn parse_relational(
             } => {
                 tokens.next();
                 let 左辺 = decay_if_arr(expr);
-                let 右辺 = decay_if_arr(parse_additive(
+                let 右辺 = decay_if_arr(parse_shift(
                     strlit_collector,
                     context,
                     tokens,
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::LessThanOrEqual, // ここを逆転させ、
-                    op_pos: *op_pos,
-                    左辺: 右辺, // ここを逆転させればよい
-                    右辺: 左辺,
-                    typ: Type::Int,
-                }
+                // 演算子を逆転させ、両辺を入れ替えればよい
+                expr = comparison_expr(BinaryOp::LessThanOrEqual, *op_pos, 右辺, 左辺);
             }
             _ => {
                 return Ok(expr);
@@ -1108,7 +2239,7 @@ fn parse_relational(
 
 fn parse_equality(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
@@ -1130,13 +2261,7 @@ fn parse_equality(
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::Equal,
-                    op_pos: *op_pos,
-                    左辺,
-                    右辺,
-                    typ: Type::Int,
-                }
+                expr = comparison_expr(BinaryOp::Equal, *op_pos, 左辺, 右辺);
             }
             Token {
                 tok: Tok::NotEqual,
@@ -1151,13 +2276,7 @@ fn parse_equality(
                     filename,
                     input,
                 )?);
-                expr = Expr::BinaryExpr {
-                    op: BinaryOp::NotEqual,
-                    op_pos: *op_pos,
-                    左辺,
-                    右辺,
-                    typ: Type::Int,
-                }
+                expr = comparison_expr(BinaryOp::NotEqual, *op_pos, 左辺, 右辺);
             }
             _ => {
                 return Ok(expr);
@@ -1166,9 +2285,9 @@ fn parse_equality(
     }
 }
 
-fn parse_logical_and(
+fn parse_bit_and(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
@@ -1178,7 +2297,7 @@ fn parse_logical_and(
         let tok = tokens.peek().unwrap();
         match tok {
             Token {
-                tok: Tok::LogicalAnd,
+                tok: Tok::Ampersand,
                 pos: op_pos,
             } => {
                 tokens.next();
@@ -1190,6 +2309,126 @@ fn parse_logical_and(
                     filename,
                     input,
                 )?);
+                expr = integer_binary_expr(BinaryOp::BitAnd, "&", *op_pos, 左辺, 右辺).map_err(
+                    |message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *op_pos,
+                    },
+                )?;
+            }
+            _ => {
+                return Ok(expr);
+            }
+        }
+    }
+}
+
+fn parse_bit_xor(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Expr, AppError> {
+    let mut expr = parse_bit_and(strlit_collector, context, tokens, filename, input)?;
+    loop {
+        let tok = tokens.peek().unwrap();
+        match tok {
+            Token {
+                tok: Tok::Caret,
+                pos: op_pos,
+            } => {
+                tokens.next();
+                let 左辺 = decay_if_arr(expr);
+                let 右辺 = decay_if_arr(parse_bit_and(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                )?);
+                expr = integer_binary_expr(BinaryOp::BitXor, "^", *op_pos, 左辺, 右辺).map_err(
+                    |message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *op_pos,
+                    },
+                )?;
+            }
+            _ => {
+                return Ok(expr);
+            }
+        }
+    }
+}
+
+fn parse_bit_or(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Expr, AppError> {
+    let mut expr = parse_bit_xor(strlit_collector, context, tokens, filename, input)?;
+    loop {
+        let tok = tokens.peek().unwrap();
+        match tok {
+            Token {
+                tok: Tok::VerticalBar,
+                pos: op_pos,
+            } => {
+                tokens.next();
+                let 左辺 = decay_if_arr(expr);
+                let 右辺 = decay_if_arr(parse_bit_xor(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                )?);
+                expr = integer_binary_expr(BinaryOp::BitOr, "|", *op_pos, 左辺, 右辺).map_err(
+                    |message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *op_pos,
+                    },
+                )?;
+            }
+            _ => {
+                return Ok(expr);
+            }
+        }
+    }
+}
+
+fn parse_logical_and(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Expr, AppError> {
+    let mut expr = parse_bit_or(strlit_collector, context, tokens, filename, input)?;
+    loop {
+        let tok = tokens.peek().unwrap();
+        match tok {
+            Token {
+                tok: Tok::LogicalAnd,
+                pos: op_pos,
+            } => {
+                tokens.next();
+                let 左辺 = decay_if_arr(expr);
+                let 右辺 = decay_if_arr(parse_bit_or(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                )?);
                 expr = Expr::BinaryExpr {
                     op: BinaryOp::LogicalAnd,
                     op_pos: *op_pos,
@@ -1207,7 +2446,7 @@ fn parse_logical_and(
 
 fn parse_logical_or(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
@@ -1244,14 +2483,170 @@ fn parse_logical_or(
     }
 }
 
+/// コンマ演算子で区切られた式。左のオペランドから順に評価し、最後のオペランドの値を式の値とする
 pub fn parse_expr(
     strlit_collector: &mut StrLitCollector,
-    context: &Context,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Expr, AppError> {
+    let mut expr = parse_assignment_expr(strlit_collector, context, tokens, filename, input)?;
+    loop {
+        let tok = tokens.peek().unwrap();
+        match tok {
+            Token {
+                tok: Tok::Comma,
+                pos: op_pos,
+            } => {
+                tokens.next();
+                let 左辺 = decay_if_arr(expr);
+                let 右辺 = decay_if_arr(parse_assignment_expr(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                )?);
+                expr = Expr::BinaryExpr {
+                    op: BinaryOp::AndThen,
+                    op_pos: *op_pos,
+                    typ: 右辺.typ(),
+                    左辺,
+                    右辺,
+                };
+            }
+            _ => {
+                return Ok(expr);
+            }
+        }
+    }
+}
+
+fn parse_conditional(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
 ) -> Result<Expr, AppError> {
-    let expr = parse_logical_or(strlit_collector, context, tokens, filename, input)?;
+    let cond = parse_logical_or(strlit_collector, context, tokens, filename, input)?;
+    let Some(Token {
+        tok: Tok::Question,
+        pos,
+    }) = tokens.peek()
+    else {
+        return Ok(cond);
+    };
+    tokens.next();
+    let then = decay_if_arr(parse_expr(
+        strlit_collector,
+        context,
+        tokens,
+        filename,
+        input,
+    )?);
+    satisfy(
+        tokens,
+        filename,
+        input,
+        |tok| tok == &Tok::Colon,
+        "条件演算子の ? に対応する : がありません",
+    )?;
+    let else_ = decay_if_arr(parse_conditional(
+        strlit_collector,
+        context,
+        tokens,
+        filename,
+        input,
+    )?);
+    conditional_expr(decay_if_arr(cond), then, else_, *pos).map_err(|message| AppError {
+        message,
+        input: input.to_string(),
+        filename: filename.to_string(),
+        pos: *pos,
+    })
+}
+
+/// 条件演算子の式を作る。第二・第三オペランドの型から、C の規則に従って結果の型を決め、両者をその型に揃える
+fn conditional_expr(
+    cond: Box<Expr>,
+    then: Box<Expr>,
+    else_: Box<Expr>,
+    pos: usize,
+) -> Result<Expr, String> {
+    if !cond.typ().is_arithmetic() && !matches!(cond.typ(), Type::Ptr(_)) {
+        return Err(format!(
+            "条件演算子の第一オペランドの型が {:?} ですが、スカラー型でなければなりません",
+            cond.typ()
+        ));
+    }
+    let null_ptr = |expr: &Expr, typ: Type| {
+        Box::new(Expr::NullPtr {
+            pos: match expr {
+                Expr::Numeric { pos, .. } => *pos,
+                _ => pos,
+            },
+            typ,
+        })
+    };
+    let (then, else_, typ) = match (then.typ(), else_.typ()) {
+        (l, r) if l.is_arithmetic() && r.is_arithmetic() => {
+            let typ = common_arithmetic_type(&l, &r);
+            (
+                convert_arithmetic(then, &typ, pos),
+                convert_arithmetic(else_, &typ, pos),
+                typ,
+            )
+        }
+        (l @ Type::Struct { .. }, r) | (l @ Type::Void, r) if l == r => (then, else_, l),
+        (l @ Type::Ptr(_), r) if r.is_integer() && 定数式を評価(&else_) == Some(0) => {
+            (then, null_ptr(&else_, l.clone()), l)
+        }
+        (l, r @ Type::Ptr(_)) if l.is_integer() && 定数式を評価(&then) == Some(0) => {
+            (null_ptr(&then, r.clone()), else_, r)
+        }
+        (Type::Ptr(l), Type::Ptr(r))
+            if l.unqualified() == r.unqualified()
+                || *l.unqualified() == Type::Void
+                || *r.unqualified() == Type::Void =>
+        {
+            // 指す先の型の修飾子は両方のものを合わせ、片方が void へのポインタなら結果も void へのポインタになる
+            let qualifiers = l.qualifiers().union(r.qualifiers());
+            let pointee = if *r.unqualified() == Type::Void {
+                Type::Void
+            } else {
+                l.unqualified().clone()
+            };
+            (
+                then,
+                else_,
+                Type::Ptr(Box::new(pointee.qualified(qualifiers))),
+            )
+        }
+        (l, r) => {
+            return Err(format!(
+                "第二オペランドの型が {l:?}、第三オペランドの型が {r:?} なので、条件演算子の結果の型が決まりません"
+            ));
+        }
+    };
+    Ok(Expr::Conditional {
+        cond,
+        then,
+        else_,
+        pos,
+        typ,
+    })
+}
+
+pub fn parse_assignment_expr(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Expr, AppError> {
+    let expr = parse_conditional(strlit_collector, context, tokens, filename, input)?;
     let tok = tokens.peek().unwrap();
     match tok {
         Token {
@@ -1260,130 +2655,252 @@ pub fn parse_expr(
         } => {
             tokens.next();
             let 左辺 = decay_if_arr(expr);
-            let 右辺 = decay_if_arr(parse_expr(
+            let 右辺 = decay_if_arr(parse_assignment_expr(
                 strlit_collector,
                 context,
                 tokens,
                 filename,
                 input,
             )?);
+            ensure_modifiable_lvalue(context, &左辺).map_err(|message| AppError {
+                message,
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos: *op_pos,
+            })?;
+            ensure_assignable(&左辺.typ(), &右辺.typ()).map_err(|message| AppError {
+                message,
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos: *op_pos,
+            })?;
+            warn_if_qualifiers_discarded(&左辺.typ(), &右辺.typ(), input, filename, *op_pos);
 
             // special case for assigning 0 to a pointer
-            if let Type::Ptr(_) = 左辺.typ() {
-                if let Expr::Numeric { val, pos, .. } = &*右辺 {
-                    if *val == 0 {
-                        return Ok(Expr::NullPtr {
-                            pos: *pos,
-                            typ: 左辺.typ(),
-                        });
-                    }
-                }
-            }
+            let 右辺 = if let (Type::Ptr(_), Expr::Numeric { val: 0, pos, .. }) =
+                (左辺.typ(), &*右辺)
+            {
+                Box::new(Expr::NullPtr {
+                    pos: *pos,
+                    typ: 左辺.typ(),
+                })
+            } else {
+                右辺
+            };
 
             Ok(Expr::BinaryExpr {
                 op: BinaryOp::Assign,
                 op_pos: *op_pos,
                 typ: 左辺.typ(),
+                右辺: convert_arithmetic(右辺, &左辺.typ(), *op_pos),
                 左辺,
-                右辺,
             })
         }
         Token {
-            tok: Tok::AddAssign,
-            pos: op_pos,
-        } => {
-            tokens.next();
-            let 左辺 = decay_if_arr(expr);
-            let 右辺 = decay_if_arr(parse_expr(
-                strlit_collector,
-                context,
-                tokens,
-                filename,
-                input,
-            )?);
-
-            Ok(add_assign_with_potential_scaling(
-                context, *op_pos, 左辺, 右辺,
-            ))
-        }
-        Token {
-            tok: Tok::SubAssign,
+            tok:
+                tok @ (Tok::AddAssign
+                | Tok::SubAssign
+                | Tok::MulAssign
+                | Tok::DivAssign
+                | Tok::RemainderAssign
+                | Tok::AndAssign
+                | Tok::OrAssign
+                | Tok::XorAssign
+                | Tok::LeftShiftAssign
+                | Tok::RightShiftAssign),
             pos: op_pos,
         } => {
             tokens.next();
             let 左辺 = decay_if_arr(expr);
-            let 右辺 = decay_if_arr(parse_expr(
+            let 右辺 = decay_if_arr(parse_assignment_expr(
                 strlit_collector,
                 context,
                 tokens,
                 filename,
                 input,
             )?);
+            let (op, symbol) = match tok {
+                Tok::AddAssign => (BinaryOp::AddAssign, "+="),
+                Tok::SubAssign => (BinaryOp::SubAssign, "-="),
+                Tok::MulAssign => (BinaryOp::MulAssign, "*="),
+                Tok::DivAssign => (BinaryOp::DivAssign, "/="),
+                Tok::RemainderAssign => (BinaryOp::RemainderAssign, "%="),
+                Tok::AndAssign => (BinaryOp::BitAndAssign, "&="),
+                Tok::OrAssign => (BinaryOp::BitOrAssign, "|="),
+                Tok::XorAssign => (BinaryOp::BitXorAssign, "^="),
+                Tok::LeftShiftAssign => (BinaryOp::LeftShiftAssign, "<<="),
+                _ => (BinaryOp::RightShiftAssign, ">>="),
+            };
 
-            Ok(sub_assign_with_potential_scaling(
-                context, *op_pos, 左辺, 右辺,
-            ))
+            compound_assign_expr(context, op, symbol, *op_pos, 左辺, 右辺).map_err(|message| {
+                AppError {
+                    message,
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *op_pos,
+                }
+            })
         }
         _ => Ok(expr),
     }
 }
 
-fn add_assign_with_potential_scaling(
+/// `a op= b` の式を作る。左辺値 `a` は一度だけ評価される。
+/// 演算は `a op b` と同じく通常の算術型変換後の型で行うので、右辺はその型に変換しておく。
+/// ポインタへの `+=` と `-=` では、右辺を指す先の型の大きさ倍したバイト数にしておく
+fn compound_assign_expr(
     context: &Context,
+    op: BinaryOp,
+    symbol: &str,
     op_pos: usize,
     左辺: Box<Expr>,
     右辺: Box<Expr>,
-) -> Expr {
-    let scaled_右辺 = match 左辺.typ() {
-        Type::Ptr(t) => Box::new(Expr::BinaryExpr {
-            op: BinaryOp::Mul,
-            op_pos,
-            左辺: decay_if_arr(Expr::Numeric {
-                val: t.sizeof(&context.global_declarations.struct_names),
-                pos: op_pos,
-                typ: Type::Int,
-            }),
-            右辺,
-            typ: Type::Int,
-        }),
-        _ => 右辺,
+) -> Result<Expr, String> {
+    ensure_modifiable_lvalue(context, &左辺)?;
+    let (l, r) = (左辺.typ(), 右辺.typ());
+    let 右辺 = match (op, &l) {
+        (BinaryOp::AddAssign | BinaryOp::SubAssign, Type::Ptr(t)) if r.is_integer() => {
+            context.ensure_complete_type(t)?;
+            scale_by_sizeof(context, t, 右辺, op_pos)
+        }
+        (
+            BinaryOp::AddAssign | BinaryOp::SubAssign | BinaryOp::MulAssign | BinaryOp::DivAssign,
+            _,
+        ) if l.is_arithmetic() && r.is_arithmetic() => {
+            convert_arithmetic(右辺, &common_arithmetic_type(&l, &r), op_pos)
+        }
+        (
+            BinaryOp::AddAssign | BinaryOp::SubAssign | BinaryOp::MulAssign | BinaryOp::DivAssign,
+            _,
+        ) => {
+            return Err(format!(
+                "左辺の型が {l:?}、右辺の型が {r:?} なので、{symbol} を計算できません"
+            ));
+        }
+        // 残りはビット演算と % なので、`_Bool` や `char` も int に拡張された上で計算される。
+        // 演算の型は右辺の型として表し、シフト演算では左辺だけで決まる
+        _ if l.is_integer() && r.is_integer() => {
+            let typ = if matches!(op, BinaryOp::LeftShiftAssign | BinaryOp::RightShiftAssign) {
+                integer_promotion(&l)
+            } else {
+                common_arithmetic_type(&l, &r)
+            };
+            convert_arithmetic(右辺, &typ, op_pos)
+        }
+        _ => {
+            return Err(format!(
+                "左辺の型が {l:?}、右辺の型が {r:?} ですが、{symbol} は整数型のオペランドにしか使えません"
+            ));
+        }
     };
 
-    Expr::BinaryExpr {
-        op: BinaryOp::AddAssign,
+    Ok(Expr::BinaryExpr {
+        op,
         op_pos,
-        typ: 左辺.typ(),
+        typ: l,
         左辺,
-        右辺: scaled_右辺,
-    }
+        右辺,
+    })
 }
 
-fn sub_assign_with_potential_scaling(
-    context: &Context,
-    op_pos: usize,
-    左辺: Box<Expr>,
-    右辺: Box<Expr>,
-) -> Expr {
-    let scaled_右辺 = match 左辺.typ() {
-        Type::Ptr(t) => Box::new(Expr::BinaryExpr {
-            op: BinaryOp::Mul,
-            op_pos,
-            左辺: decay_if_arr(Expr::Numeric {
-                val: t.sizeof(&context.global_declarations.struct_names),
-                pos: op_pos,
-                typ: Type::Int,
-            }),
-            右辺,
-            typ: Type::Int,
-        }),
-        _ => 右辺,
-    };
+/// 整数定数式をパースして、その値を返す
+pub fn parse_constant_expr(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<i32, AppError> {
+    let pos = tokens.peek().unwrap().pos;
+    let expr = parse_conditional(
+        &mut StrLitCollector::new(), // 定数式の中の文字列リテラルは `sizeof "abc"` のように評価されないので、収集しない
+        context,
+        tokens,
+        filename,
+        input,
+    )?;
+    定数式を評価(&expr).ok_or(AppError {
+        message: "整数定数式ではありません".to_string(),
+        input: input.to_string(),
+        filename: filename.to_string(),
+        pos,
+    })
+}
 
-    Expr::BinaryExpr {
-        op: BinaryOp::SubAssign,
-        op_pos,
-        typ: 左辺.typ(),
-        左辺,
-        右辺: scaled_右辺,
+pub fn 定数式を評価(expr: &Expr) -> Option<i32> {
+    match expr {
+        Expr::Numeric { val, .. } => Some(*val),
+        Expr::Cast { expr, typ, .. } => match typ {
+            Type::Int | Type::UInt => 定数式を評価(expr),
+            Type::Char => 定数式を評価(expr).map(|v| i32::from(v as i8)),
+            Type::UChar => 定数式を評価(expr).map(|v| i32::from(v as u8)),
+            _ => None,
+        },
+        Expr::Conditional {
+            cond, then, else_, ..
+        } => {
+            if 定数式を評価(cond)? != 0 {
+                定数式を評価(then)
+            } else {
+                定数式を評価(else_)
+            }
+        }
+        Expr::BinaryExpr {
+            op, 左辺, 右辺, ..
+        } => {
+            let l = 定数式を評価(左辺)?;
+            // && と || の右辺は、結果が左辺だけで決まる場合には評価されない
+            match op {
+                BinaryOp::LogicalAnd if l == 0 => return Some(0),
+                BinaryOp::LogicalOr if l != 0 => return Some(1),
+                _ => {}
+            }
+            let r = 定数式を評価(右辺)?;
+            // unsigned int の値も、同じビット列の i32 として持っておく
+            let 符号なし = integer_promotion(&左辺.typ()) == Type::UInt
+                || integer_promotion(&右辺.typ()) == Type::UInt;
+            let (ul, ur) = (l as u32, r as u32);
+            match op {
+                BinaryOp::Div if 符号なし => ul.checked_div(ur).map(|v| v as i32),
+                BinaryOp::Remainder if 符号なし => ul.checked_rem(ur).map(|v| v as i32),
+                BinaryOp::LessThan if 符号なし => Some(i32::from(ul < ur)),
+                BinaryOp::LessThanOrEqual if 符号なし => Some(i32::from(ul <= ur)),
+                // シフトの結果の型は左オペランドだけで決まる
+                BinaryOp::RightShift if integer_promotion(&左辺.typ()) == Type::UInt => {
+                    u32::try_from(r)
+                        .ok()
+                        .and_then(|r| ul.checked_shr(r))
+                        .map(|v| v as i32)
+                }
+                BinaryOp::Add => Some(l.wrapping_add(r)),
+                BinaryOp::Sub => Some(l.wrapping_sub(r)),
+                BinaryOp::Mul => Some(l.wrapping_mul(r)),
+                BinaryOp::Div => l.checked_div(r),
+                BinaryOp::Remainder => l.checked_rem(r),
+                BinaryOp::BitAnd => Some(l & r),
+                BinaryOp::BitOr => Some(l | r),
+                BinaryOp::BitXor => Some(l ^ r),
+                // 右オペランドが負か、幅以上のシフトは未定義動作なので、定数式とはみなさない
+                BinaryOp::LeftShift => u32::try_from(r).ok().and_then(|r| l.checked_shl(r)),
+                BinaryOp::RightShift => u32::try_from(r).ok().and_then(|r| l.checked_shr(r)),
+                BinaryOp::LessThan => Some(i32::from(l < r)),
+                BinaryOp::LessThanOrEqual => Some(i32::from(l <= r)),
+                BinaryOp::Equal => Some(i32::from(l == r)),
+                BinaryOp::NotEqual => Some(i32::from(l != r)),
+                BinaryOp::LogicalAnd | BinaryOp::LogicalOr => Some(i32::from(r != 0)),
+                BinaryOp::AndThen
+                | BinaryOp::Assign
+                | BinaryOp::AddAssign
+                | BinaryOp::SubAssign
+                | BinaryOp::MulAssign
+                | BinaryOp::DivAssign
+                | BinaryOp::RemainderAssign
+                | BinaryOp::BitAndAssign
+                | BinaryOp::BitOrAssign
+                | BinaryOp::BitXorAssign
+                | BinaryOp::LeftShiftAssign
+                | BinaryOp::RightShiftAssign => None,
+            }
+        }
+        _ => None,
     }
 }
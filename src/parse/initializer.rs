@@ -0,0 +1,796 @@
+use crate::apperror::*;
+use crate::ast::*;
+use crate::parse::toplevel::{BitField, DeclaredMember, StructDefinition};
+use crate::strlit_collector::StrLitCollector;
+use crate::token::*;
+use std::collections::HashMap;
+use std::{iter::Peekable, slice::Iter};
+
+use super::combinator::satisfy;
+use super::context::Context;
+use super::expression::{
+    convert_arithmetic, ensure_assignable, parse_assignment_expr, parse_constant_expr,
+    warn_if_qualifiers_discarded, 定数式を評価,
+};
+use super::typ::Type;
+
+/// `= ` の後に来る初期化子。波括弧に包まれた並びは、要素ごとに指示子の列を持つ
+#[derive(Debug, Clone)]
+pub enum Initializer {
+    Expr {
+        expr: Box<Expr>,
+        pos: usize,
+    },
+    List {
+        items: Vec<(Vec<Designator>, Initializer)>,
+        pos: usize,
+    },
+}
+
+/// `.member =` や `[index] =` のような指示子
+#[derive(Debug, Clone)]
+pub enum Designator {
+    Member { name: String, pos: usize },
+    Index { index: i32, pos: usize },
+}
+
+/// 初期化子を平坦化した結果の一つ一つ。初期化される変数の先頭から `offset` バイトの位置を、`expr` の値で初期化する
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InitializedField {
+    pub offset: i32,
+    pub typ: Type,
+    pub bit_field: Option<BitField>,
+    pub expr: Box<Expr>,
+    pub pos: usize,
+}
+
+/// コンパイル時に評価された、静的な記憶域を持つ変数の初期値
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StaticInitializer {
+    pub bytes: Vec<u8>,
+    pub relocations: Vec<Relocation>,
+}
+
+/// `bytes` の `offset` の位置に、`target` のアドレスに `addend` を足した 8 バイトの値を書き込む
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Relocation {
+    pub offset: i32,
+    pub target: RelocationTarget,
+    pub addend: i32,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RelocationTarget {
+    GlobalVar(String),
+    Func(String),
+    /// 文字列リテラルの中身。静的な初期値から指される文字列は、データ領域に置かれる
+    StrLit(String),
+}
+
+pub fn parse_initializer(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Initializer, AppError> {
+    let pos = tokens.peek().unwrap().pos;
+    if tokens.peek().unwrap().tok != Tok::開き波括弧 {
+        let expr = parse_assignment_expr(strlit_collector, context, tokens, filename, input)?;
+        return Ok(Initializer::Expr {
+            expr: Box::new(expr),
+            pos,
+        });
+    }
+    tokens.next();
+
+    let mut items = Vec::new();
+    loop {
+        if tokens.peek().unwrap().tok == Tok::閉じ波括弧 {
+            tokens.next();
+            break;
+        }
+
+        let mut designators = Vec::new();
+        loop {
+            match tokens.peek().unwrap() {
+                Token { tok: Tok::Dot, pos } => {
+                    tokens.next();
+                    let Some(Token {
+                        tok: Tok::Identifier(name),
+                        ..
+                    }) = tokens.next()
+                    else {
+                        return Err(AppError {
+                            message: "`.` の後にメンバー名がありません".to_string(),
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos: *pos + 1,
+                        });
+                    };
+                    designators.push(Designator::Member {
+                        name: name.clone(),
+                        pos: *pos,
+                    });
+                }
+                Token {
+                    tok: Tok::開き角括弧,
+                    pos,
+                } => {
+                    tokens.next();
+                    let index = parse_constant_expr(context, tokens, filename, input)?;
+                    satisfy(
+                        tokens,
+                        filename,
+                        input,
+                        |tok| tok == &Tok::閉じ角括弧,
+                        "指示子の添字の後に閉じ角括弧がありません",
+                    )?;
+                    designators.push(Designator::Index { index, pos: *pos });
+                }
+                _ => break,
+            }
+        }
+        if !designators.is_empty() {
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::Assign,
+                "指示子の後に = がありません",
+            )?;
+        }
+
+        let item = parse_initializer(strlit_collector, context, tokens, filename, input)?;
+        items.push((designators, item));
+
+        match tokens.next().unwrap() {
+            Token {
+                tok: Tok::Comma, ..
+            } => {}
+            Token {
+                tok: Tok::閉じ波括弧,
+                ..
+            } => break,
+            Token { pos, .. } => {
+                return Err(AppError {
+                    message: "初期化子の並びの中で、カンマか閉じ波括弧が期待されていました"
+                        .to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                })
+            }
+        }
+    }
+    Ok(Initializer::List { items, pos })
+}
+
+/// 初期化子を、初期化されるスカラー（と構造体の値）の列に平坦化する。
+/// 明示されなかった部分は列に含まれないので、呼び出し側でゼロにしておく必要がある。
+/// `int a[] = {1, 2, 3};` のように大きさが省略された配列は、初期化子から大きさを決めた型を返す
+pub fn 初期化子を平坦化(
+    context: &Context,
+    strlit_collector: &StrLitCollector,
+    typ: &Type,
+    init: &Initializer,
+    filename: &str,
+    input: &str,
+) -> Result<(Type, Vec<InitializedField>), AppError> {
+    let mut flattener = 平坦化 {
+        struct_names: &context.global_declarations.struct_names,
+        strlit_collector,
+        filename,
+        input,
+        fields: Vec::new(),
+    };
+    let init = flattener.波括弧を外す(typ, init);
+    let typ = match (typ.unqualified(), init) {
+        (Type::IncompleteArr(elem), Initializer::List { items, .. }) => {
+            let mut items = items.iter().peekable();
+            let len = flattener.集成体を並びで初期化(typ, 0, &mut items, true, None)?;
+            flattener.余った初期化子がないことを確認(items)?;
+            Type::Arr(elem.clone(), len).qualified(typ.qualifiers())
+        }
+        (Type::IncompleteArr(elem), Initializer::Expr { expr, pos }) => {
+            let Some(string) = flattener.文字列リテラルの中身(expr) else {
+                return Err(flattener.error(
+                    "配列は波括弧で包まれた初期化子で初期化する必要があります",
+                    *pos,
+                ));
+            };
+            let typ = Type::Arr(elem.clone(), string.len() as i32 + 1).qualified(typ.qualifiers());
+            flattener.初期化子(&typ, 0, None, init)?;
+            typ
+        }
+        _ => {
+            flattener.初期化子(typ, 0, None, init)?;
+            typ.clone()
+        }
+    };
+    context
+        .ensure_complete_type(&typ)
+        .map_err(|message| AppError {
+            message,
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos: match init {
+                Initializer::Expr { pos, .. } | Initializer::List { pos, .. } => *pos,
+            },
+        })?;
+    Ok((typ, flattener.fields))
+}
+
+struct 平坦化<'a> {
+    struct_names: &'a HashMap<String, StructDefinition>,
+    strlit_collector: &'a StrLitCollector,
+    filename: &'a str,
+    input: &'a str,
+    fields: Vec<InitializedField>,
+}
+
+impl<'a> 平坦化<'a> {
+    fn error(&self, message: &str, pos: usize) -> AppError {
+        AppError {
+            message: message.to_string(),
+            input: self.input.to_string(),
+            filename: self.filename.to_string(),
+            pos,
+        }
+    }
+
+    fn 文字列リテラルの中身(&self, expr: &Expr) -> Option<&'a String> {
+        let Expr::Call { ident, .. } = expr else {
+            return None;
+        };
+        let id = ident.strip_prefix("__builtin_strlit_")?.parse().ok()?;
+        self.strlit_collector.search_string_from_id(id)
+    }
+
+    /// `char s[] = {"abc"};` のように波括弧に包まれた文字列リテラルは、波括弧を外して扱う
+    fn 波括弧を外す<'b>(&self, typ: &Type, init: &'b Initializer) -> &'b Initializer {
+        if let (Type::Arr(elem, _) | Type::IncompleteArr(elem), Initializer::List { items, .. }) =
+            (typ.unqualified(), init)
+        {
+            if let [(designators, item @ Initializer::Expr { expr, .. })] = &items[..] {
+                if matches!(elem.unqualified(), Type::Char | Type::UChar)
+                    && designators.is_empty()
+                    && self.文字列リテラルの中身(expr).is_some()
+                {
+                    return item;
+                }
+            }
+        }
+        init
+    }
+
+    fn 余った初期化子がないことを確認(
+        &self,
+        mut items: Peekable<Iter<(Vec<Designator>, Initializer)>>,
+    ) -> Result<(), AppError> {
+        match items.next() {
+            Some((_, Initializer::Expr { pos, .. } | Initializer::List { pos, .. })) => {
+                Err(self.error("初期化子が多すぎます", *pos))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn 初期化子(
+        &mut self,
+        typ: &Type,
+        offset: i32,
+        bit_field: Option<BitField>,
+        init: &Initializer,
+    ) -> Result<(), AppError> {
+        match self.波括弧を外す(typ, init) {
+            Initializer::Expr { expr, pos } => {
+                self.式で初期化(typ, offset, bit_field, expr, *pos)
+            }
+            Initializer::List { items, .. } if is_aggregate(typ) => {
+                let mut items = items.iter().peekable();
+                self.集成体を並びで初期化(typ, offset, &mut items, true, None)?;
+                self.余った初期化子がないことを確認(items)
+            }
+            // スカラーを初期化する波括弧の中には、初期化子を高々一つだけ書ける
+            Initializer::List { items, pos } => match &items[..] {
+                [] => Ok(()),
+                [(designators, item)] if designators.is_empty() => {
+                    self.初期化子(typ, offset, bit_field, item)
+                }
+                _ => Err(self.error("スカラーの初期化子が多すぎます", *pos)),
+            },
+        }
+    }
+
+    /// 波括弧に包まれた並びから、集成体の要素やメンバーを先頭から順に初期化する。
+    /// 波括弧が省略されている（`is_braced` が偽の）ときは、指示子が来たら外側の並びに戻る。
+    /// `.b.x = 7` のような入れ子の指示子は、外側の並びで `.b` を処理した後、残りの `.x` を
+    /// `先頭の指示子` として渡し、`b` を波括弧の省略された並びとして続けて初期化させる。
+    /// 配列については、初期化された要素の数を返す
+    fn 集成体を並びで初期化<'i>(
+        &mut self,
+        typ: &Type,
+        offset: i32,
+        items: &mut Peekable<Iter<'i, (Vec<Designator>, Initializer)>>,
+        is_braced: bool,
+        mut 先頭の指示子: Option<&'i [Designator]>,
+    ) -> Result<i32, AppError> {
+        match typ.unqualified() {
+            Type::Arr(elem, _) | Type::IncompleteArr(elem) => {
+                let len = match typ.unqualified() {
+                    Type::Arr(_, len) => Some(*len),
+                    _ => None,
+                };
+                let elem_size = elem.sizeof(self.struct_names);
+                let mut next_index = 0;
+                let mut max_len = 0;
+                while let Some((item_designators, _)) = items.peek() {
+                    let (designators, is_continued) = match 先頭の指示子.take() {
+                        Some(designators) => (designators, true),
+                        None => (&item_designators[..], false),
+                    };
+                    if let Some(first) = designators.first() {
+                        if !is_braced && !is_continued {
+                            break;
+                        }
+                        let index = self.添字の指示子(first, len)?;
+                        self.指示された部分から初期化(
+                            elem,
+                            offset + index * elem_size,
+                            None,
+                            &designators[1..],
+                            items,
+                        )?;
+                        next_index = index + 1;
+                    } else {
+                        if len.is_some_and(|len| next_index >= len) {
+                            break;
+                        }
+                        self.次の要素を初期化(
+                            elem,
+                            offset + next_index * elem_size,
+                            None,
+                            items,
+                        )?;
+                        next_index += 1;
+                    }
+                    max_len = max_len.max(next_index);
+                }
+                Ok(max_len)
+            }
+            Type::Struct { struct_name } => {
+                let def = &self.struct_names[struct_name];
+                let mut next_index = 0;
+                while let Some((item_designators, _)) = items.peek() {
+                    let (designators, is_continued) = match 先頭の指示子.take() {
+                        Some(designators) => (designators, true),
+                        None => (&item_designators[..], false),
+                    };
+                    if let Some(first) = designators.first() {
+                        if !is_braced && !is_continued {
+                            break;
+                        }
+                        let (name, pos) = match first {
+                            Designator::Member { name, pos } => (name, *pos),
+                            Designator::Index { pos, .. } => {
+                                return Err(self
+                                    .error("構造体の初期化子に、添字の指示子は使えません", *pos))
+                            }
+                        };
+                        let Some(member) = def.members.get(name) else {
+                            return Err(self.error(
+                                &format!(
+                                    "構造体 {} にメンバー {name} がありません",
+                                    def.struct_name
+                                ),
+                                pos,
+                            ));
+                        };
+                        self.指示された部分から初期化(
+                            &member.member_type,
+                            offset + member.offset,
+                            member.bit_field,
+                            &designators[1..],
+                            items,
+                        )?;
+                        next_index = self.宣言順の位置(def, name) + 1;
+                    } else {
+                        // 共用体を位置で初期化するときは、最初のメンバーだけを初期化する
+                        let limit = if def.is_union {
+                            1
+                        } else {
+                            def.declaration_order.len()
+                        };
+                        if next_index >= limit {
+                            break;
+                        }
+                        let (member_type, member_offset, bit_field) =
+                            match &def.declaration_order[next_index] {
+                                DeclaredMember::Named(name) => {
+                                    let member = &def.members[name];
+                                    (member.member_type.clone(), member.offset, member.bit_field)
+                                }
+                                DeclaredMember::Anonymous {
+                                    offset,
+                                    struct_name,
+                                } => (
+                                    Type::Struct {
+                                        struct_name: struct_name.clone(),
+                                    },
+                                    *offset,
+                                    None,
+                                ),
+                            };
+                        self.次の要素を初期化(
+                            &member_type,
+                            offset + member_offset,
+                            bit_field,
+                            items,
+                        )?;
+                        next_index += 1;
+                    }
+                }
+                Ok(0)
+            }
+            t => unreachable!("集成体ではない型 {t:?} を並びで初期化しようとしています"),
+        }
+    }
+
+    /// 名前を持つメンバーか、それを含む無名のメンバーが、宣言順で何番目にあるか
+    fn 宣言順の位置(&self, def: &StructDefinition, name: &str) -> usize {
+        def.declaration_order
+            .iter()
+            .position(|member| match member {
+                DeclaredMember::Named(n) => n == name,
+                DeclaredMember::Anonymous { struct_name, .. } => {
+                    self.struct_names[struct_name].members.contains_key(name)
+                }
+            })
+            .expect("メンバーが宣言順の列に見つかりません")
+    }
+
+    fn 添字の指示子(
+        &self,
+        designator: &Designator,
+        len: Option<i32>,
+    ) -> Result<i32, AppError> {
+        match designator {
+            Designator::Index { index, pos } => {
+                if *index < 0 || len.is_some_and(|len| *index >= len) {
+                    return Err(self.error(&format!("添字 {index} が配列の範囲外です"), *pos));
+                }
+                Ok(*index)
+            }
+            Designator::Member { pos, .. } => {
+                Err(self.error("配列の初期化子に、メンバーの指示子は使えません", *pos))
+            }
+        }
+    }
+
+    /// 並びの次の初期化子で、要素やメンバーを一つ初期化する。並びの次の初期化子の指示子は、処理済みでなければならない。
+    /// 集成体が波括弧なしの初期化子で初期化されるときは、並びからその集成体の分だけを取る
+    fn 次の要素を初期化(
+        &mut self,
+        typ: &Type,
+        offset: i32,
+        bit_field: Option<BitField>,
+        items: &mut Peekable<Iter<(Vec<Designator>, Initializer)>>,
+    ) -> Result<(), AppError> {
+        let (_, init) = items.peek().unwrap();
+        if is_aggregate(typ) {
+            if let Initializer::Expr { expr, .. } = init {
+                if !self.そのまま初期化できる(typ, expr) {
+                    self.集成体を並びで初期化(typ, offset, items, false, Some(&[]))?;
+                    return Ok(());
+                }
+            }
+        }
+        items.next();
+        self.初期化子(typ, offset, bit_field, init)
+    }
+
+    /// 指示子の列の先頭が選んだ要素やメンバーを、残りの指示子 `designators` に従って並びの次の初期化子から初期化する。
+    /// 残りの指示子があれば、選ばれた集成体の中で、指示された部分に続く要素やメンバーも並びから初期化する
+    fn 指示された部分から初期化<'i>(
+        &mut self,
+        typ: &Type,
+        offset: i32,
+        bit_field: Option<BitField>,
+        designators: &'i [Designator],
+        items: &mut Peekable<Iter<'i, (Vec<Designator>, Initializer)>>,
+    ) -> Result<(), AppError> {
+        let Some(first) = designators.first() else {
+            return self.次の要素を初期化(typ, offset, bit_field, items);
+        };
+        if !is_aggregate(typ) {
+            let (Designator::Member { pos, .. } | Designator::Index { pos, .. }) = first;
+            return Err(self.error("スカラーに指示子は使えません", *pos));
+        }
+        self.集成体を並びで初期化(typ, offset, items, false, Some(designators))?;
+        Ok(())
+    }
+
+    /// 集成体を、波括弧を使わずに一つの式で初期化できるか
+    fn そのまま初期化できる(&self, typ: &Type, expr: &Expr) -> bool {
+        match typ.unqualified() {
+            Type::Arr(elem, _) | Type::IncompleteArr(elem) => {
+                matches!(elem.unqualified(), Type::Char | Type::UChar)
+                    && self.文字列リテラルの中身(expr).is_some()
+            }
+            t => *t == expr.typ(),
+        }
+    }
+
+    fn 式で初期化(
+        &mut self,
+        typ: &Type,
+        offset: i32,
+        bit_field: Option<BitField>,
+        expr: &Expr,
+        pos: usize,
+    ) -> Result<(), AppError> {
+        if is_aggregate(typ) {
+            if !self.そのまま初期化できる(typ, expr) {
+                return Err(self.error(
+                    &format!(
+                        "{:?} 型の式で {:?} 型を初期化することはできません",
+                        expr.typ(),
+                        typ
+                    ),
+                    pos,
+                ));
+            }
+            if let Type::Arr(_, len) = typ.unqualified() {
+                let string = self.文字列リテラルの中身(expr).unwrap();
+                // 配列の大きさが文字列の長さと等しいときは、終端のヌル文字を含めない
+                if string.len() as i32 > *len {
+                    return Err(self.error("文字列リテラルが配列に収まりません", pos));
+                }
+                let bytes = string.bytes().chain(std::iter::once(0)).take(*len as usize);
+                for (i, byte) in bytes.enumerate() {
+                    self.fields.push(InitializedField {
+                        offset: offset + i as i32,
+                        typ: Type::Char,
+                        bit_field: None,
+                        expr: Box::new(Expr::Numeric {
+                            val: i32::from(byte as i8),
+                            pos,
+                            typ: Type::Char,
+                        }),
+                        pos,
+                    });
+                }
+                return Ok(());
+            }
+        }
+
+        let expr = decay_if_arr(expr.clone());
+        ensure_assignable(typ, &expr.typ()).map_err(|message| self.error(&message, pos))?;
+        warn_if_qualifiers_discarded(typ, &expr.typ(), self.input, self.filename, pos);
+        self.fields.push(InitializedField {
+            offset,
+            typ: typ.unqualified().clone(),
+            bit_field,
+            expr: convert_arithmetic(expr, typ, pos),
+            pos,
+        });
+        Ok(())
+    }
+}
+
+fn is_aggregate(typ: &Type) -> bool {
+    matches!(
+        typ.unqualified(),
+        Type::Arr(..) | Type::IncompleteArr(_) | Type::Struct { .. }
+    )
+}
+
+/// 平坦化された初期化子を、コンパイル時に評価してバイト列にする
+pub fn 静的な初期値を評価(
+    fields: &[InitializedField],
+    size: i32,
+    strlit_collector: &StrLitCollector,
+    filename: &str,
+    input: &str,
+) -> Result<StaticInitializer, AppError> {
+    let mut bytes = vec![0u8; size as usize];
+    let mut relocations: Vec<Relocation> = Vec::new();
+    for field in fields {
+        let not_constant = || AppError {
+            message: "静的な記憶域を持つ変数の初期化子が、定数式ではありません".to_string(),
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos: field.pos,
+        };
+        let mut relocation = None;
+        let value: u64 = match &field.typ {
+            Type::Int | Type::UInt | Type::Char | Type::UChar | Type::Bool => {
+                i64::from(整数定数を評価(&field.expr).ok_or_else(not_constant)?) as u64
+            }
+            Type::Float => u64::from(
+                (浮動小数点定数を評価(&field.expr).ok_or_else(not_constant)? as f32).to_bits(),
+            ),
+            Type::Double => 浮動小数点定数を評価(&field.expr)
+                .ok_or_else(not_constant)?
+                .to_bits(),
+            Type::Ptr(_) => match &*field.expr {
+                Expr::NullPtr { .. } => 0,
+                expr => match 整数定数を評価(expr) {
+                    Some(val) => i64::from(val) as u64,
+                    None => {
+                        let (target, addend) =
+                            アドレス定数を評価(expr, strlit_collector).ok_or_else(not_constant)?;
+                        relocation = Some(Relocation {
+                            offset: field.offset,
+                            target,
+                            addend,
+                        });
+                        0
+                    }
+                },
+            },
+            _ => return Err(not_constant()),
+        };
+
+        // 後から書かれた初期化子が、前に書かれたものを上書きする
+        let unit_size = field.typ.sizeof_primitive("static initializer");
+        relocations
+            .retain(|r| r.offset + 8 <= field.offset || field.offset + unit_size <= r.offset);
+        relocations.extend(relocation);
+
+        let offset = field.offset as usize;
+        let unit = &mut bytes[offset..offset + unit_size as usize];
+        let value = match field.bit_field {
+            Some(BitField {
+                bit_offset, width, ..
+            }) => {
+                let mut buf = [0u8; 8];
+                buf[..unit.len()].copy_from_slice(unit);
+                let mask = ((1u64 << width) - 1) << bit_offset;
+                (u64::from_le_bytes(buf) & !mask) | ((value << bit_offset) & mask)
+            }
+            None => value,
+        };
+        let len = unit.len();
+        unit.copy_from_slice(&value.to_le_bytes()[..len]);
+    }
+    Ok(StaticInitializer { bytes, relocations })
+}
+
+fn 整数定数を評価(expr: &Expr) -> Option<i32> {
+    match expr {
+        Expr::Cast {
+            expr: inner,
+            typ: Type::Bool,
+            ..
+        } => {
+            if inner.typ().is_floating() {
+                Some(i32::from(浮動小数点定数を評価(inner)? != 0.0))
+            } else {
+                Some(i32::from(整数定数を評価(inner)? != 0))
+            }
+        }
+        Expr::Cast {
+            expr: inner,
+            typ: typ @ (Type::Int | Type::UInt | Type::Char | Type::UChar),
+            ..
+        } if inner.typ().is_floating() => {
+            let val = 浮動小数点定数を評価(inner)?;
+            Some(match typ {
+                Type::UInt => val as u32 as i32,
+                Type::Char => i32::from(val as i32 as i8),
+                Type::UChar => i32::from(val as i32 as u8),
+                _ => val as i32,
+            })
+        }
+        _ => 定数式を評価(expr),
+    }
+}
+
+fn 浮動小数点定数を評価(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::FloatingNumeric { bits, typ, .. } => Some(if *typ == Type::Float {
+            f64::from(f32::from_bits(*bits as u32))
+        } else {
+            f64::from_bits(*bits)
+        }),
+        Expr::Cast {
+            expr: inner, typ, ..
+        } if typ.is_floating() => {
+            let val = if inner.typ().is_floating() {
+                浮動小数点定数を評価(inner)?
+            } else if inner.typ() == Type::UInt {
+                f64::from(整数定数を評価(inner)? as u32)
+            } else {
+                f64::from(整数定数を評価(inner)?)
+            };
+            Some(if *typ == Type::Float {
+                f64::from(val as f32)
+            } else {
+                val
+            })
+        }
+        Expr::BinaryExpr {
+            op,
+            左辺,
+            右辺,
+            typ,
+            ..
+        } if typ.is_floating() => {
+            let (l, r) = (浮動小数点定数を評価(左辺)?, 浮動小数点定数を評価(右辺)?);
+            match op {
+                BinaryOp::Add => Some(l + r),
+                BinaryOp::Sub => Some(l - r),
+                BinaryOp::Mul => Some(l * r),
+                BinaryOp::Div => Some(l / r),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `&g`、`arr + 2`、`&s.member`、`"abc"`、`f` のような、グローバル変数・関数・文字列リテラルのアドレスに定数を足したもの
+fn アドレス定数を評価(
+    expr: &Expr,
+    strlit_collector: &StrLitCollector,
+) -> Option<(RelocationTarget, i32)> {
+    match expr {
+        Expr::DecayedArr { expr, .. }
+        | Expr::UnaryExpr {
+            op: UnaryOp::Addr,
+            expr,
+            ..
+        } => 左辺値のアドレスを評価(expr, strlit_collector),
+        Expr::DecayedFunc { ident, .. } => Some((RelocationTarget::Func(ident.clone()), 0)),
+        Expr::BinaryExpr {
+            op: op @ (BinaryOp::Add | BinaryOp::Sub),
+            左辺,
+            右辺,
+            typ: Type::Ptr(_),
+            ..
+        } => {
+            let (target, addend) = アドレス定数を評価(左辺, strlit_collector)?;
+            let offset = 定数式を評価(右辺)?;
+            Some((
+                target,
+                if *op == BinaryOp::Add {
+                    addend + offset
+                } else {
+                    addend - offset
+                },
+            ))
+        }
+        Expr::Cast {
+            expr,
+            typ: Type::Ptr(_),
+            ..
+        } => アドレス定数を評価(expr, strlit_collector),
+        _ => None,
+    }
+}
+
+fn 左辺値のアドレスを評価(
+    expr: &Expr,
+    strlit_collector: &StrLitCollector,
+) -> Option<(RelocationTarget, i32)> {
+    match expr {
+        Expr::Identifier {
+            ident,
+            local_var_id: None,
+            ..
+        } => Some((RelocationTarget::GlobalVar(ident.clone()), 0)),
+        Expr::Call { ident, .. } => {
+            let id = ident.strip_prefix("__builtin_strlit_")?.parse().ok()?;
+            let string = strlit_collector.search_string_from_id(id)?;
+            Some((RelocationTarget::StrLit(string.clone()), 0))
+        }
+        Expr::UnaryExpr {
+            op: UnaryOp::Deref,
+            expr,
+            ..
+        } => アドレス定数を評価(expr, strlit_collector),
+        _ => None,
+    }
+}
@@ -1,6 +1,7 @@
 pub mod combinator;
 pub mod context;
 pub mod expression;
+pub mod initializer;
 pub mod statement;
 pub mod toplevel;
 pub mod typ;
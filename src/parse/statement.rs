@@ -1,23 +1,30 @@
 use crate::apperror::*;
 use crate::ast::*;
 use crate::parse::combinator::recover;
-use crate::parse::context::Context;
-use crate::parse::toplevel::TypeAndSize;
+use crate::parse::context::{Context, SwitchLabels};
+use crate::parse::toplevel::{
+    parse_static_variable_definition, parse_storage_class, GlobalVarDeclaration, Linkage,
+    StorageClass, SymbolDeclaration, TypeAndSize,
+};
 use crate::parse::typ::Type;
 use crate::strlit_collector::StrLitCollector;
 use crate::token::*;
 use std::{iter::Peekable, slice::Iter};
 
 use super::combinator::satisfy;
-use super::expression::parse_expr;
-use super::typ::parse_type;
+use super::expression::{
+    convert_arithmetic, ensure_assignable, parse_assignment_expr, parse_constant_expr, parse_expr,
+    sizeof_expr, warn_if_qualifiers_discarded, 定数式を評価, 隠れたローカル変数, 順に評価,
+};
+use super::initializer::{parse_initializer, 初期化子を平坦化};
+use super::typ::{parse_qualifiers, parse_type};
 
 #[test]
 fn parse_test() {
     use crate::parse::toplevel::GlobalDeclarations;
     use crate::parse::typ::Type;
     use crate::tokenize::tokenize;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
     let input = "5 - 3;";
     let tokens = tokenize(input, "test.c").unwrap();
     let mut tokens = tokens.iter().peekable();
@@ -28,7 +35,9 @@ fn parse_test() {
                 Vec::new(),
                 GlobalDeclarations {
                     symbols: HashMap::new(),
-                    struct_names: HashMap::new()
+                    struct_names: HashMap::new(),
+                    enum_tags: HashSet::new(),
+                    file_scope_tags: HashSet::new(),
                 },
                 Type::Void
             ),
@@ -65,85 +74,348 @@ pub fn parse_statement_or_declaration(
     filename: &str,
     input: &str,
 ) -> Result<StatementOrDeclaration, AppError> {
-    if let Some((local_var_type, local_var_name)) = recover(tokens, |tokens| {
-        parse_type_and_identifier(tokens, filename, input)
-    })? {
-        match tokens.peek().unwrap() {
-            Token {
-                tok: Tok::Semicolon,
-                ..
-            } => {
-                tokens.next();
+    // `T:` の T が typedef 名であってもラベルとして読むので、型より先に見る
+    if let Token {
+        tok: Tok::Identifier(name),
+        pos,
+    } = tokens.peek().unwrap()
+    {
+        if tokens
+            .clone()
+            .nth(1)
+            .is_some_and(|tok| tok.tok == Tok::Colon)
+        {
+            tokens.next();
+            tokens.next();
+            if !context.labels.insert(name.clone()) {
+                return Err(AppError {
+                    message: format!("ラベル {name} が、同じ関数の中で重複しています"),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                });
+            }
+            let body = Box::new(parse_statement_or_declaration(
+                strlit_collector,
+                context,
+                tokens,
+                filename,
+                input,
+            )?);
+            return Ok(StatementOrDeclaration::Statement(Statement::Labeled {
+                name: name.clone(),
+                body,
+                pos: *pos,
+            }));
+        }
+    }
 
-                let typ_and_size = TypeAndSize {
-                    typ: local_var_type.clone(),
-                    size: local_var_type.sizeof(&context.global_declarations.struct_names),
-                };
+    if let Token {
+        tok: Tok::Typedef, ..
+    } = tokens.peek().unwrap()
+    {
+        let semicolon_pos = parse_typedef(context, tokens, filename, input)?;
+        // `typedef int T[n];` では、typedef の位置で配列の大きさを計算しておく
+        let mut sizes = std::mem::take(&mut context.var_len_arr_sizes);
+        return Ok(match sizes.pop() {
+            None => StatementOrDeclaration::TypeDeclaration,
+            Some(last) => StatementOrDeclaration::Statement(Statement::Expr {
+                expr: Box::new(順に評価(sizes, last)),
+                semicolon_pos,
+            }),
+        });
+    }
 
-                let id = context.insert_local_var(local_var_name.clone(), typ_and_size.clone());
-                Ok(StatementOrDeclaration::Declaration {
-                    name: local_var_name,
-                    typ_and_size,
-                    id,
-                })
-            }
-            Token {
-                tok: Tok::Assign,
-                pos,
-            } => {
-                tokens.next();
-                let expr = Box::new(parse_expr(
-                    strlit_collector,
-                    context,
-                    tokens,
-                    filename,
-                    input,
-                )?);
-                match tokens.peek().unwrap() {
-                    Token {
-                        tok: Tok::Semicolon,
-                        ..
-                    } => {
-                        tokens.next();
-                    }
-                    _ => {
-                        return Err(AppError {
-                            message: "期待されたセミコロンが来ませんでした".to_string(),
-                            input: input.to_string(),
-                            filename: filename.to_string(),
-                            pos: *pos,
-                        })
-                    }
-                };
-
-                let typ_and_size = TypeAndSize {
-                    typ: local_var_type.clone(),
-                    size: local_var_type.sizeof(&context.global_declarations.struct_names),
-                };
-
-                let id = context.insert_local_var(local_var_name.clone(), typ_and_size.clone());
-                Ok(StatementOrDeclaration::DeclarationWithInitializer {
-                    name: local_var_name,
-                    initializer: expr,
-                    typ_and_size,
-                    id,
-                })
-            }
+    if let Some(storage_class) = parse_storage_class(tokens) {
+        let declaration = parse_static_or_extern_local_declaration(
+            strlit_collector,
+            context,
+            tokens,
+            filename,
+            input,
+            storage_class,
+        )?;
+        satisfy(
+            tokens,
+            filename,
+            input,
+            |tok| tok == &Tok::Semicolon,
+            "期待されたセミコロンが来ませんでした",
+        )?;
+        return Ok(declaration);
+    }
 
-            Token { pos, .. } => Err(AppError {
+    if let Some(base_type) = recover(tokens, |tokens| {
+        parse_type(context, tokens, filename, input)
+    })? {
+        if let Token {
+            tok: Tok::Semicolon,
+            ..
+        } = tokens.peek().unwrap()
+        {
+            tokens.next();
+            return Ok(StatementOrDeclaration::TypeDeclaration);
+        }
+        let declarator_pos = tokens.peek().unwrap().pos;
+        let (local_var_type, local_var_name) =
+            parse_identifier_declarator(context, tokens, filename, input, base_type)?;
+        if !matches!(tokens.peek().unwrap().tok, Tok::Semicolon | Tok::Assign) {
+            return Err(AppError {
                 message: "関数内の変数宣言で、型名と識別子の後にセミコロン以外が来ました"
                     .to_string(),
                 input: input.to_string(),
                 filename: filename.to_string(),
-                pos: *pos,
-            }),
+                pos: tokens.peek().unwrap().pos,
+            });
         }
+        let declaration = parse_local_variable_rest(
+            strlit_collector,
+            context,
+            tokens,
+            filename,
+            input,
+            local_var_name,
+            local_var_type,
+            declarator_pos,
+        )?;
+        satisfy(
+            tokens,
+            filename,
+            input,
+            |tok| tok == &Tok::Semicolon,
+            "期待されたセミコロンが来ませんでした",
+        )?;
+        Ok(declaration)
     } else {
         parse_statement(strlit_collector, context, tokens, filename, input)
             .map(StatementOrDeclaration::Statement)
     }
 }
 
+/// ブロックスコープでの `static int x = 3` や `extern int g` をパースする。後に続くセミコロンは読まない。
+/// static なローカル変数は、宣言位置を付けた名前のグローバル変数として配置される
+fn parse_static_or_extern_local_declaration(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    storage_class: StorageClass,
+) -> Result<StatementOrDeclaration, AppError> {
+    let base_type = parse_type(context, tokens, filename, input)?;
+    let declarator_pos = tokens.peek().unwrap().pos;
+    let (typ, name) = parse_identifier_declarator(context, tokens, filename, input, base_type)?;
+    let error_at = |message: String, pos: usize| AppError {
+        message,
+        input: input.to_string(),
+        filename: filename.to_string(),
+        pos,
+    };
+    if typ.is_variably_modified() {
+        return Err(error_at(
+            "static や extern を付けた変数の型には、可変長配列を含められません".to_string(),
+            declarator_pos,
+        ));
+    }
+
+    let (global_name, typ) = match storage_class {
+        StorageClass::Static => {
+            let global_name = format!("{name}@{declarator_pos}");
+            let definition = parse_static_variable_definition(
+                strlit_collector,
+                context,
+                tokens,
+                filename,
+                input,
+                global_name.clone(),
+                typ,
+                declarator_pos,
+            )?;
+            let typ = definition.typ.clone();
+            context.static_local_vars.push(definition);
+            (global_name, typ)
+        }
+        StorageClass::Extern => {
+            if tokens.peek().unwrap().tok == Tok::Assign {
+                return Err(error_at(
+                    "ブロックスコープの extern 宣言には初期化子を書けません".to_string(),
+                    tokens.peek().unwrap().pos,
+                ));
+            }
+            // ファイルスコープに同じ名前の宣言がまだなければ、そこにも宣言を加えておく。
+            // 使われているのに定義が見つからなければ、コード生成の後でエラーになる
+            context
+                .global_declarations
+                .symbols
+                .entry(name.clone())
+                .or_insert_with(|| {
+                    SymbolDeclaration::GVar(GlobalVarDeclaration {
+                        typ: typ.clone(),
+                        linkage: Linkage::External,
+                        is_defined: false,
+                        pos: declarator_pos,
+                    })
+                });
+            (name.clone(), typ)
+        }
+    };
+
+    let size = context.ensure_complete_type(&typ).map_or(0, |()| {
+        typ.sizeof(&context.global_declarations.struct_names)
+    });
+    context
+        .insert_static_local_var(name, global_name, TypeAndSize { typ, size })
+        .map_err(|message| error_at(message, declarator_pos))?;
+    Ok(StatementOrDeclaration::StaticDeclaration)
+}
+
+/// ローカル変数の宣言子の後に続く、省略可能な初期化子をパースして、変数を登録する。後に続くセミコロンは読まない
+#[allow(clippy::too_many_arguments)]
+fn parse_local_variable_rest(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    local_var_name: String,
+    local_var_type: Type,
+    declarator_pos: usize,
+) -> Result<StatementOrDeclaration, AppError> {
+    let has_initializer = tokens.peek().unwrap().tok == Tok::Assign;
+    let sizes = std::mem::take(&mut context.var_len_arr_sizes);
+    // typedef 名を使った宣言では、配列の大きさは typedef の位置で計算済み
+    if !sizes.is_empty() || local_var_type.is_variably_modified() {
+        return parse_var_len_arr_declaration_rest(
+            strlit_collector,
+            context,
+            tokens,
+            filename,
+            input,
+            local_var_name,
+            local_var_type,
+            declarator_pos,
+            sizes,
+        );
+    }
+    // 初期化子があれば、最初の次元の大きさを省略できる
+    let typ_to_be_complete = match local_var_type.unqualified() {
+        Type::IncompleteArr(elem) if has_initializer => elem,
+        t => t,
+    };
+    context
+        .ensure_complete_type(typ_to_be_complete)
+        .map_err(|message| AppError {
+            message,
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos: declarator_pos,
+        })?;
+
+    if !has_initializer {
+        let typ_and_size = TypeAndSize {
+            typ: local_var_type.clone(),
+            size: local_var_type.sizeof(&context.global_declarations.struct_names),
+        };
+        let id = context.insert_local_var(local_var_name.clone(), typ_and_size.clone());
+        return Ok(StatementOrDeclaration::Declaration {
+            name: local_var_name,
+            typ_and_size,
+            id,
+        });
+    }
+
+    tokens.next();
+    let init = parse_initializer(strlit_collector, context, tokens, filename, input)?;
+    let (local_var_type, initializer) = 初期化子を平坦化(
+        context,
+        strlit_collector,
+        &local_var_type,
+        &init,
+        filename,
+        input,
+    )?;
+    let typ_and_size = TypeAndSize {
+        typ: local_var_type.clone(),
+        size: local_var_type.sizeof(&context.global_declarations.struct_names),
+    };
+    let id = context.insert_local_var(local_var_name.clone(), typ_and_size.clone());
+    Ok(StatementOrDeclaration::DeclarationWithInitializer {
+        name: local_var_name,
+        id,
+        typ_and_size,
+        initializer,
+    })
+}
+
+/// 可変長配列や、そのポインタの宣言子の後に続く部分をパースする。`sizes` は宣言子の中の配列の大きさを計算する式。
+/// 可変長配列の変数そのものには、実行時に確保した領域の先頭のアドレスを保存するので、変数の大きさはポインタと同じになる
+#[allow(clippy::too_many_arguments)]
+fn parse_var_len_arr_declaration_rest(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    local_var_name: String,
+    local_var_type: Type,
+    declarator_pos: usize,
+    sizes: Vec<Expr>,
+) -> Result<StatementOrDeclaration, AppError> {
+    let mut exprs = sizes;
+    let initializer = if tokens.peek().unwrap().tok == Tok::Assign {
+        if let Type::VarLenArr(..) = local_var_type.unqualified() {
+            return Err(AppError {
+                message: "可変長配列は初期化できません".to_string(),
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos: tokens.peek().unwrap().pos,
+            });
+        }
+        tokens.next();
+        let init = parse_initializer(strlit_collector, context, tokens, filename, input)?;
+        初期化子を平坦化(
+            context,
+            strlit_collector,
+            &local_var_type,
+            &init,
+            filename,
+            input,
+        )?
+        .1
+    } else {
+        vec![]
+    };
+
+    let id = context.insert_local_var(
+        local_var_name.clone(),
+        TypeAndSize {
+            typ: local_var_type.clone(),
+            size: 8,
+        },
+    );
+    // ポインタの初期化子は、配列の大きさを計算した後の代入として扱う
+    for field in initializer {
+        exprs.push(Expr::BinaryExpr {
+            op: BinaryOp::Assign,
+            op_pos: declarator_pos,
+            左辺: Box::new(Expr::Identifier {
+                ident: local_var_name.clone(),
+                pos: declarator_pos,
+                typ: local_var_type.clone(),
+                local_var_id: Some(id),
+            }),
+            右辺: field.expr,
+            typ: local_var_type.unqualified().clone(),
+        });
+    }
+    Ok(StatementOrDeclaration::VarLenArrDeclaration {
+        name: local_var_name,
+        id,
+        typ: local_var_type,
+        exprs,
+    })
+}
+
 pub fn return_void(pos: usize) -> Statement {
     Statement::Return {
         semicolon_pos: pos,
@@ -156,6 +428,20 @@ pub fn return_void(pos: usize) -> Statement {
     }
 }
 
+/// ループの本体をパースする。本体の中には break と continue が書ける
+fn parse_loop_body(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<StatementOrDeclaration, AppError> {
+    context.loop_depth += 1;
+    let body = parse_statement_or_declaration(strlit_collector, context, tokens, filename, input);
+    context.loop_depth -= 1;
+    body
+}
+
 fn parse_statement(
     strlit_collector: &mut StrLitCollector,
     context: &mut Context,
@@ -165,6 +451,18 @@ fn parse_statement(
 ) -> Result<Statement, AppError> {
     let tok = tokens.peek().unwrap();
     match tok {
+        // 空文は、何もしない空のブロックとして扱う
+        Token {
+            tok: Tok::Semicolon,
+            pos,
+        } => {
+            tokens.next();
+            Ok(Statement::Block {
+                statements: vec![],
+                pos: *pos,
+                saved_stack_pointer: None,
+            })
+        }
         Token {
             tok: Tok::Throw, ..
         } => {
@@ -260,9 +558,16 @@ fn parse_statement(
                     })
                 }
             };
+            ensure_assignable(&context.return_type, &expr.typ()).map_err(|message| AppError {
+                message,
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos,
+            })?;
+            warn_if_qualifiers_discarded(&context.return_type, &expr.typ(), input, filename, pos);
             Ok(Statement::Return {
                 semicolon_pos,
-                expr,
+                expr: convert_arithmetic(expr, &context.return_type, pos),
                 return_type: context.return_type.clone(),
             })
         }
@@ -365,7 +670,7 @@ fn parse_statement(
                 |tok| tok == &Tok::閉じ丸括弧,
                 "期待された閉じ括弧が来ませんでした",
             )?;
-            let body = Box::new(parse_statement_or_declaration(
+            let body = Box::new(parse_loop_body(
                 strlit_collector,
                 context,
                 tokens,
@@ -378,6 +683,230 @@ fn parse_statement(
                 pos: *pos,
             })
         }
+        Token {
+            tok: Tok::Switch,
+            pos,
+        } => {
+            tokens.next();
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::開き丸括弧,
+                "期待された開き括弧が来ませんでした",
+            )?;
+            let cond_pos = tokens.peek().unwrap().pos;
+            let cond = decay_if_arr(parse_expr(
+                strlit_collector,
+                context,
+                tokens,
+                filename,
+                input,
+            )?);
+            if !cond.typ().is_integer() {
+                return Err(AppError {
+                    message: format!(
+                        "switch 文の条件式の型が {:?} ですが、整数型でなければなりません",
+                        cond.typ()
+                    ),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: cond_pos,
+                });
+            }
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::閉じ丸括弧,
+                "期待された閉じ括弧が来ませんでした",
+            )?;
+
+            context.switches.push(SwitchLabels::default());
+            let body =
+                parse_statement_or_declaration(strlit_collector, context, tokens, filename, input);
+            let SwitchLabels { cases, has_default } = context.switches.pop().unwrap();
+            Ok(Statement::Switch {
+                cond,
+                body: Box::new(body?),
+                cases,
+                has_default,
+                pos: *pos,
+            })
+        }
+        Token {
+            tok: tok @ (Tok::Case | Tok::Default),
+            pos,
+        } => {
+            tokens.next();
+            let value = if *tok == Tok::Case {
+                Some(parse_constant_expr(context, tokens, filename, input)?)
+            } else {
+                None
+            };
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::Colon,
+                "期待されたコロンが来ませんでした",
+            )?;
+            let error = |message: String| AppError {
+                message,
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos: *pos,
+            };
+            let Some(labels) = context.switches.last_mut() else {
+                return Err(error(format!(
+                    "{} ラベルが switch 文の外にあります",
+                    if value.is_some() { "case" } else { "default" }
+                )));
+            };
+            match value {
+                Some(value) if labels.cases.contains(&value) => {
+                    return Err(error(format!(
+                        "case の値 {value} が、同じ switch 文の中で重複しています"
+                    )));
+                }
+                Some(value) => labels.cases.push(value),
+                None if labels.has_default => {
+                    return Err(error(
+                        "default ラベルが、同じ switch 文の中で重複しています".to_string(),
+                    ));
+                }
+                None => labels.has_default = true,
+            }
+
+            let body = Box::new(parse_statement_or_declaration(
+                strlit_collector,
+                context,
+                tokens,
+                filename,
+                input,
+            )?);
+            Ok(match value {
+                Some(value) => Statement::Case {
+                    value,
+                    body,
+                    pos: *pos,
+                },
+                None => Statement::Default { body, pos: *pos },
+            })
+        }
+        Token {
+            tok: Tok::Goto,
+            pos,
+        } => {
+            tokens.next();
+            let name = match tokens.next() {
+                Some(Token {
+                    tok: Tok::Identifier(name),
+                    ..
+                }) => name.clone(),
+                _ => {
+                    return Err(AppError {
+                        message: "goto の後にラベルの名前が来ませんでした".to_string(),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *pos,
+                    })
+                }
+            };
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::Semicolon,
+                "期待されたセミコロンが来ませんでした",
+            )?;
+            context.gotos.push((name.clone(), *pos));
+            Ok(Statement::Goto { name, pos: *pos })
+        }
+        Token { tok: Tok::Do, pos } => {
+            tokens.next();
+            let body = Box::new(parse_loop_body(
+                strlit_collector,
+                context,
+                tokens,
+                filename,
+                input,
+            )?);
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::While,
+                "do 文の本体の後に while が来ませんでした",
+            )?;
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::開き丸括弧,
+                "期待された開き括弧が来ませんでした",
+            )?;
+            let cond = decay_if_arr(parse_expr(
+                strlit_collector,
+                context,
+                tokens,
+                filename,
+                input,
+            )?);
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::閉じ丸括弧,
+                "期待された閉じ括弧が来ませんでした",
+            )?;
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::Semicolon,
+                "期待されたセミコロンが来ませんでした",
+            )?;
+            Ok(Statement::DoWhile {
+                body,
+                cond,
+                pos: *pos,
+            })
+        }
+        Token {
+            tok: tok @ (Tok::Break | Tok::Continue),
+            pos,
+        } => {
+            tokens.next();
+            if *tok == Tok::Break && context.loop_depth == 0 && context.switches.is_empty() {
+                return Err(AppError {
+                    message: "break 文がループや switch 文の外にあります".to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                });
+            }
+            if *tok == Tok::Continue && context.loop_depth == 0 {
+                return Err(AppError {
+                    message: "continue 文がループの外にあります".to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                });
+            }
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |tok| tok == &Tok::Semicolon,
+                "期待されたセミコロンが来ませんでした",
+            )?;
+            Ok(if *tok == Tok::Break {
+                Statement::Break { pos: *pos }
+            } else {
+                Statement::Continue { pos: *pos }
+            })
+        }
         Token { tok: Tok::For, pos } => {
             tokens.next();
             satisfy(
@@ -409,63 +938,36 @@ fn parse_statement(
                 _ => {
                     // either an expression or a declaration
 
+                    let declaration_pos = tokens.peek().unwrap().pos;
                     if let Some((local_var_type, local_var_name)) = recover(tokens, |tokens| {
-                        parse_type_and_identifier(tokens, filename, input)
+                        parse_type_and_identifier(context, tokens, filename, input)
                     })? {
-                        let typ_and_size = TypeAndSize {
-                            typ: local_var_type.clone(),
-                            size: local_var_type.sizeof(&context.global_declarations.struct_names),
-                        };
-
-                        let id =
-                            context.insert_local_var(local_var_name.clone(), typ_and_size.clone());
-
-                        match tokens.peek().unwrap() {
-                            Token {
-                                tok: Tok::Assign, ..
-                            } => {
-                                tokens.next();
-                                let expr = decay_if_arr(parse_expr(
-                                    strlit_collector,
-                                    context,
-                                    tokens,
-                                    filename,
-                                    input,
-                                )?);
-                                satisfy(
-                                    tokens,
-                                    filename,
-                                    input,
-                                    |tok| tok == &Tok::Semicolon,
-                                    "期待されたセミコロンが来ませんでした",
-                                )?;
-                                Box::new(StatementOrDeclaration::DeclarationWithInitializer {
-                                    name: local_var_name,
-                                    id,
-                                    typ_and_size,
-                                    initializer: expr,
-                                })
-                            }
-                            Token {
-                                tok: Tok::Semicolon,
-                                ..
-                            } => {
-                                tokens.next();
-                                Box::new(StatementOrDeclaration::Declaration {
-                                    name: local_var_name,
-                                    id,
-                                    typ_and_size,
-                                })
-                            }
-                            _ => {
-                                return Err(AppError {
-                                    message: "for の初期化で ; か = が来ませんでした".to_string(),
-                                    input: input.to_string(),
-                                    filename: filename.to_string(),
-                                    pos: tokens.peek().unwrap().pos,
-                                })
-                            }
+                        if !matches!(tokens.peek().unwrap().tok, Tok::Semicolon | Tok::Assign) {
+                            return Err(AppError {
+                                message: "for の初期化で ; か = が来ませんでした".to_string(),
+                                input: input.to_string(),
+                                filename: filename.to_string(),
+                                pos: tokens.peek().unwrap().pos,
+                            });
                         }
+                        let declaration = parse_local_variable_rest(
+                            strlit_collector,
+                            context,
+                            tokens,
+                            filename,
+                            input,
+                            local_var_name,
+                            local_var_type,
+                            declaration_pos,
+                        )?;
+                        satisfy(
+                            tokens,
+                            filename,
+                            input,
+                            |tok| tok == &Tok::Semicolon,
+                            "期待されたセミコロンが来ませんでした",
+                        )?;
+                        Box::new(declaration)
                     } else {
                         let expr = parse_expr(strlit_collector, context, tokens, filename, input)?;
                         satisfy(
@@ -526,7 +1028,7 @@ fn parse_statement(
                 |tok| tok == &Tok::閉じ丸括弧,
                 "期待された閉じ括弧が来ませんでした",
             )?;
-            let body = Box::new(parse_statement_or_declaration(
+            let body = Box::new(parse_loop_body(
                 strlit_collector,
                 context,
                 tokens,
@@ -578,9 +1080,23 @@ fn parse_statement(
                     )?),
                 }
             }
+            let declares_var_len_arr = statements.iter().any(|stmt| {
+                matches!(
+                    stmt,
+                    StatementOrDeclaration::VarLenArrDeclaration { typ, .. }
+                        if matches!(typ.unqualified(), Type::VarLenArr(..))
+                )
+            });
+            let saved_stack_pointer = declares_var_len_arr.then(|| {
+                context.insert_hidden_local_var(TypeAndSize {
+                    typ: Type::Ptr(Box::new(Type::Void)),
+                    size: 8,
+                })
+            });
             Ok(Statement::Block {
                 statements,
                 pos: *pos,
+                saved_stack_pointer,
             })
         }
         _ => {
@@ -607,34 +1123,21 @@ fn parse_statement(
     }
 }
 
-fn consume_num(
-    tokens: &mut Peekable<Iter<Token>>,
-    filename: &str,
-    input: &str,
-    msg: &str,
-) -> Result<i32, AppError> {
-    match tokens.peek().unwrap() {
-        Token {
-            tok: Tok::Num(n), ..
-        } => {
-            tokens.next();
-            Ok(*n)
-        }
-        Token { pos, .. } => Err(AppError {
-            message: msg.to_string(),
-            input: input.to_string(),
-            filename: filename.to_string(),
-            pos: *pos,
-        }),
-    }
-}
-
 pub fn parse_角括弧に包まれた数の列(
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
     typ: &mut Type,
 ) -> Result<(), AppError> {
+    /// 配列の一つの次元の大きさ
+    enum 大きさ {
+        省略,
+        定数(i32),
+        /// 可変長配列の、int 型に変換された大きさの式
+        実行時(Box<Expr>, usize),
+    }
+
     let mut sizes = vec![];
     while let Token {
         tok: Tok::開き角括弧,
@@ -642,44 +1145,400 @@ pub fn parse_角括弧に包まれた数の列(
     } = tokens.peek().unwrap()
     {
         tokens.next();
-        let s = consume_num(tokens, filename, input, "開き角括弧の後に数がない")?;
+        let pos = tokens.peek().unwrap().pos;
+        if tokens.peek().unwrap().tok == Tok::閉じ角括弧 {
+            tokens.next();
+            if !sizes.is_empty() {
+                return Err(AppError {
+                    message: "配列の大きさを省略できるのは、最初の次元だけです".to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos,
+                });
+            }
+            sizes.push(大きさ::省略);
+            continue;
+        }
+        // 関数の中や仮引数の型では、大きさが定数式でない配列は可変長配列になる
+        let s = if context.is_file_scope() && context.preceding_params.is_none() {
+            parse_constant_expr(context, tokens, filename, input)?
+        } else {
+            let expr = parse_assignment_expr(
+                &mut StrLitCollector::new(),
+                context,
+                tokens,
+                filename,
+                input,
+            )?;
+            match 定数式を評価(&expr) {
+                Some(s) => s,
+                None if expr.typ().is_arithmetic() && !expr.typ().is_floating() => {
+                    satisfy(
+                        tokens,
+                        filename,
+                        input,
+                        |tok| tok == &Tok::閉じ角括弧,
+                        "配列の大きさの後に閉じ角括弧がない",
+                    )?;
+                    let len = convert_arithmetic(Box::new(expr), &Type::Int, pos);
+                    sizes.push(大きさ::実行時(len, pos));
+                    continue;
+                }
+                None => {
+                    return Err(AppError {
+                        message: "配列の大きさが整数ではありません".to_string(),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos,
+                    })
+                }
+            }
+        };
+        if s < 0 {
+            return Err(AppError {
+                message: "配列の大きさが負です".to_string(),
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos,
+            });
+        }
         satisfy(
             tokens,
             filename,
             input,
             |tok| tok == &Tok::閉じ角括弧,
-            "数の後に閉じ角括弧がない",
+            "配列の大きさの後に閉じ角括弧がない",
         )?;
-        sizes.push(s);
+        sizes.push(大きさ::定数(s));
     }
 
     for s in sizes.into_iter().rev() {
-        let t = std::mem::replace(typ, Type::Int);
-        *typ = Type::Arr(Box::new(t), s);
+        let t = Box::new(std::mem::replace(typ, Type::Int));
+        *typ = match s {
+            大きさ::定数(s) => Type::Arr(t, s),
+            大きさ::省略 => Type::IncompleteArr(t),
+            大きさ::実行時(len, pos) => {
+                // 要素の大きさが分かってから配列全体の大きさを計算するよう、内側の次元から順に式を積む
+                let size_var = context.insert_hidden_local_var(TypeAndSize {
+                    typ: Type::Int,
+                    size: 4,
+                });
+                context.var_len_arr_sizes.push(Expr::BinaryExpr {
+                    op: BinaryOp::Assign,
+                    op_pos: pos,
+                    左辺: Box::new(隠れたローカル変数(size_var, Type::Int, pos)),
+                    右辺: Box::new(Expr::BinaryExpr {
+                        op: BinaryOp::Mul,
+                        op_pos: pos,
+                        左辺: len,
+                        右辺: Box::new(sizeof_expr(context, &t, pos)),
+                        typ: Type::Int,
+                    }),
+                    typ: Type::Int,
+                });
+                Type::VarLenArr(t, size_var)
+            }
+        };
     }
 
     Ok(())
 }
 
 pub fn parse_type_and_identifier(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<(Type, String), AppError> {
+    let typ = parse_type(context, tokens, filename, input)?;
+    parse_identifier_declarator(context, tokens, filename, input, typ)
+}
+
+/// キャストや sizeof に現れる、`int *`・`char [4]`・`int (*)(int)` のような型名をパースする
+pub fn parse_type_name(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<Type, AppError> {
+    let typ = parse_type(context, tokens, filename, input)?;
+    match parse_declarator(context, tokens, filename, input, typ)? {
+        (typ, None) => Ok(typ),
+        (_, Some((ident, pos))) => Err(AppError {
+            message: format!("型名の中に識別子 {ident} があります"),
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos,
+        }),
+    }
+}
+
+/// `typedef struct Node *NodePtr;` をパースし、typedef 名を context に登録する。セミコロンの位置を返す
+pub fn parse_typedef(
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
+) -> Result<usize, AppError> {
+    tokens.next(); // consume `typedef`
+    let base_type = parse_type(context, tokens, filename, input)?;
+    let pos = tokens.peek().unwrap().pos;
+    let (typ, name) = parse_identifier_declarator(context, tokens, filename, input, base_type)?;
+    let semicolon_pos = tokens.peek().unwrap().pos;
+    satisfy(
+        tokens,
+        filename,
+        input,
+        |tok| tok == &Tok::Semicolon,
+        "typedef 宣言の後にセミコロンがありません",
+    )?;
+    context
+        .insert_typedef(name, typ)
+        .map_err(|message| AppError {
+            message,
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos,
+        })?;
+    Ok(semicolon_pos)
+}
+
+/// 識別子を必ず持つ宣言子をパースする
+fn parse_identifier_declarator(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    typ: Type,
 ) -> Result<(Type, String), AppError> {
-    let mut typ = parse_type(tokens, filename, input)?;
-    match tokens.next().unwrap() {
+    let pos = tokens.peek().unwrap().pos;
+    match parse_declarator(context, tokens, filename, input, typ)? {
+        (typ, Some((ident, _))) => Ok((typ, ident)),
+        (_, None) => Err(AppError {
+            message: "「型と識別子」をパースできません".to_string(),
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos,
+        }),
+    }
+}
+
+/// `typ` を基本型として、`*fp`・`(*fp)(int)`・`a[3]` のような宣言子をパースする。
+/// 識別子はその位置と組にして返す。識別子を持たない抽象宣言子（`(*)(int)` など）の場合、識別子は `None` となる。
+pub fn parse_declarator(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    mut typ: Type,
+) -> Result<(Type, Option<(String, usize)>), AppError> {
+    while tokens.peek().unwrap().tok == Tok::Asterisk {
+        tokens.next();
+        typ = Type::Ptr(Box::new(typ)).qualified(parse_qualifiers(tokens));
+    }
+
+    // `(` の直後に `*` が来るなら、それは関数型の接尾辞ではなく、宣言子をグループ化する丸括弧
+    let mut t2 = tokens.clone();
+    let is_grouping_paren = matches!(
+        (t2.next(), t2.next()),
+        (
+            Some(Token {
+                tok: Tok::開き丸括弧,
+                ..
+            }),
+            Some(Token {
+                tok: Tok::Asterisk,
+                ..
+            })
+        )
+    );
+
+    match tokens.peek().unwrap() {
         Token {
             tok: Tok::Identifier(ident),
-            ..
+            pos,
         } => {
-            parse_角括弧に包まれた数の列(tokens, filename, input, &mut typ)?;
-            Ok((typ, ident.clone()))
+            tokens.next();
+            parse_宣言子の接尾辞(context, tokens, filename, input, &mut typ)?;
+            Ok((typ, Some((ident.clone(), *pos))))
         }
-        Token { pos, .. } => Err(AppError {
-            message: "「型と識別子」をパースできません".to_string(),
-            input: input.to_string(),
-            filename: filename.to_string(),
-            pos: *pos,
+        _ if is_grouping_paren => {
+            // `int (*fp)(int)` の型は、括弧の外側の `(int)` を読むまで確定しない。
+            // そこで括弧の中身はいったん読み飛ばし、外側の接尾辞を読んでから、複製しておいたイテレータで中身を読む。
+            tokens.next();
+            let mut inner = tokens.clone();
+            let mut depth = 1;
+            while depth > 0 {
+                match tokens.next() {
+                    Some(Token {
+                        tok: Tok::開き丸括弧,
+                        ..
+                    }) => depth += 1,
+                    Some(Token {
+                        tok: Tok::閉じ丸括弧,
+                        ..
+                    }) => depth -= 1,
+                    Some(_) => {}
+                    None => {
+                        return Err(AppError {
+                            message: "宣言子の開き丸括弧に対応する閉じ丸括弧がありません"
+                                .to_string(),
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos: input.len(),
+                        })
+                    }
+                }
+            }
+            parse_宣言子の接尾辞(context, tokens, filename, input, &mut typ)?;
+            let (typ, ident) = parse_declarator(context, &mut inner, filename, input, typ)?;
+            satisfy(
+                &mut inner,
+                filename,
+                input,
+                |tok| tok == &Tok::閉じ丸括弧,
+                "宣言子の開き丸括弧に対応する閉じ丸括弧がありません",
+            )?;
+            Ok((typ, ident))
+        }
+        _ => {
+            parse_宣言子の接尾辞(context, tokens, filename, input, &mut typ)?;
+            Ok((typ, None))
+        }
+    }
+}
+
+/// 宣言子の後ろに付く `[3][4]` や `(int, char *)` をパースし、`typ` を配列型や関数型にする。
+fn parse_宣言子の接尾辞(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    typ: &mut Type,
+) -> Result<(), AppError> {
+    if let Token {
+        tok: Tok::開き丸括弧,
+        ..
+    } = tokens.peek().unwrap()
+    {
+        tokens.next();
+        // 関数定義でなければ、仮引数の型に現れた可変長配列の大きさは計算しなくてよい
+        let (params, is_variadic, _) = parse_仮引数の列(context, tokens, filename, input)?;
+        let return_type = std::mem::replace(typ, Type::Int);
+        *typ = Type::Func {
+            return_type: Box::new(return_type.unqualified().clone()),
+            // 仮引数の名前は型には含まれないので読み捨てる
+            params: params.map(|params| params.into_iter().map(|(typ, _)| typ).collect()),
+            is_variadic,
+        };
+        return Ok(());
+    }
+    parse_角括弧に包まれた数の列(context, tokens, filename, input, typ)
+}
+
+/// 調整済みの仮引数の型と、名前とその位置の組。名前が省略されていれば `None`
+pub type 仮引数 = (Type, Option<(String, usize)>);
+
+/// 関数型の宣言子の仮引数の並び。`()` の場合は `None`。二つ目の値は、仮引数の並びが `...` で終わっているかどうか。
+/// 三つ目の値は、仮引数の型に現れた可変長配列の大きさを計算して隠れたローカル変数に保存する式で、
+/// 関数定義では本体の先頭で評価する
+pub type 仮引数の列 = (Option<Vec<仮引数>>, bool, Vec<Expr>);
+
+/// 関数型の宣言子の、開き丸括弧の後から閉じ丸括弧までをパースする。
+/// 仮引数の名前は省略できる。名前が必要な関数定義では、呼び出し側で確かめる
+pub fn parse_仮引数の列(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<仮引数の列, AppError> {
+    // 仮引数の列は入れ子になりうるので、外側の列で読んだ仮引数や可変長配列の大きさは退避しておく
+    let outer_params = context.preceding_params.replace(vec![]);
+    let outer_sizes = std::mem::take(&mut context.var_len_arr_sizes);
+    let result = parse_仮引数の列の残り(context, tokens, filename, input);
+    context.preceding_params = outer_params;
+    let sizes = std::mem::replace(&mut context.var_len_arr_sizes, outer_sizes);
+    let (params, is_variadic) = result?;
+    Ok((params, is_variadic, sizes))
+}
+
+fn parse_仮引数の列の残り(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<(Option<Vec<仮引数>>, bool), AppError> {
+    if let Token {
+        tok: Tok::閉じ丸括弧,
+        ..
+    } = tokens.peek().unwrap()
+    {
+        tokens.next();
+        return Ok((None, false));
+    }
+
+    // check whether the following two tokens are `void` and `)`
+    let mut t2 = tokens.clone();
+    if let (
+        Some(Token { tok: Tok::Void, .. }),
+        Some(Token {
+            tok: Tok::閉じ丸括弧,
+            ..
         }),
+    ) = (t2.next(), t2.next())
+    {
+        tokens.next(); // consume `void`
+        tokens.next(); // consume `)`
+        return Ok((Some(vec![]), false));
+    }
+
+    let mut params = vec![];
+    loop {
+        let typ = parse_type(context, tokens, filename, input)?;
+        let (typ, ident) = parse_declarator(context, tokens, filename, input, typ)?;
+        let param = (adjust_param_type(typ), ident);
+        if let Some(preceding_params) = &mut context.preceding_params {
+            preceding_params.push(param.clone());
+        }
+        params.push(param);
+        match tokens.next().unwrap() {
+            Token {
+                tok: Tok::閉じ丸括弧,
+                ..
+            } => return Ok((Some(params), false)),
+            Token {
+                tok: Tok::Comma, ..
+            } if tokens.peek().unwrap().tok == Tok::Ellipsis => {
+                tokens.next();
+                satisfy(
+                    tokens,
+                    filename,
+                    input,
+                    |tok| tok == &Tok::閉じ丸括弧,
+                    "... の後に閉じ丸括弧が来ませんでした",
+                )?;
+                return Ok((Some(params), true));
+            }
+            Token {
+                tok: Tok::Comma, ..
+            } => {}
+            Token { pos, .. } => {
+                return Err(AppError {
+                    message: "閉じ丸括弧かカンマが期待されていました".to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                })
+            }
+        }
+    }
+}
+
+/// 関数型の仮引数は関数へのポインタ型の仮引数として、配列型の仮引数は要素へのポインタ型の仮引数として扱われる
+fn adjust_param_type(typ: Type) -> Type {
+    match typ {
+        Type::Func { .. } => Type::Ptr(Box::new(typ)),
+        Type::Arr(t, _) | Type::IncompleteArr(t) | Type::VarLenArr(t, _) => Type::Ptr(t),
+        _ => typ,
     }
 }
@@ -1,7 +1,17 @@
 use super::combinator::satisfy;
+use super::expression::parse_constant_expr;
+use super::expression::順に評価;
+use super::initializer::parse_initializer;
+use super::initializer::Initializer;
+use super::initializer::StaticInitializer;
+use super::initializer::初期化子を平坦化;
+use super::initializer::静的な初期値を評価;
+use super::statement::parse_declarator;
 use super::statement::parse_statement_or_declaration;
-use super::statement::parse_type_and_identifier;
+use super::statement::parse_typedef;
+use super::statement::parse_仮引数の列;
 use super::statement::parse_角括弧に包まれた数の列;
+use super::statement::仮引数の列;
 use super::typ::parse_type;
 use super::typ::Type;
 use crate::apperror::*;
@@ -9,7 +19,7 @@ use crate::ast::*;
 use crate::parse::context::Context;
 use crate::parse::statement::return_void;
 use crate::token::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use crate::strlit_collector::StrLitCollector;
 use std::{iter::Peekable, slice::Iter};
 
@@ -17,37 +27,75 @@ use std::{iter::Peekable, slice::Iter};
 pub enum ToplevelDefOrDecl {
     FuncDef(FunctionDefinition),
     GVar(GlobalVariableDefinition),
+    /// `extern int x;` のように、定義を伴わない変数の宣言
+    GVarDecl {
+        name: String,
+        typ: Type,
+        pos: usize,
+    },
     FuncDecl {
         func_name: String,
         params: Option<Vec<Type>>,
+        is_variadic: bool,
         return_type: Type,
+        pos: usize,
     },
+    /// `enum E { A, B };` や typedef のように、型だけを宣言していて、変数や関数を宣言していない
+    TypeDeclaration,
 }
 
 #[derive(Debug, Clone)]
 pub struct GlobalVariableDefinition {
     pub name: String,
     pub typ: Type,
+    /// 初期化子を持たない変数は、ゼロで初期化される
+    pub initializer: Option<StaticInitializer>,
+    pub pos: usize,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct StructDefinition {
     pub struct_name: String,
+    /// 共用体の場合、全てのメンバーのオフセットが 0 になる
+    pub is_union: bool,
     pub size: i32,
     pub align: i32,
     pub members: HashMap<String, StructMember>,
+    /// 位置による初期化のために、メンバーを宣言された順に並べたもの。名前を持たないビットフィールドは含まない
+    pub declaration_order: Vec<DeclaredMember>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DeclaredMember {
+    Named(String),
+    /// 無名の構造体・共用体のメンバー。それが持つメンバーは `members` にも展開されている
+    Anonymous {
+        offset: i32,
+        struct_name: String,
+    },
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct StructMember {
     pub member_type: Type,
+    /// ビットフィールドの場合は、それを含む記憶単位（`member_type` の大きさ）のオフセット
     pub offset: i32,
+    pub bit_field: Option<BitField>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BitField {
+    /// 記憶単位の最下位ビットから数えた位置
+    pub bit_offset: u8,
+    pub width: u8,
+    pub is_signed: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionDefinition {
     pub func_name: String,
     pub params: Vec<(Type, String)>,
+    pub is_variadic: bool,
     pub pos: usize,
     pub statements: Vec<StatementOrDeclaration>,
     pub return_type: Type,
@@ -62,6 +110,7 @@ impl From<FunctionDefinition> for (String, FunctionSignature) {
                 pos: s.pos,
                 return_type: s.return_type,
                 params: Some(s.params.into_iter().map(|(typ, _)| typ).collect()),
+                is_variadic: s.is_variadic,
             },
         )
     }
@@ -71,20 +120,60 @@ impl From<FunctionDefinition> for (String, FunctionSignature) {
 pub struct FunctionSignature {
     /// `None` if it is a declaration of the form `int foo();`, which prior to C23 means "parameter types are unspecified"
     pub params: Option<Vec<Type>>,
+    /// 仮引数の並びが `...` で終わっているなら true
+    pub is_variadic: bool,
     pub pos: usize,
     pub return_type: Type,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SymbolDeclaration {
-    Func(FunctionSignature),
-    GVar(Type),
+    Func(FunctionSignature, Linkage),
+    GVar(GlobalVarDeclaration),
+    EnumConst(i32),
+    Typedef(Type),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GlobalVarDeclaration {
+    pub typ: Type,
+    pub linkage: Linkage,
+    /// extern で宣言されただけで、定義がまだ現れていなければ偽
+    pub is_defined: bool,
+    pub pos: usize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StorageClass {
+    Static,
+    Extern,
+}
+
+/// 翻訳単位は一つしかないので、結合は再宣言が矛盾していないかを確かめるためだけに使う
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Linkage {
+    External,
+    Internal,
+}
+
+pub fn parse_storage_class(tokens: &mut Peekable<Iter<Token>>) -> Option<StorageClass> {
+    let storage_class = match tokens.peek().unwrap().tok {
+        Tok::Static => StorageClass::Static,
+        Tok::Extern => StorageClass::Extern,
+        _ => return None,
+    };
+    tokens.next();
+    Some(storage_class)
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct GlobalDeclarations {
     pub symbols: HashMap<String, SymbolDeclaration>,
     pub struct_names: HashMap<String, StructDefinition>,
+    /// 定義された列挙型のタグ。struct_names と同じく、ブロックスコープのタグは宣言位置を付けた名前で持つ
+    pub enum_tags: HashSet<String>,
+    /// ファイルスコープで宣言された構造体・共用体・列挙型のタグ
+    pub file_scope_tags: HashSet<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -96,26 +185,33 @@ pub struct TypeAndSize {
 #[allow(clippy::too_many_arguments)]
 fn after_param_list(
     strlit_collector: &mut StrLitCollector,
-    previous_global_declarations: &GlobalDeclarations,
+    file_context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
-    params: Option<Vec<(Type, String)>>, // `None` if it is a declaration/definition of the form `int foo()`
+    // params is `None` if it is a declaration/definition of the form `int foo()`
+    (params, is_variadic, var_len_arr_sizes): 仮引数の列,
     pos: usize,
     return_type: Type,
     func_name: &str,
 ) -> Result<ToplevelDefOrDecl, AppError> {
+    // 戻り値は左辺値ではないので、一番外側の修飾子は意味を持たない
+    let return_type = return_type.unqualified().clone();
+
     // First, we check that params list has no duplicates
     let mut param_names = HashMap::new();
 
     if let Some(params) = &params {
-        for (typ, ident) in params {
+        for (typ, (ident, ident_pos)) in params
+            .iter()
+            .filter_map(|(typ, name)| Some((typ, name.as_ref()?)))
+        {
             if param_names.contains_key(ident) {
                 return Err(AppError {
                     message: format!("関数 {func_name} の引数 {ident} が重複しています"),
                     input: input.to_string(),
                     filename: filename.to_string(),
-                    pos,
+                    pos: *ident_pos,
                 });
             }
             param_names.insert(ident.clone(), typ.clone());
@@ -135,31 +231,77 @@ fn after_param_list(
             let mut param_declarations: Vec<(String, TypeAndSize)> = Vec::new();
 
             // 波括弧が後に続く場合は、() は (void) の意味
-            let params = params.unwrap_or_default();
+            let params = params
+                .unwrap_or_default()
+                .into_iter()
+                .enumerate()
+                .map(|(i, (typ, name))| match name {
+                    Some((name, _)) => Ok((typ, name)),
+                    None => Err(AppError {
+                        message: format!(
+                            "関数 {func_name} の定義で、{} 番目の仮引数に名前がありません",
+                            i + 1
+                        ),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos,
+                    }),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for typ in params.iter().map(|(typ, _)| typ).chain([&return_type]) {
+                file_context
+                    .ensure_complete_type(typ)
+                    .map_err(|message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos,
+                    })?;
+            }
 
             for (typ, ident) in &params {
                 let typ_and_size = TypeAndSize {
                     typ: typ.clone(),
-                    size: typ.sizeof(&previous_global_declarations.struct_names),
+                    size: typ.sizeof(&file_context.global_declarations.struct_names),
                 };
                 param_declarations.push((ident.clone(), typ_and_size));
             }
 
-            let mut global_declarations = (*previous_global_declarations).clone();
+            let mut global_declarations = file_context.global_declarations.clone();
 
             let signature = FunctionSignature {
                 params: Some(params.iter().map(|(typ, _)| (*typ).clone()).collect()),
+                is_variadic,
                 pos,
                 return_type: return_type.clone(),
             };
-            // 今読んでる関数の定義も足さないと再帰呼び出しができない
+            // 今読んでる関数の定義も足さないと再帰呼び出しができない。
+            // 結合は parse_all で登録するときに決めるので、ここでは以前の宣言のものを引き継ぐだけでよい
+            let linkage = match global_declarations.symbols.get(func_name) {
+                Some(SymbolDeclaration::Func(_, linkage)) => *linkage,
+                _ => Linkage::External,
+            };
             global_declarations.symbols.insert(
                 func_name.to_string(),
-                SymbolDeclaration::Func(signature.clone()),
+                SymbolDeclaration::Func(signature.clone(), linkage),
             );
 
             let mut context =
                 Context::new(param_declarations, global_declarations, return_type.clone());
+            context.is_variadic = is_variadic;
+            context.inherit_local_var_declarations(file_context.take_local_var_declarations());
+
+            // 仮引数の型に現れた可変長配列の大きさは、関数の本体に入ったときに計算する
+            let mut sizes = var_len_arr_sizes;
+            if let Some(last) = sizes.pop() {
+                statements_or_declarations.push(StatementOrDeclaration::Statement(
+                    Statement::Expr {
+                        expr: Box::new(順に評価(sizes, last)),
+                        semicolon_pos: pos,
+                    },
+                ));
+            }
 
             loop {
                 match tokens.peek() {
@@ -191,6 +333,38 @@ fn after_param_list(
                 }
             }
 
+            if let Some((name, goto_pos)) = context
+                .gotos
+                .iter()
+                .find(|(name, _)| !context.labels.contains(name))
+            {
+                return Err(AppError {
+                    message: format!(
+                        "goto 文の飛び先のラベル {name} が、関数 {func_name} の中にありません"
+                    ),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *goto_pos,
+                });
+            }
+
+            // 関数内で定義された構造体も、コード生成の際には必要になる
+            file_context.global_declarations.struct_names =
+                std::mem::take(&mut context.global_declarations.struct_names);
+            // static なローカル変数はグローバル変数として配置し、ブロックスコープでの extern 宣言はファイルスコープにも知らせる
+            file_context
+                .static_local_vars
+                .append(&mut context.static_local_vars);
+            for (name, decl) in std::mem::take(&mut context.global_declarations.symbols) {
+                if matches!(decl, SymbolDeclaration::GVar(_)) {
+                    file_context
+                        .global_declarations
+                        .symbols
+                        .entry(name)
+                        .or_insert(decl);
+                }
+            }
+
             if return_type == Type::Void {
                 statements_or_declarations
                     .push(StatementOrDeclaration::Statement(return_void(pos)));
@@ -199,6 +373,7 @@ fn after_param_list(
             Ok(ToplevelDefOrDecl::FuncDef(FunctionDefinition {
                 func_name: func_name.to_string(),
                 params,
+                is_variadic,
                 pos,
                 statements: statements_or_declarations,
                 return_type,
@@ -213,13 +388,18 @@ fn after_param_list(
             tokens.next();
             // If we reach here, it means we have a function declaration
 
+            // 宣言では、仮引数の型に現れた可変長配列の大きさを計算しないので、そのための変数も要らない
+            file_context.take_local_var_declarations();
+
             // strip the parameter names
             let params = params.map(|p| p.into_iter().map(|(typ, _)| typ).collect::<Vec<_>>());
 
             Ok(ToplevelDefOrDecl::FuncDecl {
                 func_name: func_name.to_string(),
                 params,
+                is_variadic,
                 return_type,
+                pos,
             })
         }
 
@@ -234,12 +414,121 @@ fn after_param_list(
 
 pub fn parse_toplevel_definition(
     strlit_collector: &mut StrLitCollector,
-    previous_declarations: &GlobalDeclarations,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<(Option<StorageClass>, ToplevelDefOrDecl), AppError> {
+    let storage_class = parse_storage_class(tokens);
+    let def_or_decl = parse_toplevel_definition_after_storage_class(
+        strlit_collector,
+        context,
+        tokens,
+        filename,
+        input,
+        storage_class,
+    )?;
+    Ok((storage_class, def_or_decl))
+}
+
+fn parse_toplevel_definition_after_storage_class(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
+    storage_class: Option<StorageClass>,
 ) -> Result<ToplevelDefOrDecl, AppError> {
-    let mut return_type = parse_type(tokens, filename, input)?;
+    if let Some(Token {
+        tok: Tok::Typedef, ..
+    }) = tokens.peek()
+    {
+        parse_typedef(context, tokens, filename, input)?;
+        return Ok(ToplevelDefOrDecl::TypeDeclaration);
+    }
+
+    let mut return_type = parse_type(context, tokens, filename, input)?;
+
+    if let Some(Token {
+        tok: Tok::Semicolon,
+        ..
+    }) = tokens.peek()
+    {
+        tokens.next();
+        return Ok(ToplevelDefOrDecl::TypeDeclaration);
+    }
+
+    // `int (*fp)(int);` のように、丸括弧で始まる宣言子を持つグローバル変数や関数の宣言
+    if let Some(Token {
+        tok: Tok::開き丸括弧,
+        pos,
+    }) = tokens.peek()
+    {
+        // 関数定義だった場合に仮引数の名前を読み直すため、宣言子の先頭を覚えておく
+        let mut declarator_tokens = tokens.clone();
+        let (typ, name) = parse_declarator(context, tokens, filename, input, return_type)?;
+        let Some((name, name_pos)) = name else {
+            return Err(AppError {
+                message: "グローバル変数の宣言に識別子がありません".to_string(),
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos: *pos,
+            });
+        };
+        if let Type::Func {
+            return_type,
+            params,
+            is_variadic,
+        } = typ
+        {
+            if tokens.peek().unwrap().tok == Tok::開き波括弧 {
+                // `int (*get(void))(int) { ... }` は関数ポインタを返す関数の定義。
+                // parse_declarator は仮引数の名前を読み捨てるので、識別子の直後の丸括弧から読み直す
+                while declarator_tokens.next().unwrap().pos != name_pos {}
+                declarator_tokens.next(); // consume `(`
+                let params =
+                    parse_仮引数の列(context, &mut declarator_tokens, filename, input)?;
+                return after_param_list(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                    params,
+                    *pos,
+                    *return_type,
+                    &name,
+                );
+            }
+            satisfy(
+                tokens,
+                filename,
+                input,
+                |t| *t == Tok::Semicolon,
+                "関数宣言の後のセミコロンが期待されていました",
+            )?;
+            // `int (*get(void))(int);` は関数ポインタを返す関数の宣言
+            return Ok(ToplevelDefOrDecl::FuncDecl {
+                func_name: name,
+                params,
+                is_variadic,
+                return_type: *return_type,
+                pos: *pos,
+            });
+        }
+        return parse_global_variable_rest(
+            strlit_collector,
+            context,
+            tokens,
+            filename,
+            input,
+            name,
+            typ,
+            storage_class,
+            *pos,
+        );
+    }
+
     match tokens.next().unwrap() {
         Token {
             tok: Tok::Identifier(ident),
@@ -247,121 +536,61 @@ pub fn parse_toplevel_definition(
         } => match tokens.peek().unwrap() {
             Token {
                 tok: Tok::開き丸括弧,
-                pos: open_pos,
+                ..
             } => {
                 tokens.next();
-
-                let mut params = Vec::new();
-
-                // If the next token is `)`, we have no parameters
-                if let Token {
-                        tok: Tok::閉じ丸括弧,
-                        ..
-                    } = tokens.peek().unwrap() {
-                    tokens.next();
-                    return after_param_list(
-                        strlit_collector,
-                        previous_declarations,
-                        tokens,
-                        filename,
-                        input,
-                        None,
-                        *pos,
-                        return_type,
-                        ident,
-                    );
-                }
-
-                // check whether the following two tokens are `void` and `)`
-                if let Some(Token {
-                    tok: Tok::Void,
-                    ..
-                }) = tokens.peek()
-                {
-                    let mut t2 = tokens.clone();
-                    t2.next();
-                    if let Some(Token {
-                        tok: Tok::閉じ丸括弧,
-                        ..
-                    }) = t2.next()
-                    {
-                        tokens.next(); // consume `void`
-                        tokens.next(); // consume `)`
-                        return after_param_list(
-                            strlit_collector,
-                            previous_declarations,
-                            tokens,
-                            filename,
-                            input,
-                            Some(vec![]), // definitely no parameters
-                            *pos,
-                            return_type,
-                            ident,
-                        );
-                    }
-                }
-
-
-                // We have at least one parameter
-
-
-                let param = parse_type_and_identifier(tokens,filename, input)?;
-                params.push(param);
-
-                loop {
-                    match tokens.peek().unwrap() {
-                        Token {
-                            tok: Tok::閉じ丸括弧,
-                            ..
-                        } => {
-                            tokens.next();
-                            return after_param_list(
-                                strlit_collector,
-                                previous_declarations,
-                                tokens,
-                                filename,
-                                input,
-                                Some(params),
-                                *pos,
-                                return_type,
-                                ident,
-                            );
-                        }
-                        Token {
-                            tok: Tok::Comma, ..
-                        } => {
-                            tokens.next();
-                            let param = parse_type_and_identifier(tokens, filename, input)?;
-                            params.push(param);
-                        }
-                        _ => {
-                            break Err(AppError {
-                                message: "閉じ丸括弧かカンマが期待されていました".to_string(),
-                                input: input.to_string(),
-                                filename: filename.to_string(),
-                                pos: *open_pos + 1,
-                            })
-                        }
-                    }
-                }
+                let params = parse_仮引数の列(context, tokens, filename, input)?;
+                after_param_list(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                    params,
+                    *pos,
+                    return_type,
+                    ident,
+                )
             }
             Token {
-                tok: Tok::Semicolon,
+                tok: Tok::Semicolon | Tok::Assign,
                 ..
-            } => {
-                tokens.next();
-                Ok(ToplevelDefOrDecl::GVar(GlobalVariableDefinition { name: ident.to_string(), typ: return_type }))
-            }
+            } => parse_global_variable_rest(
+                strlit_collector,
+                context,
+                tokens,
+                filename,
+                input,
+                ident.to_string(),
+                return_type,
+                storage_class,
+                *pos,
+            ),
             Token {
                 tok: Tok::開き角括弧,
                 ..
             } => {
-                parse_角括弧に包まれた数の列(tokens, filename,input, &mut return_type)?;
-                satisfy(tokens,filename, input, |t| *t == Tok::Semicolon, "グローバルな配列宣言の後のセミコロンが期待されていました")?;
-                Ok(ToplevelDefOrDecl::GVar(GlobalVariableDefinition { name: ident.to_string(), typ: return_type }))
+                parse_角括弧に包まれた数の列(
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                    &mut return_type,
+                )?;
+                parse_global_variable_rest(
+                    strlit_collector,
+                    context,
+                    tokens,
+                    filename,
+                    input,
+                    ident.to_string(),
+                    return_type,
+                    storage_class,
+                    *pos,
+                )
             }
             _ => Err(AppError {
-                message: "トップレベルに識別子がありますが、その後に来たものが「関数引数の丸括弧」でも「グローバル変数定義を終わらせるセミコロン」でも「グローバル変数として配列を定義するための開き角括弧」でもありません"
+                message: "トップレベルに識別子がありますが、その後に来たものが「関数引数の丸括弧」でも「グローバル変数定義を終わらせるセミコロン」でも「初期化子の前の =」でも「グローバル変数として配列を定義するための開き角括弧」でもありません"
                     .to_string(),
                 input: input.to_string(),
                 filename: filename.to_string(),
@@ -377,20 +606,140 @@ pub fn parse_toplevel_definition(
     }
 }
 
-pub fn parse_toplevel_struct_definition(
-    global_declarations: &mut GlobalDeclarations,
+/// グローバル変数の宣言子の後に続く、省略可能な初期化子とセミコロンをパースする
+#[allow(clippy::too_many_arguments)]
+fn parse_global_variable_rest(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    name: String,
+    typ: Type,
+    storage_class: Option<StorageClass>,
+    pos: usize,
+) -> Result<ToplevelDefOrDecl, AppError> {
+    // 初期化子のない extern 宣言は定義ではないので、`extern int a[];` のように不完全型でもよい
+    if storage_class == Some(StorageClass::Extern) && tokens.peek().unwrap().tok != Tok::Assign {
+        satisfy(
+            tokens,
+            filename,
+            input,
+            |t| *t == Tok::Semicolon,
+            "グローバル変数宣言の後のセミコロンが期待されていました",
+        )?;
+        return Ok(ToplevelDefOrDecl::GVarDecl { name, typ, pos });
+    }
+    let definition = parse_static_variable_definition(
+        strlit_collector,
+        context,
+        tokens,
+        filename,
+        input,
+        name,
+        typ,
+        pos,
+    )?;
+    satisfy(
+        tokens,
+        filename,
+        input,
+        |t| *t == Tok::Semicolon,
+        "グローバル変数宣言の後のセミコロンが期待されていました",
+    )?;
+    Ok(ToplevelDefOrDecl::GVar(definition))
+}
+
+/// 静的な記憶域を持つ変数の宣言子の後に続く、省略可能な初期化子をパースする。
+/// 初期化子はコンパイル時に評価する。後に続くセミコロンは読まない
+#[allow(clippy::too_many_arguments)]
+pub fn parse_static_variable_definition(
+    strlit_collector: &mut StrLitCollector,
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+    name: String,
+    typ: Type,
+    pos: usize,
+) -> Result<GlobalVariableDefinition, AppError> {
+    let has_initializer = tokens.peek().unwrap().tok == Tok::Assign;
+    // 初期化子があれば、最初の次元の大きさを省略できる
+    let typ_to_be_complete = match typ.unqualified() {
+        Type::IncompleteArr(elem) if has_initializer => elem,
+        t => t,
+    };
+    context
+        .ensure_complete_type(typ_to_be_complete)
+        .map_err(|message| AppError {
+            message,
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos,
+        })?;
+
+    if !has_initializer {
+        return Ok(GlobalVariableDefinition {
+            name,
+            typ,
+            initializer: None,
+            pos,
+        });
+    }
+    tokens.next();
+    let init = parse_initializer(strlit_collector, context, tokens, filename, input)?;
+    define_static_variable(
+        strlit_collector,
+        context,
+        filename,
+        input,
+        name,
+        &typ,
+        &init,
+        pos,
+    )
+}
+
+/// 静的な記憶域を持つ変数の定義を、初期化子をコンパイル時に評価して作る
+#[allow(clippy::too_many_arguments)]
+pub fn define_static_variable(
+    strlit_collector: &StrLitCollector,
+    context: &Context,
+    filename: &str,
+    input: &str,
+    name: String,
+    typ: &Type,
+    init: &Initializer,
+    pos: usize,
+) -> Result<GlobalVariableDefinition, AppError> {
+    let (typ, fields) =
+        初期化子を平坦化(context, strlit_collector, typ, init, filename, input)?;
+    let size = typ.sizeof(&context.global_declarations.struct_names);
+    let initializer =
+        静的な初期値を評価(&fields, size, strlit_collector, filename, input)?;
+    Ok(GlobalVariableDefinition {
+        name,
+        typ,
+        initializer: Some(initializer),
+        pos,
+    })
+}
+
+/// `struct Foo {` や `union Foo {` の後の `int x; char y; }` をパースし、定義を登録する
+pub fn parse_struct_body(
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
     struct_name: &str,
+    is_union: bool,
 ) -> Result<(), AppError> {
-    tokens.next(); // consume `struct`
-    tokens.next(); // consume `struct_name`
-    tokens.next(); // consume `{`
-
     let mut members = HashMap::new();
+    let mut declaration_order = Vec::new();
     let mut overall_alignment = 1;
-    let mut next_member_offset: i32 = 0;
+    // ビットフィールドを詰めるため、オフセットはビット単位で数える
+    let mut next_bit_offset: i32 = 0;
+    let mut max_member_size: i32 = 0;
 
     loop {
         match tokens.peek() {
@@ -411,147 +760,440 @@ pub fn parse_toplevel_struct_definition(
                 break;
             }
             _ => {
-                let member_type = parse_type(tokens, filename, input)?;
-                match tokens.next().unwrap() {
-                    Token {
-                        tok: Tok::Identifier(member_name),
-                        ..
-                    } => {
-                        satisfy(
-                            tokens,
-                            filename,
-                            input,
-                            |tok| tok == &Tok::Semicolon,
-                            "メンバーの後にセミコロンがありません",
-                        )?;
-                        let member_size = member_type.sizeof(&global_declarations.struct_names);
-                        if next_member_offset
-                            % member_type.alignof(&global_declarations.struct_names)
-                            != 0
-                        {
-                            next_member_offset += member_type
-                                .alignof(&global_declarations.struct_names)
-                                - (next_member_offset
-                                    % member_type.alignof(&global_declarations.struct_names));
+                // `struct { int a; int b; };` のような、名前を持たないメンバー
+                let mut lookahead = tokens.clone();
+                let is_anonymous_member =
+                    matches!(
+                        lookahead.next().map(|t| &t.tok),
+                        Some(Tok::Struct | Tok::Union)
+                    ) && matches!(lookahead.next().map(|t| &t.tok), Some(Tok::開き波括弧));
+
+                let member_type = parse_type(context, tokens, filename, input)?;
+                let member_pos = tokens.peek().unwrap().pos;
+                let (member_type, member_name) = match tokens.peek().unwrap().tok {
+                    Tok::Semicolon if is_anonymous_member => (member_type, None),
+                    // `int : 3;` は名前を持たないビットフィールド
+                    Tok::Colon => (member_type, None),
+                    _ => match parse_declarator(context, tokens, filename, input, member_type)? {
+                        (member_type, Some((member_name, _))) => (member_type, Some(member_name)),
+                        (member_type, None) if tokens.peek().unwrap().tok == Tok::Colon => {
+                            (member_type, None)
+                        }
+                        (_, None) => {
+                            return Err(AppError {
+                                message: "構造体のメンバー名がありません".to_string(),
+                                input: input.to_string(),
+                                filename: filename.to_string(),
+                                pos: member_pos,
+                            })
+                        }
+                    },
+                };
+                let width = if tokens.peek().unwrap().tok == Tok::Colon {
+                    tokens.next();
+                    Some(parse_constant_expr(context, tokens, filename, input)?)
+                } else {
+                    None
+                };
+                satisfy(
+                    tokens,
+                    filename,
+                    input,
+                    |tok| tok == &Tok::Semicolon,
+                    "メンバーの後にセミコロンがありません",
+                )?;
+                if member_type.is_variably_modified() {
+                    return Err(AppError {
+                        message: "メンバーの型には、可変長配列を含められません".to_string(),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: member_pos,
+                    });
+                }
+                context
+                    .ensure_complete_type(&member_type)
+                    .map_err(|message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: member_pos,
+                    })?;
+
+                let struct_names = &context.global_declarations.struct_names;
+                let member_size = member_type.sizeof(struct_names);
+                let member_align = member_type.alignof(struct_names);
+
+                if let Some(width) = width {
+                    if !member_type.is_integer() {
+                        return Err(AppError {
+                            message:
+                                "ビットフィールドの型は int か char か _Bool でなければなりません"
+                                    .to_string(),
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos: member_pos,
+                        });
+                    }
+                    let unit_bits = member_size * 8;
+                    // `_Bool` の幅は 1 ビットなので、それより広いビットフィールドは作れない
+                    let max_width = if *member_type.unqualified() == Type::Bool {
+                        1
+                    } else {
+                        unit_bits
+                    };
+                    if !(0..=max_width).contains(&width) {
+                        return Err(AppError {
+                            message: format!(
+                                "ビットフィールドの幅 {width} は 0 以上 {max_width} 以下でなければなりません"
+                            ),
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos: member_pos,
+                        });
+                    }
+
+                    // 幅 0 のビットフィールドは、次のビットフィールドを新しい記憶単位から始めさせる
+                    if width == 0 || next_bit_offset % unit_bits + width > unit_bits {
+                        if member_name.is_some() && width == 0 {
+                            return Err(AppError {
+                                message: "幅が 0 のビットフィールドには名前を付けられません"
+                                    .to_string(),
+                                input: input.to_string(),
+                                filename: filename.to_string(),
+                                pos: member_pos,
+                            });
+                        }
+                        if !is_union {
+                            next_bit_offset =
+                                (next_bit_offset + unit_bits - 1) / unit_bits * unit_bits;
                         }
-                        overall_alignment = overall_alignment
-                            .max(member_type.alignof(&global_declarations.struct_names));
-                        members.insert(
-                            member_name.to_owned(),
-                            StructMember {
-                                member_type,
-                                offset: next_member_offset,
-                            },
-                        );
-                        next_member_offset += member_size;
                     }
-                    Token { pos, .. } => {
+
+                    // 名前を持たないビットフィールドは、構造体のアラインメントに影響しない
+                    let Some(member_name) = member_name else {
+                        if !is_union {
+                            next_bit_offset += width;
+                        }
+                        continue;
+                    };
+                    overall_alignment = overall_alignment.max(member_align);
+                    let bit_start = if is_union { 0 } else { next_bit_offset };
+                    let is_signed = !member_type.is_unsigned();
+                    let member = StructMember {
+                        member_type,
+                        offset: bit_start / unit_bits * member_size,
+                        bit_field: Some(BitField {
+                            bit_offset: (bit_start % unit_bits) as u8,
+                            width: width as u8,
+                            is_signed,
+                        }),
+                    };
+                    if members.insert(member_name.clone(), member).is_some() {
                         return Err(AppError {
-                            message: "構造体のメンバー名がありません".to_string(),
+                            message: format!("メンバー {member_name} が重複しています"),
                             input: input.to_string(),
                             filename: filename.to_string(),
-                            pos: *pos,
+                            pos: member_pos,
+                        });
+                    }
+                    declaration_order.push(DeclaredMember::Named(member_name));
+                    if !is_union {
+                        next_bit_offset += width;
+                    }
+                    max_member_size = max_member_size.max((width + 7) / 8);
+                    continue;
+                }
+
+                let mut next_member_offset = (next_bit_offset + 7) / 8;
+                if next_member_offset % member_align != 0 {
+                    next_member_offset += member_align - (next_member_offset % member_align);
+                }
+                overall_alignment = overall_alignment.max(member_align);
+                let offset = if is_union { 0 } else { next_member_offset };
+
+                declaration_order.push(match (&member_name, &member_type) {
+                    (Some(member_name), _) => DeclaredMember::Named(member_name.clone()),
+                    (None, Type::Struct { struct_name }) => DeclaredMember::Anonymous {
+                        offset,
+                        struct_name: struct_name.clone(),
+                    },
+                    (None, _) => unreachable!("無名のメンバーは構造体か共用体のはずです"),
+                });
+
+                // 無名のメンバーが持つメンバーは、外側の構造体のメンバーとして扱う
+                let new_members = match (member_name, &member_type) {
+                    (Some(member_name), _) => vec![(
+                        member_name,
+                        StructMember {
+                            member_type,
+                            offset,
+                            bit_field: None,
+                        },
+                    )],
+                    (None, Type::Struct { struct_name }) => struct_names[struct_name]
+                        .members
+                        .iter()
+                        .map(|(name, member)| {
+                            (
+                                name.clone(),
+                                StructMember {
+                                    offset: offset + member.offset,
+                                    ..member.clone()
+                                },
+                            )
                         })
+                        .collect(),
+                    (None, _) => unreachable!("無名のメンバーは構造体か共用体のはずです"),
+                };
+                for (name, member) in new_members {
+                    if members.insert(name.clone(), member).is_some() {
+                        return Err(AppError {
+                            message: format!("メンバー {name} が重複しています"),
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos: member_pos,
+                        });
                     }
                 }
+                if !is_union {
+                    next_bit_offset = (next_member_offset + member_size) * 8;
+                }
+                max_member_size = max_member_size.max(member_size);
             }
         }
     }
 
-    satisfy(
-        tokens,
-        filename,
-        input,
-        |tok| tok == &Tok::Semicolon,
-        "構造体定義の終わりの直後にセミコロンがありません",
-    )?;
+    let unpadded_size = if is_union {
+        max_member_size
+    } else {
+        (next_bit_offset + 7) / 8
+    };
 
-    global_declarations.struct_names.insert(
+    context.global_declarations.struct_names.insert(
         struct_name.to_owned(),
         StructDefinition {
             struct_name: struct_name.to_owned(),
-            size: (next_member_offset as u32).div_ceil(overall_alignment as u32) as i32
+            is_union,
+            size: (unpadded_size as u32).div_ceil(overall_alignment as u32) as i32
                 * overall_alignment,
             align: overall_alignment,
             members,
+            declaration_order,
         },
     );
     Ok(())
 }
 
+/// ファイルスコープの変数や関数の宣言を登録する。同じ名前の以前の宣言があれば、
+/// それと結合や型が矛盾しないかを確かめ、二つを合わせた宣言で置き換える
+fn declare_global_symbol(
+    global_declarations: &mut GlobalDeclarations,
+    name: &str,
+    new_decl: SymbolDeclaration,
+    storage_class: Option<StorageClass>,
+) -> Result<(), String> {
+    let prior = global_declarations.symbols.get(name);
+    let prior_linkage = match prior {
+        None => None,
+        Some(SymbolDeclaration::Func(_, linkage)) => Some(*linkage),
+        Some(SymbolDeclaration::GVar(decl)) => Some(decl.linkage),
+        Some(SymbolDeclaration::EnumConst(_) | SymbolDeclaration::Typedef(_)) => {
+            return Err(format!(
+                "{name} は既に、列挙子か typedef 名としてファイルスコープで宣言されています"
+            ))
+        }
+    };
+    // extern と書かれた宣言や、記憶域クラスを持たない関数の宣言は、以前の宣言の結合を引き継ぐ
+    let linkage = match storage_class {
+        Some(StorageClass::Static) => Linkage::Internal,
+        Some(StorageClass::Extern) => prior_linkage.unwrap_or(Linkage::External),
+        None if matches!(new_decl, SymbolDeclaration::Func(..)) => {
+            prior_linkage.unwrap_or(Linkage::External)
+        }
+        None => Linkage::External,
+    };
+    if prior_linkage.is_some_and(|prior_linkage| prior_linkage != linkage) {
+        return Err(format!(
+            "{name} が、内部結合と外部結合の両方で宣言されています"
+        ));
+    }
+
+    let merged = match (prior, new_decl) {
+        (None, SymbolDeclaration::Func(signature, _)) => {
+            SymbolDeclaration::Func(signature, linkage)
+        }
+        (None, SymbolDeclaration::GVar(decl)) => {
+            SymbolDeclaration::GVar(GlobalVarDeclaration { linkage, ..decl })
+        }
+        (Some(SymbolDeclaration::Func(prior, _)), SymbolDeclaration::Func(signature, _)) => {
+            if prior.return_type != signature.return_type
+                || matches!((&prior.params, &signature.params), (Some(a), Some(b))
+                    if a.len() != b.len() || a.iter().zip(b).any(|(a, b)| !a.is_compatible_with(b)))
+                || (prior.params.is_some()
+                    && signature.params.is_some()
+                    && prior.is_variadic != signature.is_variadic)
+            {
+                return Err(format!(
+                    "関数 {name} が、以前の宣言と異なる型で再宣言されています"
+                ));
+            }
+            // 仮引数の型が分かっている方の宣言を残す
+            if signature.params.is_some() {
+                SymbolDeclaration::Func(signature, linkage)
+            } else {
+                SymbolDeclaration::Func(
+                    FunctionSignature {
+                        params: prior.params.clone(),
+                        is_variadic: prior.is_variadic,
+                        ..signature
+                    },
+                    linkage,
+                )
+            }
+        }
+        (Some(SymbolDeclaration::GVar(prior)), SymbolDeclaration::GVar(decl)) => {
+            // `extern int a[]; int a[3];` のように、配列の大きさは後の宣言で補える
+            let typ = match (prior.typ.unqualified(), decl.typ.unqualified()) {
+                _ if prior.typ == decl.typ => decl.typ,
+                (Type::IncompleteArr(e1), Type::Arr(e2, _)) if e1 == e2 => decl.typ,
+                (Type::Arr(e1, _), Type::IncompleteArr(e2)) if e1 == e2 => prior.typ.clone(),
+                _ => {
+                    return Err(format!(
+                        "変数 {name} が、以前の宣言と異なる型で再宣言されています"
+                    ))
+                }
+            };
+            SymbolDeclaration::GVar(GlobalVarDeclaration {
+                typ,
+                linkage,
+                is_defined: prior.is_defined || decl.is_defined,
+                pos: if prior.is_defined {
+                    prior.pos
+                } else {
+                    decl.pos
+                },
+            })
+        }
+        (Some(_), _) => {
+            return Err(format!("{name} が、関数と変数の両方として宣言されています"));
+        }
+        (None, _) => unreachable!("列挙子や typedef 名は declare_global_symbol では宣言しない"),
+    };
+    global_declarations.symbols.insert(name.to_string(), merged);
+    Ok(())
+}
+
 pub fn parse_all(
     strlit_collector: &mut StrLitCollector,
     global_declarations: &mut GlobalDeclarations,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
-) -> Result<Vec<FunctionDefinition>, AppError> {
+) -> Result<(Vec<FunctionDefinition>, Vec<GlobalVariableDefinition>), AppError> {
     let mut function_definitions: Vec<FunctionDefinition> = vec![];
+    let mut global_variable_definitions: Vec<GlobalVariableDefinition> = vec![];
+    let mut context = Context::new_file_scope(global_declarations.clone());
     while tokens.peek().is_some() {
-        // If it starts with the keyword `struct`, we might be seeing a toplevel struct definition:
-        // `struct Foo { int x; }`
-        // To check for that, we peek three tokens:
-
-        if let Some(Token {
-            tok: Tok::Struct, ..
-        }) = tokens.peek()
-        {
-            let mut duplicated_iter = tokens.clone();
-            duplicated_iter.next();
-            if let Some(Token {
-                tok: Tok::Identifier(struct_name),
-                ..
-            }) = duplicated_iter.next()
-            {
-                if let Some(Token {
-                    tok: Tok::開き波括弧,
-                    ..
-                }) = duplicated_iter.next()
+        let (storage_class, new_def_or_decl) =
+            parse_toplevel_definition(strlit_collector, &mut context, tokens, filename, input)?;
+        // static なローカル変数や、ファイルスコープの複合リテラルも、グローバル変数として配置する
+        global_variable_definitions.append(&mut context.static_local_vars);
+        let (name, new_decl, pos) = match new_def_or_decl {
+            ToplevelDefOrDecl::FuncDef(new_def) => {
+                let pos = new_def.pos;
+                if function_definitions
+                    .iter()
+                    .any(|defined| defined.func_name == new_def.func_name)
                 {
-                    // We have a struct definition
-                    parse_toplevel_struct_definition(
-                        global_declarations,
-                        tokens,
-                        filename,
-                        input,
-                        struct_name,
-                    )?;
-
-                    continue; // skip to the next iteration
+                    return Err(AppError {
+                        message: format!("関数 {} が重複して定義されています", new_def.func_name),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos,
+                    });
                 }
-            }
-        }
-
-        let new_def_or_decl =
-            parse_toplevel_definition(strlit_collector, global_declarations, tokens, filename, input)?;
-        match new_def_or_decl {
-            ToplevelDefOrDecl::FuncDef(new_def) => {
                 let (name, signature) = new_def.clone().into();
-                global_declarations
-                    .symbols
-                    .insert(name, SymbolDeclaration::Func(signature));
                 function_definitions.push(new_def);
+                (
+                    name,
+                    SymbolDeclaration::Func(signature, Linkage::External),
+                    pos,
+                )
             }
             ToplevelDefOrDecl::GVar(gvar) => {
-                global_declarations
-                    .symbols
-                    .insert(gvar.name, SymbolDeclaration::GVar(gvar.typ));
+                let decl = SymbolDeclaration::GVar(GlobalVarDeclaration {
+                    typ: gvar.typ.clone(),
+                    linkage: Linkage::External,
+                    is_defined: true,
+                    pos: gvar.pos,
+                });
+                let (name, pos) = (gvar.name.clone(), gvar.pos);
+                // `int x; int x = 3;` のように同じ変数が再び宣言されたら、初期化子を持つ方を残す
+                match global_variable_definitions
+                    .iter_mut()
+                    .find(|defined| defined.name == gvar.name)
+                {
+                    Some(defined)
+                        if defined.initializer.is_some() && gvar.initializer.is_some() =>
+                    {
+                        return Err(AppError {
+                            message: format!("グローバル変数 {name} が重複して定義されています"),
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos,
+                        });
+                    }
+                    Some(defined) if gvar.initializer.is_some() => *defined = gvar,
+                    Some(_) => {}
+                    None => global_variable_definitions.push(gvar),
+                }
+                (name, decl, pos)
             }
+            ToplevelDefOrDecl::GVarDecl { name, typ, pos } => (
+                name,
+                SymbolDeclaration::GVar(GlobalVarDeclaration {
+                    typ,
+                    linkage: Linkage::External,
+                    is_defined: false,
+                    pos,
+                }),
+                pos,
+            ),
             ToplevelDefOrDecl::FuncDecl {
                 func_name,
                 params,
+                is_variadic,
                 return_type,
-            } => {
-                global_declarations.symbols.insert(
-                    func_name,
-                    SymbolDeclaration::Func(FunctionSignature {
+                pos,
+            } => (
+                func_name,
+                SymbolDeclaration::Func(
+                    FunctionSignature {
                         params,
-                        pos: 0,
+                        is_variadic,
+                        pos,
                         return_type,
-                    }),
-                );
-            }
-        }
+                    },
+                    Linkage::External,
+                ),
+                pos,
+            ),
+            ToplevelDefOrDecl::TypeDeclaration => continue,
+        };
+        declare_global_symbol(
+            &mut context.global_declarations,
+            &name,
+            new_decl,
+            storage_class,
+        )
+        .map_err(|message| AppError {
+            message,
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos,
+        })?;
     }
-    Ok(function_definitions)
+
+    *global_declarations = context.global_declarations;
+    Ok((function_definitions, global_variable_definitions))
 }
@@ -2,91 +2,311 @@ use std::{collections::HashMap, iter::Peekable, slice::Iter};
 
 use crate::{
     apperror::AppError,
-    parse::toplevel::StructDefinition,
+    parse::{
+        context::Context,
+        expression::parse_constant_expr,
+        toplevel::{parse_struct_body, StructDefinition},
+    },
     token::{Tok, Token},
 };
 
+/// 型修飾子の集合
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Qualifiers {
+    pub is_const: bool,
+    pub is_volatile: bool,
+}
+
+impl Qualifiers {
+    pub const CONST: Self = Self {
+        is_const: true,
+        is_volatile: false,
+    };
+    pub const VOLATILE: Self = Self {
+        is_const: false,
+        is_volatile: true,
+    };
+
+    pub fn is_empty(self) -> bool {
+        !self.is_const && !self.is_volatile
+    }
+
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            is_const: self.is_const || other.is_const,
+            is_volatile: self.is_volatile || other.is_volatile,
+        }
+    }
+
+    /// `self` の修飾子が全て `other` にも付いているかどうか
+    pub fn is_subset_of(self, other: Self) -> bool {
+        (!self.is_const || other.is_const) && (!self.is_volatile || other.is_volatile)
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Type {
     Int,
+    /// `unsigned int`
+    UInt,
     Char,
+    /// `unsigned char`
+    UChar,
+    Bool,
+    Float,
+    Double,
     Ptr(Box<Type>),
     Arr(Box<Type>, i32),
-    Struct { struct_name: String },
+    /// `int m[][4]` の `[]` のような、大きさの分からない配列型。
+    /// 不完全型なので変数の型にはできないが、仮引数の型としてはポインタ型に読み替えられる
+    IncompleteArr(Box<Type>),
+    /// `int a[n]` のような可変長配列型。大きさ（バイト数）は実行時に計算され、
+    /// 二つ目の ID を持つ隠れたローカル変数に保存される
+    VarLenArr(Box<Type>, u64),
+    /// 共用体もこの型で表す
+    Struct {
+        struct_name: String,
+    },
     Void,
+    Func {
+        return_type: Box<Type>,
+        /// `int (*fp)()` のように仮引数の型が指定されていなければ `None`
+        params: Option<Vec<Type>>,
+        /// `int (*fp)(const char *, ...)` のように、仮引数の並びが `...` で終わっているなら true
+        is_variadic: bool,
+    },
+    /// `const int` のように修飾された型。修飾子が空の `Qualified` は作らず、`Qualified` を入れ子にもしない。
+    /// 式の値の型（`Expr::typ()`）には、一番外側の修飾子は付かない
+    Qualified(Qualifiers, Box<Type>),
 }
 
 impl Type {
     pub fn deref(&self) -> Option<Self> {
         match self {
-            Type::Int | Type::Char | Type::Struct { .. } | Type::Void => None,
-            Type::Ptr(x) | Type::Arr(x, _) => Some((**x).clone()),
+            Type::Int
+            | Type::UInt
+            | Type::Char
+            | Type::UChar
+            | Type::Bool
+            | Type::Float
+            | Type::Double
+            | Type::Struct { .. }
+            | Type::Void
+            | Type::Func { .. } => None,
+            Type::Ptr(x) | Type::Arr(x, _) | Type::IncompleteArr(x) | Type::VarLenArr(x, _) => {
+                Some((**x).clone())
+            }
+            Type::Qualified(_, t) => t.deref(),
+        }
+    }
+
+    /// 修飾子を付け加えた型。配列型を修飾すると、要素の型が修飾される
+    #[must_use]
+    pub fn qualified(self, qualifiers: Qualifiers) -> Self {
+        if qualifiers.is_empty() {
+            return self;
+        }
+        match self {
+            Type::Qualified(q, t) => Type::Qualified(q.union(qualifiers), t),
+            Type::Arr(t, len) => Type::Arr(Box::new(t.qualified(qualifiers)), len),
+            Type::IncompleteArr(t) => Type::IncompleteArr(Box::new(t.qualified(qualifiers))),
+            Type::VarLenArr(t, size_var) => {
+                Type::VarLenArr(Box::new(t.qualified(qualifiers)), size_var)
+            }
+            t => Type::Qualified(qualifiers, Box::new(t)),
         }
     }
 
+    /// 一番外側に付いている修飾子
+    pub fn qualifiers(&self) -> Qualifiers {
+        match self {
+            Type::Qualified(q, _) => *q,
+            _ => Qualifiers::default(),
+        }
+    }
+
+    /// 一番外側の修飾子を取り除いた型
+    pub fn unqualified(&self) -> &Self {
+        match self {
+            Type::Qualified(_, t) => t,
+            t => t,
+        }
+    }
+
+    /// 二つの型が互換かどうか。可変長配列型は、要素の型が互換な配列型と互換になる。
+    /// それ以外の型は、等しい場合に限り互換とみなす
+    pub fn is_compatible_with(&self, other: &Type) -> bool {
+        match (self, other) {
+            (
+                Type::VarLenArr(a, _),
+                Type::VarLenArr(b, _) | Type::Arr(b, _) | Type::IncompleteArr(b),
+            )
+            | (Type::Arr(a, _) | Type::IncompleteArr(a), Type::VarLenArr(b, _))
+            | (Type::Ptr(a), Type::Ptr(b)) => a.is_compatible_with(b),
+            (Type::Arr(a, n), Type::Arr(b, m)) => n == m && a.is_compatible_with(b),
+            (Type::Qualified(q1, a), Type::Qualified(q2, b)) => q1 == q2 && a.is_compatible_with(b),
+            _ => self == other,
+        }
+    }
+
+    /// 可変長配列型か、それを要素や指す先に含む型（可変修飾型）かどうか
+    pub fn is_variably_modified(&self) -> bool {
+        match self.unqualified() {
+            Type::VarLenArr(..) => true,
+            Type::Ptr(t) | Type::Arr(t, _) | Type::IncompleteArr(t) => t.is_variably_modified(),
+            _ => false,
+        }
+    }
+
+    pub fn is_floating(&self) -> bool {
+        matches!(self.unqualified(), Type::Float | Type::Double)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self.unqualified(),
+            Type::Int | Type::UInt | Type::Char | Type::UChar | Type::Bool
+        )
+    }
+
+    /// 符号なし整数型かどうか。`_Bool` も符号なし整数型に含まれる
+    pub fn is_unsigned(&self) -> bool {
+        matches!(self.unqualified(), Type::UInt | Type::UChar | Type::Bool)
+    }
+
+    pub fn is_arithmetic(&self) -> bool {
+        self.is_integer() || self.is_floating()
+    }
+
     pub fn sizeof_primitive(&self, msg: &str) -> i32 {
         match self {
-            Type::Int => 4,
-            Type::Char => 1,
+            Type::Int | Type::UInt => 4,
+            Type::Char | Type::UChar => 1,
+            Type::Bool => 1,
+            Type::Float => 4,
+            Type::Double => 8,
             Type::Void => 1,
+            Type::Func { .. } => 1,
             Type::Ptr(_) => 8,
             Type::Arr(t, len) => t
                 .sizeof_primitive(msg)
                 .checked_mul(*len)
                 .expect("型のサイズが u8 に収まりません"),
+            Type::IncompleteArr(_) => {
+                panic!("大きさの分からない配列型のサイズは求められません。 msg: {msg}")
+            }
+            Type::VarLenArr(..) => {
+                panic!("可変長配列型のサイズはコンパイル時には求められません。 msg: {msg}")
+            }
             Type::Struct { .. } => {
                 panic!("sizeof_primitive() は構造体に対しては定義されていません。 msg: {msg}")
             }
+            Type::Qualified(_, t) => t.sizeof_primitive(msg),
         }
     }
 
     pub fn sizeof(&self, struct_def_table: &HashMap<String, StructDefinition>) -> i32 {
         match self {
-            Type::Int => 4,
-            Type::Char => 1,
-            Type::Void => 1, // GNU extension
+            Type::Int | Type::UInt => 4,
+            Type::Char | Type::UChar => 1,
+            Type::Bool => 1,
+            Type::Float => 4,
+            Type::Double => 8,
+            Type::Void => 1,        // GNU extension
+            Type::Func { .. } => 1, // GNU extension
             Type::Ptr(_) => 8,
             Type::Arr(t, len) => t
                 .sizeof(struct_def_table)
                 .checked_mul(*len)
                 .expect("型のサイズが i32 に収まりません"),
+            Type::IncompleteArr(_) => panic!("大きさの分からない配列型のサイズは求められません"),
+            Type::VarLenArr(..) => panic!("可変長配列型のサイズはコンパイル時には求められません"),
             Type::Struct { struct_name } => struct_def_table.get(struct_name).map_or_else(
                 || {
                     panic!("構造体 {struct_name} の定義が見つかりません");
                 },
                 |s| s.size,
             ),
+            Type::Qualified(_, t) => t.sizeof(struct_def_table),
         }
     }
 
     pub fn alignof(&self, struct_def_table: &HashMap<String, StructDefinition>) -> i32 {
         match self {
-            Type::Int => 4,
-            Type::Char => 1,
-            Type::Void => 1, // GNU extension
+            Type::Int | Type::UInt => 4,
+            Type::Char | Type::UChar => 1,
+            Type::Bool => 1,
+            Type::Float => 4,
+            Type::Double => 8,
+            Type::Void => 1,        // GNU extension
+            Type::Func { .. } => 1, // GNU extension
             Type::Ptr(_) => 8,
-            Type::Arr(t, _) => t.alignof(struct_def_table),
+            Type::Arr(t, _) | Type::IncompleteArr(t) | Type::VarLenArr(t, _) => {
+                t.alignof(struct_def_table)
+            }
             Type::Struct { struct_name } => struct_def_table.get(struct_name).map_or_else(
                 || {
                     panic!("構造体 {struct_name} の定義が見つかりません");
                 },
                 |s| s.align,
             ),
+            Type::Qualified(_, t) => t.alignof(struct_def_table),
         }
     }
 }
 
 pub fn parse_type(
+    context: &mut Context,
     tokens: &mut Peekable<Iter<Token>>,
     filename: &str,
     input: &str,
 ) -> Result<Type, AppError> {
+    let leading_qualifiers = parse_qualifiers(tokens);
+    let typedef_type = match tokens.peek().unwrap() {
+        Token {
+            tok: Tok::Identifier(ident),
+            ..
+        } => context.resolve_typedef(ident),
+        _ => None,
+    };
     let mut typ = match tokens.peek().unwrap() {
         Token {
-            tok: Tok::Const, ..
+            tok: Tok::Identifier(_),
+            ..
+        } if typedef_type.is_some() => {
+            tokens.next().unwrap();
+            typedef_type.unwrap()
+        }
+        Token { tok: Tok::Enum, .. } => {
+            tokens.next().unwrap();
+            parse_enum_specifier(context, tokens, filename, input)?;
+            Type::Int // 列挙型は int と互換な型として扱う
+        }
+        Token {
+            tok: tok @ (Tok::Signed | Tok::Unsigned),
+            ..
         } => {
+            let is_unsigned = *tok == Tok::Unsigned;
             tokens.next().unwrap();
-            return parse_type(tokens, filename, input); // ignore const for now
+            // `unsigned` だけなら `unsigned int` とみなす
+            let is_char = match tokens.peek().unwrap().tok {
+                Tok::Int => {
+                    tokens.next().unwrap();
+                    false
+                }
+                Tok::Char => {
+                    tokens.next().unwrap();
+                    true
+                }
+                _ => false,
+            };
+            match (is_unsigned, is_char) {
+                (false, false) => Type::Int,
+                (false, true) => Type::Char,
+                (true, false) => Type::UInt,
+                (true, true) => Type::UChar,
+            }
         }
         Token { tok: Tok::Int, .. } => {
             tokens.next().unwrap();
@@ -100,17 +320,102 @@ pub fn parse_type(
             tokens.next().unwrap();
             Type::Void
         }
+        Token { tok: Tok::Bool, .. } => {
+            tokens.next().unwrap();
+            Type::Bool
+        }
+        Token {
+            tok: Tok::Float, ..
+        } => {
+            tokens.next().unwrap();
+            Type::Float
+        }
+        Token {
+            tok: Tok::Double, ..
+        } => {
+            tokens.next().unwrap();
+            Type::Double
+        }
         Token {
-            tok: Tok::Struct, ..
+            tok: tok @ (Tok::Struct | Tok::Union),
+            ..
         } => {
+            let is_union = *tok == Tok::Union;
             tokens.next().unwrap();
             match tokens.next() {
                 Some(Token {
-                    tok: Tok::Identifier(struct_name),
-                    ..
-                }) => Type::Struct {
-                    struct_name: struct_name.clone(),
-                },
+                    tok: Tok::開き波括弧,
+                    pos,
+                }) => {
+                    // タグを持たない構造体には、識別子としてありえない名前を付けて登録する
+                    let struct_name =
+                        format!("(無名{})@{pos}", if is_union { "共用体" } else { "構造体" });
+                    parse_struct_body(context, tokens, filename, input, &struct_name, is_union)?;
+                    Type::Struct { struct_name }
+                }
+                Some(Token {
+                    tok: Tok::Identifier(tag),
+                    pos,
+                }) => {
+                    // `struct Foo {` と `struct Foo;` は現在のスコープに新しくタグを宣言し、
+                    // それ以外は外側のスコープまで遡ってタグを探し、見つからなければ現在のスコープに宣言する
+                    let struct_name = match tokens.peek().unwrap().tok {
+                        Tok::開き波括弧 | Tok::Semicolon => {
+                            context.declare_struct_tag(tag, *pos)
+                        }
+                        _ => context.resolve_struct_tag(tag, *pos),
+                    };
+                    // 構造体と共用体のタグは同じ名前空間を共有する
+                    if context.global_declarations.enum_tags.contains(&struct_name) {
+                        return Err(AppError {
+                            message: format!("{tag} は列挙型として定義されています"),
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos: *pos,
+                        });
+                    }
+                    if let Some(def) = context.global_declarations.struct_names.get(&struct_name) {
+                        if def.is_union != is_union {
+                            return Err(AppError {
+                                message: format!(
+                                    "{tag} は{}として定義されています",
+                                    if def.is_union {
+                                        "共用体"
+                                    } else {
+                                        "構造体"
+                                    }
+                                ),
+                                input: input.to_string(),
+                                filename: filename.to_string(),
+                                pos: *pos,
+                            });
+                        }
+                    }
+                    if tokens.peek().unwrap().tok == Tok::開き波括弧 {
+                        if context
+                            .global_declarations
+                            .struct_names
+                            .contains_key(&struct_name)
+                        {
+                            return Err(AppError {
+                                message: format!("{tag} が再定義されています"),
+                                input: input.to_string(),
+                                filename: filename.to_string(),
+                                pos: *pos,
+                            });
+                        }
+                        tokens.next();
+                        parse_struct_body(
+                            context,
+                            tokens,
+                            filename,
+                            input,
+                            &struct_name,
+                            is_union,
+                        )?;
+                    }
+                    Type::Struct { struct_name }
+                }
                 Some(Token { pos, .. }) => {
                     return Err(AppError {
                         message: "構造体名がありません".to_string(),
@@ -138,21 +443,192 @@ pub fn parse_type(
             })
         }
     };
+    // `int const` のように、修飾子は基本型の後にも書ける
+    typ = typ.qualified(leading_qualifiers.union(parse_qualifiers(tokens)));
 
     loop {
         match tokens.peek().unwrap() {
             Token {
                 tok: Tok::Asterisk, ..
             } => {
-                typ = Type::Ptr(Box::new(typ));
                 tokens.next().unwrap();
+                typ = Type::Ptr(Box::new(typ)).qualified(parse_qualifiers(tokens));
             }
+            _ => return Ok(typ),
+        }
+    }
+}
+
+/// 並んでいる `const` と `volatile` を読む
+pub fn parse_qualifiers(tokens: &mut Peekable<Iter<Token>>) -> Qualifiers {
+    let mut qualifiers = Qualifiers::default();
+    loop {
+        match tokens.peek().unwrap().tok {
+            Tok::Const => qualifiers.is_const = true,
+            Tok::Volatile => qualifiers.is_volatile = true,
+            _ => return qualifiers,
+        }
+        tokens.next();
+    }
+}
+
+/// 型名の始まりとなりうるトークンかどうか
+pub fn is_type_name_start(context: &Context, tok: &Tok) -> bool {
+    match tok {
+        Tok::Int
+        | Tok::Char
+        | Tok::Void
+        | Tok::Bool
+        | Tok::Float
+        | Tok::Double
+        | Tok::Struct
+        | Tok::Union
+        | Tok::Enum
+        | Tok::Signed
+        | Tok::Unsigned
+        | Tok::Const
+        | Tok::Volatile => true,
+        Tok::Identifier(ident) => context.resolve_typedef(ident).is_some(),
+        _ => false,
+    }
+}
+
+/// `enum` の後の、タグと列挙子の並び `Tag { A, B = 3, C, }` をパースし、列挙子を context に登録する
+fn parse_enum_specifier(
+    context: &mut Context,
+    tokens: &mut Peekable<Iter<Token>>,
+    filename: &str,
+    input: &str,
+) -> Result<(), AppError> {
+    let enum_pos = tokens.peek().unwrap().pos;
+    let tag = if let Token {
+        tok: Tok::Identifier(tag),
+        pos,
+    } = tokens.peek().unwrap()
+    {
+        tokens.next();
+        Some((tag, *pos))
+    } else {
+        None
+    };
+
+    // 列挙型のタグは、構造体・共用体のタグと同じ名前空間を共有する
+    if let Some((tag, pos)) = tag {
+        let is_definition = tokens.peek().unwrap().tok == Tok::開き波括弧;
+        let enum_name = if is_definition {
+            context.declare_struct_tag(tag, pos)
+        } else {
+            context.resolve_struct_tag(tag, pos)
+        };
+        let error = if let Some(def) = context.global_declarations.struct_names.get(&enum_name) {
+            Some(format!(
+                "{tag} は{}として定義されています",
+                if def.is_union {
+                    "共用体"
+                } else {
+                    "構造体"
+                }
+            ))
+        } else if is_definition && !context.global_declarations.enum_tags.insert(enum_name) {
+            Some(format!("{tag} が再定義されています"))
+        } else {
+            None
+        };
+        if let Some(message) = error {
+            return Err(AppError {
+                message,
+                input: input.to_string(),
+                filename: filename.to_string(),
+                pos,
+            });
+        }
+    }
+
+    if tokens.peek().unwrap().tok != Tok::開き波括弧 {
+        if tag.is_some() {
+            return Ok(());
+        }
+        return Err(AppError {
+            message: "enum の後にタグも列挙子の並びもありません".to_string(),
+            input: input.to_string(),
+            filename: filename.to_string(),
+            pos: enum_pos,
+        });
+    }
+    tokens.next(); // consume `{`
+
+    // 次の列挙子の値。i32 の最大値の次は存在しないので None
+    let mut next_value = Some(0);
+    let mut enumerator_count = 0;
+    loop {
+        match tokens.next().unwrap() {
             Token {
-                tok: Tok::Const, ..
+                tok: Tok::閉じ波括弧,
+                pos,
             } => {
-                tokens.next().unwrap(); // ignore const for now
+                if enumerator_count == 0 {
+                    return Err(AppError {
+                        message: "列挙子の並びが空です".to_string(),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *pos,
+                    });
+                }
+                return Ok(());
+            }
+            Token {
+                tok: Tok::Identifier(name),
+                pos,
+            } => {
+                let value = if tokens.peek().unwrap().tok == Tok::Assign {
+                    tokens.next();
+                    parse_constant_expr(context, tokens, filename, input)?
+                } else {
+                    next_value.ok_or(AppError {
+                        message: format!("列挙子 {name} の値が int に収まりません"),
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *pos,
+                    })?
+                };
+                context
+                    .insert_enum_const(name.clone(), value)
+                    .map_err(|message| AppError {
+                        message,
+                        input: input.to_string(),
+                        filename: filename.to_string(),
+                        pos: *pos,
+                    })?;
+                next_value = value.checked_add(1);
+                enumerator_count += 1;
+
+                match tokens.next().unwrap() {
+                    Token {
+                        tok: Tok::Comma, ..
+                    } => {}
+                    Token {
+                        tok: Tok::閉じ波括弧,
+                        ..
+                    } => return Ok(()),
+                    Token { pos, .. } => {
+                        return Err(AppError {
+                            message: "列挙子の後にカンマか閉じ波括弧が期待されていました"
+                                .to_string(),
+                            input: input.to_string(),
+                            filename: filename.to_string(),
+                            pos: *pos,
+                        })
+                    }
+                }
+            }
+            Token { pos, .. } => {
+                return Err(AppError {
+                    message: "列挙子の名前が期待されていました".to_string(),
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos: *pos,
+                })
             }
-            _ => return Ok(typ),
         }
     }
 }
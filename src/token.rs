@@ -1,6 +1,10 @@
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Tok {
     Num(i32),
+    /// `1.5f` のような float 型の定数。Eq を実装するためにビット列で持つ
+    FloatNum(u32),
+    /// `1.5` のような double 型の定数。Eq を実装するためにビット列で持つ
+    DoubleNum(u64),
     Add,
     Sub,
     Asterisk,
@@ -23,10 +27,22 @@ pub enum Tok {
     Else,
     While,
     For,
+    Do,
+    Break,
+    Continue,
+    Goto,
+    Switch,
+    Case,
+    Default,
     開き波括弧,
     閉じ波括弧,
     Comma,
     Ampersand,
+    VerticalBar,
+    Caret,
+    Tilde,
+    LeftShift,
+    RightShift,
     Int,
     Char,
     Sizeof,
@@ -36,17 +52,42 @@ pub enum Tok {
     Decrement,
     AddAssign,
     SubAssign,
+    MulAssign,
+    DivAssign,
+    RemainderAssign,
+    AndAssign,
+    OrAssign,
+    XorAssign,
+    LeftShiftAssign,
+    RightShiftAssign,
     LogicalNot,
     Alignof,
     Struct,
+    Union,
+    Typedef,
+    Static,
+    Extern,
     Arrow,
     Dot,
+    /// `...`
+    Ellipsis,
     LogicalAnd,
     LogicalOr,
     Void,
     Const,
+    Volatile,
     BuiltinPopulateArgcArgv,
     Percent,
+    Enum,
+    Colon,
+    Question,
+    Signed,
+    Unsigned,
+    Float,
+    Double,
+    Bool,
+    True,
+    False,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
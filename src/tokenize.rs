@@ -98,13 +98,33 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
                     "else" => Tok::Else,
                     "while" => Tok::While,
                     "for" => Tok::For,
+                    "do" => Tok::Do,
+                    "break" => Tok::Break,
+                    "continue" => Tok::Continue,
+                    "goto" => Tok::Goto,
+                    "switch" => Tok::Switch,
+                    "case" => Tok::Case,
+                    "default" => Tok::Default,
                     "int" => Tok::Int,
                     "char" => Tok::Char,
                     "sizeof" => Tok::Sizeof,
                     "_Alignof" => Tok::Alignof,
                     "struct" => Tok::Struct,
+                    "union" => Tok::Union,
+                    "typedef" => Tok::Typedef,
+                    "static" => Tok::Static,
+                    "extern" => Tok::Extern,
+                    "enum" => Tok::Enum,
                     "void" => Tok::Void,
                     "const" => Tok::Const,
+                    "volatile" => Tok::Volatile,
+                    "signed" => Tok::Signed,
+                    "unsigned" => Tok::Unsigned,
+                    "float" => Tok::Float,
+                    "double" => Tok::Double,
+                    "_Bool" | "bool" => Tok::Bool,
+                    "true" => Tok::True,
+                    "false" => Tok::False,
                     "__builtin_populate_argc_argv" => Tok::BuiltinPopulateArgcArgv,
                     _ => Tok::Identifier(ident),
                 };
@@ -148,8 +168,36 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
             }
             '%' => {
                 iter.next();
+                match iter.peek() {
+                    Some(&(pos, '=')) => {
+                        iter.next();
+                        ans.push(Token {
+                            tok: Tok::RemainderAssign,
+                            pos,
+                        });
+                    }
+                    _ => ans.push(Token {
+                        tok: Tok::Percent,
+                        pos,
+                    }),
+                }
+            }
+            '.' if iter.clone().nth(1).is_some_and(|(_, c)| c.is_ascii_digit()) => {
+                // `.5` のような浮動小数点定数
+                let tok = parse_num(&mut iter).map_err(|message| AppError {
+                    message,
+                    input: input.to_string(),
+                    filename: filename.to_string(),
+                    pos,
+                })?;
+                ans.push(Token { tok, pos });
+            }
+            '.' if iter.clone().nth(1).is_some_and(|(_, c)| c == '.')
+                && iter.clone().nth(2).is_some_and(|(_, c)| c == '.') =>
+            {
+                iter.nth(2);
                 ans.push(Token {
-                    tok: Tok::Percent,
+                    tok: Tok::Ellipsis,
                     pos,
                 });
             }
@@ -210,10 +258,19 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
             }
             '*' => {
                 iter.next();
-                ans.push(Token {
-                    tok: Tok::Asterisk,
-                    pos,
-                });
+                match iter.peek() {
+                    Some(&(pos, '=')) => {
+                        iter.next();
+                        ans.push(Token {
+                            tok: Tok::MulAssign,
+                            pos,
+                        });
+                    }
+                    _ => ans.push(Token {
+                        tok: Tok::Asterisk,
+                        pos,
+                    }),
+                }
             }
             '/' => {
                 iter.next();
@@ -254,6 +311,13 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
                             }
                         }
                     }
+                    Some(&(pos, '=')) => {
+                        iter.next();
+                        ans.push(Token {
+                            tok: Tok::DivAssign,
+                            pos,
+                        });
+                    }
                     _ => {
                         ans.push(Token { tok: Tok::Div, pos });
                     }
@@ -319,6 +383,22 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
                             pos,
                         });
                     }
+                    Some(&(pos, '<')) => {
+                        iter.next();
+                        match iter.peek() {
+                            Some(&(pos, '=')) => {
+                                iter.next();
+                                ans.push(Token {
+                                    tok: Tok::LeftShiftAssign,
+                                    pos,
+                                });
+                            }
+                            _ => ans.push(Token {
+                                tok: Tok::LeftShift,
+                                pos,
+                            }),
+                        }
+                    }
                     _ => ans.push(Token {
                         tok: Tok::LessThan,
                         pos,
@@ -335,6 +415,22 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
                             pos,
                         });
                     }
+                    Some(&(pos, '>')) => {
+                        iter.next();
+                        match iter.peek() {
+                            Some(&(pos, '=')) => {
+                                iter.next();
+                                ans.push(Token {
+                                    tok: Tok::RightShiftAssign,
+                                    pos,
+                                });
+                            }
+                            _ => ans.push(Token {
+                                tok: Tok::RightShift,
+                                pos,
+                            }),
+                        }
+                    }
                     _ => ans.push(Token {
                         tok: Tok::GreaterThan,
                         pos,
@@ -414,12 +510,12 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
                 let num = parse_num(&mut iter);
 
                 let new_tok = Token {
-                    tok: Tok::Num(num.map_err(|message| AppError {
+                    tok: num.map_err(|message| AppError {
                         message,
                         input: input.to_string(),
                         filename: filename.to_string(),
                         pos,
-                    })?),
+                    })?,
                     pos,
                 };
                 ans.push(new_tok);
@@ -431,6 +527,20 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
                     pos,
                 });
             }
+            ':' => {
+                iter.next();
+                ans.push(Token {
+                    tok: Tok::Colon,
+                    pos,
+                });
+            }
+            '?' => {
+                iter.next();
+                ans.push(Token {
+                    tok: Tok::Question,
+                    pos,
+                });
+            }
             '&' => {
                 iter.next();
                 match iter.peek() {
@@ -441,6 +551,13 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
                             pos,
                         });
                     }
+                    Some(&(pos, '=')) => {
+                        iter.next();
+                        ans.push(Token {
+                            tok: Tok::AndAssign,
+                            pos,
+                        });
+                    }
                     _ => ans.push(Token {
                         tok: Tok::Ampersand,
                         pos,
@@ -457,16 +574,42 @@ pub fn tokenize(input: &str, filename: &str) -> Result<Vec<Token>, AppError> {
                             pos,
                         });
                     }
-                    _ => {
-                        return Err(AppError {
-                            message: "bit OR is not yet implemented".to_string(),
-                            input: input.to_string(),
-                            filename: filename.to_string(),
+                    Some(&(pos, '=')) => {
+                        iter.next();
+                        ans.push(Token {
+                            tok: Tok::OrAssign,
+                            pos,
+                        });
+                    }
+                    _ => ans.push(Token {
+                        tok: Tok::VerticalBar,
+                        pos,
+                    }),
+                }
+            }
+            '^' => {
+                iter.next();
+                match iter.peek() {
+                    Some(&(pos, '=')) => {
+                        iter.next();
+                        ans.push(Token {
+                            tok: Tok::XorAssign,
                             pos,
-                        })
+                        });
                     }
+                    _ => ans.push(Token {
+                        tok: Tok::Caret,
+                        pos,
+                    }),
                 }
             }
+            '~' => {
+                iter.next();
+                ans.push(Token {
+                    tok: Tok::Tilde,
+                    pos,
+                });
+            }
             c => {
                 return Err(AppError {
                     message: format!(
@@ -509,19 +652,68 @@ fn expect_end_of_char_lit(
 }
 
 fn parse_num(
-    iter: &mut std::iter::Peekable<impl Iterator<Item = (usize, char)>>,
-) -> Result<i32, String> {
+    iter: &mut std::iter::Peekable<impl Iterator<Item = (usize, char)> + Clone>,
+) -> Result<Tok, String> {
     let mut s = String::new();
 
-    while let Some(&(_, c)) = iter.peek() {
-        if c.is_ascii_digit() {
-            s.push(c);
-            iter.next();
-        } else {
-            break;
+    fn push_digits(
+        s: &mut String,
+        iter: &mut std::iter::Peekable<impl Iterator<Item = (usize, char)>>,
+    ) {
+        while let Some(&(_, c)) = iter.peek() {
+            if c.is_ascii_digit() {
+                s.push(c);
+                iter.next();
+            } else {
+                break;
+            }
         }
     }
 
-    s.parse::<i32>()
-        .map_err(|_| "入力が i32 に収まりません".to_string())
+    push_digits(&mut s, iter);
+
+    // 小数点か指数部があれば浮動小数点定数
+    let mut is_floating = false;
+    if let Some(&(_, '.')) = iter.peek() {
+        is_floating = true;
+        s.push('.');
+        iter.next();
+        push_digits(&mut s, iter);
+    }
+    if let Some(&(_, 'e' | 'E')) = iter.peek() {
+        let mut lookahead = iter.clone();
+        lookahead.next();
+        let sign = match lookahead.peek() {
+            Some(&(_, c @ ('+' | '-'))) => {
+                lookahead.next();
+                Some(c)
+            }
+            _ => None,
+        };
+        if lookahead.peek().is_some_and(|(_, c)| c.is_ascii_digit()) {
+            is_floating = true;
+            *iter = lookahead;
+            s.push('e');
+            s.extend(sign);
+            push_digits(&mut s, iter);
+        }
+    }
+
+    if !is_floating {
+        return s
+            .parse::<i32>()
+            .map(Tok::Num)
+            .map_err(|_| "入力が i32 に収まりません".to_string());
+    }
+
+    if let Some(&(_, 'f' | 'F')) = iter.peek() {
+        iter.next();
+        return s
+            .parse::<f32>()
+            .map(|val| Tok::FloatNum(val.to_bits()))
+            .map_err(|_| format!("浮動小数点定数 {s} を解釈できません"));
+    }
+    s.parse::<f64>()
+        .map(|val| Tok::DoubleNum(val.to_bits()))
+        .map_err(|_| format!("浮動小数点定数 {s} を解釈できません"))
 }
@@ -46,6 +46,19 @@ pub fn rcxへとポップ() -> [u8; 1] {
     [0x59]
 }
 
+/// movsxd rax, dword [rcx + rax * 4]
+pub fn rcxにraxの4倍を足した位置の4バイトをraxに符号拡張して読み込む() -> [u8; 4] {
+    [0x48, 0x63, 0x04, 0x81]
+}
+
+pub fn raxにrcxを足す() -> [u8; 3] {
+    [0x48, 0x01, 0xc8]
+}
+
+pub fn jmp_rax() -> [u8; 2] {
+    [0xff, 0xe0]
+}
+
 pub fn r8へとポップ() -> [u8; 2] {
     [0x41, 0x58]
 }
@@ -74,6 +87,26 @@ pub fn edx_eaxをediで割る_商はeaxに_余りはedxに() -> [u8; 2] {
     [0xf7, 0xff]
 }
 
+pub fn edx_eaxをecxで割る_商はeaxに_余りはedxに() -> [u8; 2] {
+    [0xf7, 0xf9]
+}
+
+pub fn edxをゼロクリア() -> [u8; 2] {
+    [0x31, 0xd2]
+}
+
+pub fn edx_eaxをediで符号なしで割る_商はeaxに_余りはedxに() -> [u8; 2] {
+    [0xf7, 0xf7]
+}
+
+pub fn edx_eaxをecxで符号なしで割る_商はeaxに_余りはedxに() -> [u8; 2] {
+    [0xf7, 0xf1]
+}
+
+pub fn edxをediにコピー() -> [u8; 2] {
+    [0x89, 0xd7]
+}
+
 pub fn raxをプッシュ() -> [u8; 1] {
     [0x50]
 }
@@ -102,6 +135,14 @@ pub fn フラグを読んで以下であるかどうかをalにセット() -> [u
     [0x0f, 0x9e, 0xc0]
 }
 
+pub fn フラグを読んで符号なしで未満であるかどうかをalにセット() -> [u8; 3] {
+    [0x0f, 0x92, 0xc0]
+}
+
+pub fn フラグを読んで符号なしで以下であるかどうかをalにセット() -> [u8; 3] {
+    [0x0f, 0x96, 0xc0]
+}
+
 pub fn alをゼロ拡張してediにセット() -> [u8; 3] {
     [0x0f, 0xb6, 0xf8]
 }
@@ -110,10 +151,18 @@ pub fn rdiを間接参照() -> [u8; 3] {
     [0x48, 0x8b, 0x3f]
 }
 
+pub fn ediを間接参照() -> [u8; 2] {
+    [0x8b, 0x3f]
+}
+
 pub fn rdiをmovzxで間接参照() -> [u8; 4] {
     [0x48, 0x0f, 0xb6, 0x3f]
 }
 
+pub fn rdiをmovsxで間接参照() -> [u8; 4] {
+    [0x48, 0x0f, 0xbe, 0x3f]
+}
+
 pub fn raxが指す位置にrdiを代入() -> [u8; 3] {
     [0x48, 0x89, 0x38]
 }
@@ -142,6 +191,22 @@ pub fn call_rax() -> [u8; 2] {
     [0xff, 0xd0]
 }
 
+pub fn call_r11() -> [u8; 3] {
+    [0x41, 0xff, 0xd3]
+}
+
+pub fn raxをr11にコピー() -> [u8; 3] {
+    [0x49, 0x89, 0xc3]
+}
+
+pub fn rsiをrdiにコピー() -> [u8; 3] {
+    [0x48, 0x89, 0xf7]
+}
+
+pub fn rsiにraxを足す() -> [u8; 3] {
+    [0x48, 0x01, 0xc6]
+}
+
 pub fn syscall() -> [u8; 2] {
     [0x0f, 0x05]
 }
@@ -174,6 +239,114 @@ pub fn dilをeaxに符号拡張してmov() -> [u8; 4] {
     [0x40, 0x0f, 0xbe, 0xc7]
 }
 
+pub fn dilをeaxにゼロ拡張してmov() -> [u8; 4] {
+    [0x40, 0x0f, 0xb6, 0xc7]
+}
+
 pub fn leave_ret() -> [u8; 2] {
     [0xc9, 0xc3]
-}
\ No newline at end of file
+}
+pub fn dilをediに符号拡張してmov() -> [u8; 4] {
+    [0x40, 0x0f, 0xbe, 0xff]
+}
+
+pub fn ediをrdiに符号拡張してmov() -> [u8; 3] {
+    [0x48, 0x63, 0xff]
+}
+
+pub fn dilをediにゼロ拡張してmov() -> [u8; 4] {
+    [0x40, 0x0f, 0xb6, 0xff]
+}
+
+/// mov edi, edi。32 ビットのレジスタへの書き込みは、上位 32 ビットを 0 にする
+pub fn ediをrdiにゼロ拡張してmov() -> [u8; 2] {
+    [0x89, 0xff]
+}
+
+pub fn rdiをrsiにコピー() -> [u8; 3] {
+    [0x48, 0x89, 0xfe]
+}
+
+pub fn rep_movsb() -> [u8; 2] {
+    [0xf3, 0xa4]
+}
+
+pub fn rep_stosb() -> [u8; 2] {
+    [0xf3, 0xaa]
+}
+
+pub fn rspをraxにコピー() -> [u8; 3] {
+    [0x48, 0x89, 0xe0]
+}
+
+pub fn ediをesiにコピー() -> [u8; 2] {
+    [0x89, 0xfe]
+}
+
+pub fn ediにesiをorする() -> [u8; 2] {
+    [0x09, 0xf7]
+}
+
+pub fn ediとeaxのandを取る() -> [u8; 2] {
+    [0x21, 0xc7]
+}
+
+pub fn ediにeaxをorする() -> [u8; 2] {
+    [0x09, 0xc7]
+}
+
+pub fn ediとeaxのxorを取る() -> [u8; 2] {
+    [0x31, 0xc7]
+}
+
+pub fn eaxをecxにコピー() -> [u8; 2] {
+    [0x89, 0xc1]
+}
+
+pub fn ediをclだけ左シフト() -> [u8; 2] {
+    [0xd3, 0xe7]
+}
+
+pub fn ediをclだけ算術右シフト() -> [u8; 2] {
+    [0xd3, 0xff]
+}
+
+pub fn ediをclだけ論理右シフト() -> [u8; 2] {
+    [0xd3, 0xef]
+}
+
+pub fn rdiをxmm0にコピー() -> [u8; 5] {
+    [0x66, 0x48, 0x0f, 0x6e, 0xc7]
+}
+
+pub fn rdiをxmm1にコピー() -> [u8; 5] {
+    [0x66, 0x48, 0x0f, 0x6e, 0xcf]
+}
+
+pub fn raxをxmm0にコピー() -> [u8; 5] {
+    [0x66, 0x48, 0x0f, 0x6e, 0xc0]
+}
+
+pub fn raxをxmm1にコピー() -> [u8; 5] {
+    [0x66, 0x48, 0x0f, 0x6e, 0xc8]
+}
+
+pub fn xmm0をrdiにコピー() -> [u8; 5] {
+    [0x66, 0x48, 0x0f, 0x7e, 0xc7]
+}
+
+pub fn rspをrsiにコピー() -> [u8; 3] {
+    [0x48, 0x89, 0xe6]
+}
+
+pub fn rspをrdiにコピー() -> [u8; 3] {
+    [0x48, 0x89, 0xe7]
+}
+
+pub fn raxをrspにコピー() -> [u8; 3] {
+    [0x48, 0x89, 0xc4]
+}
+
+pub fn rspからrdiを引く() -> [u8; 3] {
+    [0x48, 0x29, 0xfc]
+}
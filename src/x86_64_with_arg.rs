@@ -82,11 +82,59 @@ pub fn jmp(n: i32) -> Buf {
 
     /*if n >= i8::MIN as i32 && n <= i8::MAX as i32 {
         _jmp_i8(n as i8)
-    } else*/ {
+    } else*/
+    {
         jmp_i32(n)
     }
 }
 
+/// 長さ 0 のラベル。`ラベルへjmp` などの飛び先になる
+pub fn ラベル(name: &str) -> Buf {
+    Buf::Label {
+        name: name.to_string(),
+    }
+}
+
+/// ラベル `name` への jmp。オフセットは、関数全体を生成し終えてから `Buf::resolve_labels` で埋める
+pub fn ラベルへjmp(name: &str) -> Buf {
+    Buf::JumpToLabel {
+        name: name.to_string(),
+        opcode: vec![0xe9],
+    }
+}
+
+/// ラベル `name` への je
+pub fn ラベルへje(name: &str) -> Buf {
+    Buf::JumpToLabel {
+        name: name.to_string(),
+        opcode: vec![0x0f, 0x84],
+    }
+}
+
+/// 符号なしの比較で大きかったときに、ラベル `name` へ飛ぶ ja
+pub fn ラベルへja(name: &str) -> Buf {
+    Buf::JumpToLabel {
+        name: name.to_string(),
+        opcode: vec![0x0f, 0x87],
+    }
+}
+
+/// rip 相対で、ラベル `name` のアドレスを rcx に入れる lea
+pub fn ラベルのアドレスをrcxに代入(name: &str) -> Buf {
+    Buf::JumpToLabel {
+        name: name.to_string(),
+        opcode: vec![0x48, 0x8d, 0x0d],
+    }
+}
+
+/// ジャンプテーブルの要素。ラベル `table` からラベル `name` へのオフセット
+pub fn ジャンプテーブルの要素(name: &str, table: &str) -> Buf {
+    Buf::LabelOffset {
+        name: name.to_string(),
+        base: table.to_string(),
+    }
+}
+
 pub fn je(n: i32) -> Buf {
     fn je_i8(n: i8) -> Buf {
         Buf::from([0x74, n.to_le_bytes()[0]])
@@ -119,7 +167,6 @@ pub fn ebpに即値をセット(n: u32) -> [u8; 5] {
     [0xbd, buf[0], buf[1], buf[2], buf[3]]
 }
 
-
 pub fn edxに即値をセット(n: u32) -> [u8; 5] {
     let buf = n.to_le_bytes();
     [0xba, buf[0], buf[1], buf[2], buf[3]]
@@ -291,6 +338,23 @@ pub fn rbpにoffsetを足した位置にecxを代入(offset: i32) -> Buf {
     }
 }
 
+pub fn rbpにoffsetを足した位置にraxを代入(offset: i32) -> Buf {
+    fn rbpにoffsetを足した位置にraxを代入_i8(offset: i8) -> Buf {
+        Buf::from([0x48, 0x89, 0x45, offset.to_le_bytes()[0]])
+    }
+
+    fn rbpにoffsetを足した位置にraxを代入_i32(offset: i32) -> Buf {
+        let buf = offset.to_le_bytes();
+        Buf::from([0x48, 0x89, 0x85, buf[0], buf[1], buf[2], buf[3]])
+    }
+
+    if offset >= i8::MIN as i32 && offset <= i8::MAX as i32 {
+        rbpにoffsetを足した位置にraxを代入_i8(offset as i8)
+    } else {
+        rbpにoffsetを足した位置にraxを代入_i32(offset)
+    }
+}
+
 pub fn rbpにoffsetを足した位置にr8dを代入(offset: i32) -> Buf {
     fn rbpにoffsetを足した位置にr8dを代入_i8(offset: i8) -> Buf {
         Buf::from([0x44, 0x89, 0x45, offset.to_le_bytes()[0]])
@@ -395,12 +459,12 @@ pub fn rbpにoffsetを足した位置にrcxを代入(offset: i32) -> Buf {
 
 pub fn rbpにoffsetを足した位置にr8を代入(offset: i32) -> Buf {
     fn rbpにoffsetを足した位置にr8を代入_i8(offset: i8) -> Buf {
-        Buf::from([0x48, 0x89, 0x45, offset.to_le_bytes()[0]])
+        Buf::from([0x4c, 0x89, 0x45, offset.to_le_bytes()[0]])
     }
 
     fn rbpにoffsetを足した位置にr8を代入_i32(offset: i32) -> Buf {
         let buf = offset.to_le_bytes();
-        Buf::from([0x48, 0x89, 0x85, buf[0], buf[1], buf[2], buf[3]])
+        Buf::from([0x4c, 0x89, 0x85, buf[0], buf[1], buf[2], buf[3]])
     }
 
     if offset >= i8::MIN as i32 && offset <= i8::MAX as i32 {
@@ -412,12 +476,12 @@ pub fn rbpにoffsetを足した位置にr8を代入(offset: i32) -> Buf {
 
 pub fn rbpにoffsetを足した位置にr9を代入(offset: i32) -> Buf {
     fn rbpにoffsetを足した位置にr9を代入_i8(offset: i8) -> Buf {
-        Buf::from([0x48, 0x89, 0x4d, offset.to_le_bytes()[0]])
+        Buf::from([0x4c, 0x89, 0x4d, offset.to_le_bytes()[0]])
     }
 
     fn rbpにoffsetを足した位置にr9を代入_i32(offset: i32) -> Buf {
         let buf = offset.to_le_bytes();
-        Buf::from([0x48, 0x89, 0x8d, buf[0], buf[1], buf[2], buf[3]])
+        Buf::from([0x4c, 0x89, 0x8d, buf[0], buf[1], buf[2], buf[3]])
     }
 
     if offset >= i8::MIN as i32 && offset <= i8::MAX as i32 {
@@ -443,6 +507,22 @@ pub fn rbpにoffsetを足したアドレスをrdiに代入(offset: i32) -> Buf {
     }
 }
 
+pub fn rbpにoffsetを足したアドレスをraxに代入(offset: i32) -> Buf {
+    fn rbpにoffsetを足したアドレスをraxに代入_i8(offset: i8) -> Buf {
+        Buf::from([0x48, 0x8d, 0x45, offset.to_le_bytes()[0]])
+    }
+
+    fn rbpにoffsetを足したアドレスをraxに代入_i32(offset: i32) -> Buf {
+        let buf = offset.to_le_bytes();
+        Buf::from([0x48, 0x8d, 0x85, buf[0], buf[1], buf[2], buf[3]])
+    }
+    if offset >= i8::MIN as i32 && offset <= i8::MAX as i32 {
+        rbpにoffsetを足したアドレスをraxに代入_i8(offset as i8)
+    } else {
+        rbpにoffsetを足したアドレスをraxに代入_i32(offset)
+    }
+}
+
 pub fn rbpにoffsetを足したアドレスをrsiに代入(offset: i32) -> Buf {
     fn rbpにoffsetを足したアドレスをrsiに代入_i8(offset: i8) -> Buf {
         Buf::from([0x48, 0x8d, 0x75, offset.to_le_bytes()[0]])
@@ -492,4 +572,236 @@ pub fn rspにoffsetを足したアドレスをrdiに代入(offset: i32) -> Buf {
     } else {
         rspにoffsetを足したアドレスをrdiに代入_i32(offset)
     }
-}
\ No newline at end of file
+}
+pub fn ecxに即値をセット(n: u32) -> [u8; 5] {
+    let buf = n.to_le_bytes();
+    [0xb9, buf[0], buf[1], buf[2], buf[3]]
+}
+
+pub fn rdiにoffsetを足した位置の値をプッシュ(offset: i8) -> [u8; 3] {
+    [0xff, 0x77, offset.to_le_bytes()[0]]
+}
+
+pub fn rbpにoffsetを足した位置の値をraxに読み込む(offset: i32) -> Buf {
+    fn rbpにoffsetを足した位置の値をraxに読み込む_i8(offset: i8) -> Buf {
+        Buf::from([0x48, 0x8b, 0x45, offset.to_le_bytes()[0]])
+    }
+
+    fn rbpにoffsetを足した位置の値をraxに読み込む_i32(offset: i32) -> Buf {
+        let buf = offset.to_le_bytes();
+        Buf::from([0x48, 0x8b, 0x85, buf[0], buf[1], buf[2], buf[3]])
+    }
+
+    if offset >= i8::MIN as i32 && offset <= i8::MAX as i32 {
+        rbpにoffsetを足した位置の値をraxに読み込む_i8(offset as i8)
+    } else {
+        rbpにoffsetを足した位置の値をraxに読み込む_i32(offset)
+    }
+}
+
+pub fn ediを左シフト(n: u8) -> [u8; 3] {
+    [0xc1, 0xe7, n]
+}
+
+pub fn ediを算術右シフト(n: u8) -> [u8; 3] {
+    [0xc1, 0xff, n]
+}
+
+pub fn ediを論理右シフト(n: u8) -> [u8; 3] {
+    [0xc1, 0xef, n]
+}
+
+pub fn esiを左シフト(n: u8) -> [u8; 3] {
+    [0xc1, 0xe6, n]
+}
+
+pub fn esiを論理右シフト(n: u8) -> [u8; 3] {
+    [0xc1, 0xee, n]
+}
+
+pub fn ediと即値のandを取る(n: u32) -> [u8; 6] {
+    let buf = n.to_le_bytes();
+    [0x81, 0xe7, buf[0], buf[1], buf[2], buf[3]]
+}
+
+pub fn ediと即値を比較してフラグをセット(n: i32) -> [u8; 6] {
+    let buf = n.to_le_bytes();
+    [0x81, 0xff, buf[0], buf[1], buf[2], buf[3]]
+}
+
+pub fn eaxから即値を引く(n: i32) -> [u8; 5] {
+    let buf = n.to_le_bytes();
+    [0x2d, buf[0], buf[1], buf[2], buf[3]]
+}
+
+pub fn eaxと即値を比較してフラグをセット(n: i32) -> [u8; 5] {
+    let buf = n.to_le_bytes();
+    [0x3d, buf[0], buf[1], buf[2], buf[3]]
+}
+
+pub fn rdiに代入(n: u64) -> [u8; 10] {
+    let buf = n.to_le_bytes();
+    [
+        0x48, 0xbf, buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
+    ]
+}
+
+/// スカラーの SSE 命令のプレフィックス。倍精度なら sd 命令、単精度なら ss 命令になる
+fn sse_prefix(倍精度: bool) -> u8 {
+    if 倍精度 {
+        0xf2
+    } else {
+        0xf3
+    }
+}
+
+pub fn xmm0にxmm1を足す(倍精度: bool) -> [u8; 4] {
+    [sse_prefix(倍精度), 0x0f, 0x58, 0xc1]
+}
+
+pub fn xmm0からxmm1を引く(倍精度: bool) -> [u8; 4] {
+    [sse_prefix(倍精度), 0x0f, 0x5c, 0xc1]
+}
+
+pub fn xmm0にxmm1を掛ける(倍精度: bool) -> [u8; 4] {
+    [sse_prefix(倍精度), 0x0f, 0x59, 0xc1]
+}
+
+pub fn xmm0をxmm1で割る(倍精度: bool) -> [u8; 4] {
+    [sse_prefix(倍精度), 0x0f, 0x5e, 0xc1]
+}
+
+/// cmpsd / cmpss。述語が成り立てば xmm0 の下位が全て 1 に、成り立たなければ全て 0 になる
+pub fn xmm0とxmm1を比較した結果のマスクをxmm0にセット(
+    倍精度: bool,
+    述語: u8,
+) -> [u8; 5] {
+    [sse_prefix(倍精度), 0x0f, 0xc2, 0xc1, 述語]
+}
+
+pub fn ediを浮動小数点数に変換してxmm0へ(倍精度: bool) -> [u8; 4] {
+    [sse_prefix(倍精度), 0x0f, 0x2a, 0xc7]
+}
+
+pub fn xmm0を整数に切り捨ててediへ(倍精度: bool) -> [u8; 4] {
+    [sse_prefix(倍精度), 0x0f, 0x2c, 0xf8]
+}
+
+/// rdi の値を 64 ビットの符号付き整数とみなして変換する。unsigned int の値はゼロ拡張してから渡す
+pub fn rdiを浮動小数点数に変換してxmm0へ(倍精度: bool) -> [u8; 5] {
+    [sse_prefix(倍精度), 0x48, 0x0f, 0x2a, 0xc7]
+}
+
+/// 64 ビットの符号付き整数に切り捨てるので、unsigned int に収まる値なら edi に正しい値が残る
+pub fn xmm0を整数に切り捨ててrdiへ(倍精度: bool) -> [u8; 5] {
+    [sse_prefix(倍精度), 0x48, 0x0f, 0x2c, 0xf8]
+}
+
+/// 単精度から倍精度へ、または倍精度から単精度へ変換する
+pub fn xmm0の精度を変換(倍精度へ: bool) -> [u8; 4] {
+    [sse_prefix(!倍精度へ), 0x0f, 0x5a, 0xc0]
+}
+
+pub fn xmmへとポップ(n: u8) -> Buf {
+    // movq xmm{n}, [rsp]; add rsp, 8
+    Buf::from([0xf3, 0x0f, 0x7e, 0x04 | (n << 3), 0x24]).join(rspに即値を足す(8))
+}
+
+pub fn rbpにoffsetを足した位置にxmmを代入(n: u8, offset: i32) -> Buf {
+    fn rbpにoffsetを足した位置にxmmを代入_i8(n: u8, offset: i8) -> Buf {
+        Buf::from([0x66, 0x0f, 0xd6, 0x45 | (n << 3), offset.to_le_bytes()[0]])
+    }
+
+    fn rbpにoffsetを足した位置にxmmを代入_i32(n: u8, offset: i32) -> Buf {
+        let buf = offset.to_le_bytes();
+        Buf::from([
+            0x66,
+            0x0f,
+            0xd6,
+            0x85 | (n << 3),
+            buf[0],
+            buf[1],
+            buf[2],
+            buf[3],
+        ])
+    }
+
+    if offset >= i8::MIN as i32 && offset <= i8::MAX as i32 {
+        rbpにoffsetを足した位置にxmmを代入_i8(n, offset as i8)
+    } else {
+        rbpにoffsetを足した位置にxmmを代入_i32(n, offset)
+    }
+}
+
+pub fn rdiにoffsetを足した位置にxmmを代入(n: u8, offset: i8) -> [u8; 5] {
+    [0x66, 0x0f, 0xd6, 0x47 | (n << 3), offset.to_le_bytes()[0]]
+}
+
+pub fn rdiにoffsetを足した位置からxmmに読み込む(n: u8, offset: i8) -> [u8; 5] {
+    [0xf3, 0x0f, 0x7e, 0x47 | (n << 3), offset.to_le_bytes()[0]]
+}
+
+pub fn rdiにoffsetを足した位置にraxを代入(offset: i8) -> [u8; 4] {
+    [0x48, 0x89, 0x47, offset.to_le_bytes()[0]]
+}
+
+pub fn rdiにoffsetを足した位置の4バイトに即値を代入(
+    offset: i8,
+    n: u32,
+) -> [u8; 7] {
+    let buf = n.to_le_bytes();
+    [
+        0xc7,
+        0x47,
+        offset.to_le_bytes()[0],
+        buf[0],
+        buf[1],
+        buf[2],
+        buf[3],
+    ]
+}
+
+pub fn rdiにoffsetを足した位置の4バイトに即値を足す(offset: i8, n: i8) -> [u8; 4] {
+    [0x83, 0x47, offset.to_le_bytes()[0], n.to_le_bytes()[0]]
+}
+
+pub fn rdiにoffsetを足した位置の8バイトに即値を足す(
+    offset: i8,
+    n: i32,
+) -> [u8; 8] {
+    let buf = n.to_le_bytes();
+    [
+        0x48,
+        0x81,
+        0x47,
+        offset.to_le_bytes()[0],
+        buf[0],
+        buf[1],
+        buf[2],
+        buf[3],
+    ]
+}
+
+pub fn rdiにoffsetを足した位置からeaxに読み込む(offset: i8) -> [u8; 3] {
+    [0x8b, 0x47, offset.to_le_bytes()[0]]
+}
+
+pub fn rdiにoffsetを足した位置からrsiに読み込む(offset: i8) -> [u8; 4] {
+    [0x48, 0x8b, 0x77, offset.to_le_bytes()[0]]
+}
+
+pub fn rsiにoffsetを足した位置からraxに読み込む(offset: i8) -> [u8; 4] {
+    [0x48, 0x8b, 0x46, offset.to_le_bytes()[0]]
+}
+
+pub fn rdiにoffsetを足した位置にrdxを代入(offset: i8) -> [u8; 4] {
+    [0x48, 0x89, 0x57, offset.to_le_bytes()[0]]
+}
+
+pub fn rdiにoffsetを足した位置からraxに読み込む(offset: i8) -> [u8; 4] {
+    [0x48, 0x8b, 0x47, offset.to_le_bytes()[0]]
+}
+
+pub fn rdiにoffsetを足した位置からrdxに読み込む(offset: i8) -> [u8; 4] {
+    [0x48, 0x8b, 0x57, offset.to_le_bytes()[0]]
+}
@@ -60,6 +60,34 @@ check() {
   jobs+=($!)
 }
 
+check_error_inner() {
+  TMPDIR=$(mktemp -d testwork/XXXXXX)
+  input="$1"
+
+  set +e
+  (cd $TMPDIR && ../../target/debug/c_to_elf_compiler <(echo "$input")) 2>/dev/null
+  actual="$?"
+  set -e
+
+  # コンパイルエラーなら終了ステータスは 1 になる。パニックした場合は 101 なので失敗として扱う
+  if [ "$actual" != "1" ]; then
+    printf "\033[31m[FAIL]\033[m %s => compile error expected, but the compiler exited with %s\n" "$input" "$actual"
+    exit 1
+  fi
+
+  printf "\033[32m[PASS]\033[m %s => compile error\n" "$input"
+  rm -rf $TMPDIR
+}
+
+check_error() {
+  jobs_count=${#jobs[@]}
+  if [ $jobs_count -gt 5 ]; then
+    wait_jobs
+  fi
+  check_error_inner "$@" &
+  jobs+=($!)
+}
+
 fail_count=0
 
 wait_jobs() {
@@ -585,6 +613,509 @@ check 123 "int atoi(); int main() { return atoi(\"123\"); }"
 check 0 "int printf(); int main() { return printf(\"a\"); }" "a"
 check 0 "int printf(); int main() { return printf(\"a%dbc\", 123); }" "a123bc"
 
+# function pointers
+check 7 "int add(int a, int b) { return a + b; } int main() { int (*fp)(int, int) = add; return fp(3, 4); }"
+check 5 "int sub(int a, int b) { return a - b; } int main() { int (*fp)(int, int); fp = &sub; return (*fp)(9, 4); }"
+check 1 "int sub(int a, int b) { return a - b; } int main() { int (*fp)(int, int) = sub; return fp == sub; }"
+check 3 "int sub(int a, int b) { return a - b; } int apply(int (*f)(int, int), int x, int y) { return f(x, y); } int main() { return apply(sub, 5, 2); }"
+check 3 "int add(int a, int b) { return a + b; } int call_twice(int f(int, int), int x) { return f(f(x, x), x); } int main() { return call_twice(add, 1); }"
+check 9 "int add(int a, int b) { return a + b; } struct Ops { int (*op)(int, int); }; int main() { struct Ops ops; struct Ops *p = &ops; ops.op = add; return p->op(4, 5); }"
+check 7 "int add(int a, int b) { return a + b; } int sub(int a, int b) { return a - b; } int main() { int (*table[2])(int, int); table[0] = add; table[1] = sub; return table[1](10, 3); }"
+check 8 "int main() { int (*fp)(int); return sizeof(fp); }"
+check 42 "int answer(void) { return 42; } int (*get(void))(void); int main() { int (*fp)(void) = answer; int (**pp)(void) = &fp; return (*pp)(); }"
+check 2 "int f(int x); int main() { int (*p)(int) = f; return p(2); } int f(int x) { return x; }"
+check 6 "int f(int x); int main() { return f(3); } int f(int x) { return x * 2; }"
+check 8 "int inc(int x); int dec(int x); int main() { int (*table[2])(int) = {inc, dec}; return table[0](4) + table[1](4); } int inc(int x) { return x + 1; } int dec(int x) { return x - 1; }"
+check 10 "int twice(int x) { return x * 2; } int (*get(void))(int); int main() { return get()(5); } int (*get(void))(int) { return twice; }"
+check 13 "int add(int a, int b) { return a + b; } int sub(int a, int b) { return a - b; } int (*pick(int op))(int, int) { if (op) return sub; return add; } int main() { return pick(0)(3, 4) + pick(1)(10, 4); }"
+check 5 "int add1(int x) { return x + 1; } int (*pick(int (*g)(int pick), int k))(int) { return k ? g : 0; } int main() { return pick(add1, 1)(4); }"
+check 5 "int f(int, char *); int main() { return f(3, \"ab\"); } int f(int n, char *s) { return n + s[1] - 96; }"
+check 7 "int apply(int (*)(int), int); int twice(int x) { return x * 2; } int main() { return apply(twice, 3) + 1; } int apply(int (*f)(int), int x) { return f(x); }"
+
+# enumerations
+check 0 "enum Color { RED, GREEN, BLUE }; int main() { return RED; }"
+check 2 "enum Color { RED, GREEN, BLUE }; int main() { return BLUE; }"
+check 13 "enum { A = 10, B, C = 1, D, }; int main() { return B + D + 0 * C; }"
+check 8 "enum E { X = 2 * 3 + 1, Y }; int main() { return Y; }"
+check 4 "enum Size { N = 4 }; int main() { int a[N]; return sizeof(a) / sizeof(a[0]); }"
+check 6 "enum { W = 2, H = W + 1 }; int main() { int a[W][H]; return sizeof(a) / sizeof(int); }"
+check 4 "enum E { A }; int main() { return sizeof(enum E); }"
+check 1 "enum Color { RED, GREEN }; int main() { enum Color c = GREEN; return c; }"
+check 5 "enum Color { RED, GREEN }; int paint(enum Color c) { return c + 4; } int main() { return paint(GREEN); }"
+check 3 "enum E { A }; int main() { enum E { B = 3 }; return B; }"
+check 5 "enum E; enum E { A = 5 }; int main() { enum E e = A; return e; }"
+check 11 "int main() { enum E { A = 5 }; enum E e = A; { enum E { B = 6 }; return B + e; } }"
+check 7 "enum { A = 3 }; int main() { enum { A = 7 }; return A; }"
+check 3 "enum { A = 3 }; int main() { { enum { A = 7 }; } return A; }"
+check 9 "int main() { enum { K = 9 } k = K; return k; }"
+check 2 "int main() { enum T { P = 1, Q }; enum T t = Q; return t; }"
+
+# unions
+check 8 "union U { int a; char *p; }; int main() { return sizeof(union U); }"
+check 4 "union U { int a; char c; }; int main() { return sizeof(union U); }"
+check 8 "union U { char c[5]; int a; }; int main() { return sizeof(union U); }"
+check 65 "union U { int a; char c; }; int main() { union U u; u.a = 65; return u.c; }"
+check 1 "union U { int a; char c; }; int main() { union U u; return &u.a == &u.c; }"
+check 7 "union U { int a; char c; }; int main() { union U u; union U *p = &u; p->c = 7; return p->c; }"
+check 12 "union V { int i; char *s; }; struct Tagged { int kind; union V v; }; int main() { struct Tagged t; t.kind = 1; t.v.i = 11; return t.kind + t.v.i; }"
+check 16 "union V { int i; char *s; }; struct Tagged { int kind; union V v; }; int main() { return sizeof(struct Tagged); }"
+
+# typedef
+check 3 "typedef int MyInt; int main() { MyInt x = 3; return x; }"
+check 8 "typedef int *IntPtr; int main() { return sizeof(IntPtr); }"
+check 5 "typedef struct Node Node; struct Node { int val; Node *next; }; int main() { Node a; Node b; a.next = &b; b.val = 5; return a.next->val; }"
+check 7 "typedef struct Point { int x; int y; } Point; int main() { Point p; p.x = 3; p.y = 4; return p.x + p.y; }"
+check 8 "typedef struct { int x; int y; } Pair; int main() { return sizeof(Pair); }"
+check 12 "typedef int Arr3[3]; int main() { Arr3 a; return sizeof(a); }"
+check 9 "typedef int (*BinOp)(int, int); int mul(int a, int b) { return a * b; } int main() { BinOp f = mul; return f(3, 3); }"
+check 4 "typedef char C; typedef C *CP; int main() { CP p; return sizeof(*p) + sizeof(p) - 5; }"
+check 2 "typedef int T; int main() { typedef char T; return sizeof(T) + 1; }"
+check 5 "typedef int T; int main() { { typedef char T; } return sizeof(T) + 1; }"
+check 6 "typedef int T; int main() { int T = 6; return T; }"
+check 4 "typedef int T; int main() { { int T = 2; T = T * 2; } T x = 4; return x; }"
+check 7 "typedef int T; T add(T a, T b) { return a + b; } int main() { return add(3, 4); }"
+check 1 "typedef int T; typedef int T; int main() { T x = 1; return x; }"
+check 3 "typedef enum { RED, GREEN, BLUE } Color; int main() { Color c = BLUE; return c + 1; }"
+check 8 "typedef int T; int main() { return sizeof(T *); }"
+
+# casts
+check 1 "int main() { return (int)1; }"
+check 44 "int main() { return (char)300; }"
+check 200 "int main() { int x = 456; char c = (char)x; return c + 256; }"
+check 3 "typedef int T; int main() { T x = 3; return (T)x; }"
+check 4 "int main() { int a[2]; a[1] = 4; void *p = a; return *((int *)p + 1); }"
+check 0 "int main() { (void)42; return 0; }"
+check 16 "int main() { return sizeof(int [4]); }"
+check 8 "int main() { return sizeof(int (*)(int)); }"
+check 3 "enum { N = (int)3 }; int main() { return N; }"
+
+# struct values
+check 7 "struct P { int x; int y; }; int main() { struct P a; struct P b; a.x = 3; a.y = 4; b = a; return b.x + b.y; }"
+check 9 "struct P { int x; int y; }; int main() { struct P a; a.x = 4; a.y = 5; struct P b = a; a.x = 100; return b.x + b.y; }"
+check 30 "struct Big { int a[10]; }; int main() { struct Big x; struct Big y; x.a[0] = 10; x.a[9] = 20; y = x; return y.a[0] + y.a[9]; }"
+check 11 "struct P { int x; int y; }; int sum(struct P p) { return p.x + p.y; } int main() { struct P a; a.x = 5; a.y = 6; return sum(a); }"
+check 5 "struct P { int x; int y; }; int f(struct P p) { p.x = 100; return 0; } int main() { struct P a; a.x = 5; f(a); return a.x; }"
+check 21 "struct Q { char c; int i; char *p; }; int g(int a, struct Q q, int b) { return a + q.c + q.i + *q.p + b; } int main() { char z = 10; struct Q q; q.c = 1; q.i = 2; q.p = &z; return g(3, q, 5); }"
+check 55 "struct Big { int a[10]; }; int sum(struct Big b) { int s = 0; int i; for (i = 0; i < 10; i++) s = s + b.a[i]; return s; } int main() { struct Big b; int i; for (i = 0; i < 10; i++) b.a[i] = i + 1; return sum(b); }"
+check 12 "struct P { int x; int y; }; struct P make(int x, int y) { struct P p; p.x = x; p.y = y; return p; } int main() { struct P a = make(5, 7); return a.x + a.y; }"
+check 7 "struct P { int x; int y; }; struct P make(int x, int y) { struct P p; p.x = x; p.y = y; return p; } int main() { return make(3, 7).y; }"
+check 24 "struct T { char a; char b; char c; }; struct T make(void) { struct T t; t.a = 7; t.b = 8; t.c = 9; return t; } int main() { struct T t = make(); return t.a + t.b + t.c; }"
+check 45 "struct Big { int a[10]; }; struct Big make(int k) { struct Big b; int i; for (i = 0; i < 10; i++) b.a[i] = i * k; return b; } int main() { struct Big b = make(1); return b.a[0] + b.a[9] + make(4).a[9]; }"
+check 13 "struct S { int a; int b; int c; }; struct S id(struct S s) { return s; } int main() { struct S s; s.a = 1; s.b = 4; s.c = 8; struct S t = id(s); return t.a + t.b + t.c; }"
+check 28 "struct S { int v[3]; }; int f(int a, int b, int c, int d, int e, struct S s) { return a + b + c + d + e + s.v[0] + s.v[2]; } int main() { struct S s; s.v[0] = 6; s.v[2] = 7; return f(1, 2, 3, 4, 5, s); }"
+check 10 "struct P { int x; int y; }; int main() { struct P a; struct P b; struct P c; a.x = 10; c = b = a; return c.x; }"
+check 6 "struct P { int x; int y; }; int main() { struct P a; struct P *p = &a; struct P b; b.x = 6; *p = b; return a.x; }"
+
+
+# forward-declared, self-referential and block-scoped struct tags
+check 3 "struct Node; struct Node { int val; struct Node *next; }; int main() { struct Node a; struct Node b; a.val = 1; a.next = &b; b.val = 2; b.next = 0; return a.val + a.next->val; }"
+check 7 "struct A; struct B { struct A *a; int y; }; struct A { struct B *b; int x; }; int main() { struct A a; struct B b; a.b = &b; b.a = &a; a.x = 3; b.y = 4; return a.b->a->x + b.a->b->y; }"
+check 10 "struct P; typedef struct P P; struct P { int x; P *self; }; int main() { P p; p.x = 10; p.self = &p; return p.self->self->x; }"
+check 12 "int main() { struct S { int a; int b; }; struct S s; s.a = 5; s.b = 7; return s.a + s.b; }"
+check 5 "struct S { char c; }; int main() { int n = sizeof(struct S); { struct S { int a; int b; }; n = n + sizeof(struct S) - 5; } return n + sizeof(struct S); }"
+check 32 "struct S { int a; }; int main() { struct S; struct S { int x; struct S *p; }; struct S s; s.p = &s; return sizeof(struct S) + sizeof(s); }"
+check 6 "struct L { int v; struct L *next; }; int sum(struct L *l) { int s = 0; for (; l; l = l->next) s = s + l->v; return s; } int main() { struct L c; struct L b; struct L a; a.v = 1; a.next = &b; b.v = 2; b.next = &c; c.v = 3; c.next = 0; return sum(&a); }"
+check 3 "int f() { struct T { int x; } t; t.x = 1; return t.x; } int g() { struct T { char c; int y; } t; t.y = 2; return t.y + sizeof(struct T) - 8; } int main() { return f() + g(); }"
+check 9 "struct X; struct X *p; struct X *q; int get(struct X x); struct X { int a; int b; }; int get(struct X x) { return x.a + x.b; } int main() { struct X a[2]; a[1].a = 4; a[1].b = 5; p = &a[0]; q = &a[1]; *p = *q; return get(p[0]); }"
+check 7 "int main() { struct S *p; struct S { int x; } s; p = &s; s.x = 7; return p->x; }"
+check 7 "int main() { struct S *p; { struct S { int y; int z; } t; t.z = 1; } struct S { int x; } s; p = &s; s.x = 3; return p->x + sizeof(*p); }"
+check 4 "struct S *g; struct S { int x; }; int main() { struct S s; s.x = 4; g = &s; return g->x; }"
+
+# anonymous struct and union members
+check 8 "struct S { int kind; union { int i; char c; }; }; int main() { struct S s; s.kind = 1; s.i = 7; return s.kind + s.i + sizeof(struct S) - 8; }"
+check 12 "struct V { union { struct { int x; int y; }; int xy[2]; }; }; int main() { struct V v; v.x = 5; v.y = 7; return v.xy[0] + v.xy[1] + sizeof(struct V) - 8; }"
+check 9 "union U { struct { char a; char b; }; int n; }; int main() { union U u; u.n = 0; u.a = 4; u.b = 5; return u.a + u.b; }"
+
+
+# bit-fields
+check 4 "struct S { unsigned a : 3; unsigned b : 5; }; int main() { return sizeof(struct S); }"
+check 1 "struct S { char a : 3; char b : 5; }; int main() { return sizeof(struct S); }"
+check 8 "struct S { int a : 30; int b : 3; }; int main() { return sizeof(struct S); }"
+check 4 "struct S { char c; int a : 3; }; int main() { return sizeof(struct S); }"
+check 2 "struct S { char a : 4; char : 0; char b : 4; }; int main() { return sizeof(struct S); }"
+check 2 "struct S { char a; int : 3; }; int main() { return sizeof(struct S); }"
+check 4 "union U { int a : 3; char b : 5; }; int main() { return sizeof(union U); }"
+check 12 "struct S { unsigned a : 3; unsigned b : 5; unsigned c : 8; }; int main() { struct S s; s.a = 5; s.b = 7; s.c = 0; return s.a + s.b; }"
+check 1 "struct S { int a : 3; }; int main() { struct S s; s.a = 7; return -s.a; }"
+check 7 "struct S { unsigned a : 3; }; int main() { struct S s; s.a = 15; return s.a; }"
+check 3 "struct S { int x : 2; int y : 6; }; int main() { struct S s; s.y = 0; s.x = 1; s.y = -2; return s.x - s.y; }"
+check 42 "struct S { unsigned lo : 4; unsigned hi : 4; }; int main() { struct S s; char *p = &s; *p = 42; return s.lo + s.hi * 16; }"
+check 18 "struct S { unsigned lo : 4; unsigned hi : 4; }; int main() { struct S s; char *p = &s; s.lo = 2; s.hi = 1; return *p; }"
+check 6 "struct S { unsigned n : 3; }; int main() { struct S s; s.n = 4; s.n += 2; return s.n; }"
+check 1 "struct S { unsigned n : 3; }; int main() { struct S s; s.n = 6; s.n += 3; return s.n; }"
+check 5 "struct S { int flag : 1; unsigned count : 4; }; int main() { struct S s; struct S *p = &s; p->count = 4; p->count++; return p->count; }"
+check 9 "struct S { unsigned a : 3; unsigned b : 5; }; int main() { struct S s; int x = s.a = 9; return x + 8; }"
+check 7 "typedef unsigned u32; struct F { u32 a : 3; }; int main() { struct F f; f.a = 7; return f.a; }"
+check 71 "typedef int i32; struct F { unsigned a : 3; i32 b : 3; }; int main() { struct F f; f.a = 7; f.b = 7; return f.a * 10 + (f.b < 0); }"
+check 3 "struct F { unsigned a : 32; unsigned b : 31; }; int main() { struct F f; f.a = -1; f.b = -1; return (f.a > 0) + (f.b > 0) * 2; }"
+check 8 "struct S { int a : 3; int b; }; int main() { struct S s; return sizeof(s.b) + sizeof(s.a + 1); }"
+check_error "struct S { int a : 3; int b; }; int main() { struct S s; return sizeof(s.a); }"
+check_error "struct S { int a : 3; int b; }; int main() { struct S *p; return sizeof p->a; }"
+
+# unsigned integer types
+check 5 "int main() { return sizeof(unsigned) + sizeof(unsigned char); }"
+check 13 "int main() { unsigned x = -1; return (x > 0) + (x / 2 > 0) * 4 + (x % 10 == 5) * 8; }"
+check 7 "int main() { unsigned x = 1; return (x < -1) + 2 * (-1 < 0) + 4 * ((unsigned)-1 > 1); }"
+check 3 "int main() { unsigned char c = 200; int i = c; return (i == 200) + (c > 100) * 2; }"
+check 104 "int main() { unsigned char c = 250; c += 10; unsigned x = 5; x -= 6; return c + (x > 5) * 100; }"
+check 3 "int main() { unsigned char c = -1; unsigned x = 7; x /= -1; return (c == 255) + 2 * (x == 0); }"
+check 3 "int main() { unsigned x = 7; int neg = -x; return (-x > 0) + (neg == -7) * 2; }"
+check 1 "unsigned char uc(void) { return 255; } int main() { return uc() == 255; }"
+check 1 "int main() { char c = -1; return c == -1; }"
+check 3 "int main() { char c = -1; switch (c) { case -1: return 3; case 255: return 5; default: return 4; } }"
+check 7 "int main() { char s[2]; s[0] = 200; unsigned char u = 200; return (s[0] < 0) + (u > 0) * 2 + (s[0] + u == 144) * 4; }"
+check 3 "char g = -3; int main() { char *p = &g; double d = *p; return (d == -3.0) + (g / 3 == -1) * 2; }"
+check 7 "int main() { int a[3] = {1, 7, 9}; int *p = a + 2; char c = -1; p += c; return *p; }"
+check 3 "int a[(unsigned)-1 / 2 > 0 ? 3 : 1]; int main() { return sizeof a / sizeof a[0]; }"
+check 3 "int main() { unsigned x = -1; double d = x; float f = x; return (d > 4e9) + (f > 4e9) * 2; }"
+check 7 "unsigned g = -1; double h = (unsigned)-1; unsigned k = 3e9; int main() { return (g > 2000000000) + (h > 4e9) * 2 + (k / 1000000000 == 3) * 4; }"
+check 7 "int main() { unsigned x = -1; return (x > 0) + ((x >> 28) == 15) * 2 + (x / 2 > 0) * 4; }"
+check 15 "int main() { unsigned x = -16; x >>= 28; return x; }"
+check 3 "int main() { int x = -16; unsigned s = 28; unsigned char c = 255; return (x >> s == -1) + (c >> 7) * 2; }"
+check 31 "int a[(unsigned)-1 >> 28]; int b[(-1 >> 28) + 2]; int main() { return sizeof a / sizeof a[0] + sizeof b / sizeof b[0] * 16; }"
+
+# floating point
+check 8 "int main() { return sizeof(double) + sizeof(float) - 4; }"
+check 3 "int main() { double x = 1.5; return x * 2; }"
+check 5 "int main() { return (int)(.5 * 10); }"
+check 100 "int main() { double x = 1e3; return x / 10; }"
+check 5 "int main() { float f = 2.5f; return f + f; }"
+check 7 "int main() { double x = 7.9; return x; }"
+check 249 "int main() { double x = -7.9; int n = x; return n; }"
+check 2 "int main() { int n = 5; double x = n; return x / 2; }"
+check 6 "int main() { char c = 3; double x = c; float f = x; return f * 2; }"
+check 1 "int main() { double a = 0.1; double b = 0.2; return a + b > 0.3; }"
+check 1 "int main() { float a = 0.1f; float b = 0.2f; return a + b == 0.3f; }"
+check 11 "int main() { double a = 1.0; double b = 2.0; return (a < b) + (a <= b) * 2 + (a > b) * 4 + (a >= a) * 8 + (a == b) * 16 + (a != a) * 32; }"
+check 3 "int main() { double x = 4.5; double y = -x; return x + y + 3; }"
+check 4 "int main() { double x = 1.25; x += 2; x++; x -= 0.25; return x; }"
+check 1 "int main() { double x = 0.0; return !x; }"
+check 7 "int main() { double x = 3.5; if (x) return x * 2; return 0; }"
+check 10 "double half(double x) { return x / 2; } int main() { return half(20); }"
+check 9 "float sq(float x) { return x * x; } int main() { return sq(3.0f); }"
+check 21 "double f(int a, double b, int c, float d) { return a + b * c + d; } int main() { return f(1, 2.5, 4, 10); }"
+check 30 "double f(int a, int b, int c, int d, int e, int f, double g) { return a + b + c + d + e + f + g; } int main() { return f(1, 2, 3, 4, 5, 6, 9); }"
+check 7 "struct P { double x; double y; }; struct P make(double x, double y) { struct P p; p.x = x; p.y = y; return p; } double sum(struct P p) { return p.x + p.y; } int main() { return sum(make(2.5, 4.5)); }"
+check 9 "struct Q { double x; int n; }; struct Q make(double x, int n) { struct Q q; q.x = x; q.n = n; return q; } int main() { struct Q q = make(1.5, 3); return q.x * 2 + q.n * 2; }"
+check 16 "struct F { float a; float b; }; struct F swap(struct F f) { struct F g; g.a = f.b; g.b = f.a; return g; } int main() { struct F f; f.a = 1.5f; f.b = 3.25f; struct F g = swap(f); return g.a * 4 + g.b * 2; }"
+check 6 "struct M { int n; float f; }; int get(struct M m) { return m.n + m.f; } int main() { struct M m; m.n = 2; m.f = 4.5f; return get(m); }"
+check 12 "int main() { double a[3]; a[0] = 1.5; a[1] = 2.5; a[2] = 8; double *p = a; return p[0] + p[1] + *(p + 2); }"
+
+# _Bool
+check 1 "int main() { return sizeof(_Bool); }"
+check 1 "int main() { _Bool b = 42; return b; }"
+check 1 "int main() { _Bool b = 256; return b; }"
+check 0 "int main() { _Bool b = 0; return b; }"
+check 1 "int main() { int x = 3; bool b = &x; return b; }"
+check 1 "int main() { bool b = 0.5; return b; }"
+check 0 "int main() { bool b = -0.0; return b; }"
+check 3 "int main() { return true + true + (int)(bool)-7 + false; }"
+check 1 "int main() { bool b = true; b++; return b; }"
+check 1 "int main() { bool b = false; b--; return b; }"
+check 0 "int main() { bool b = true; b -= 1; return b; }"
+check 1 "int main() { bool b = true; b -= 2; return b; }"
+check 1 "int main() { bool b = true; b += 255; return b; }"
+check 3 "int main() { bool a = 5 > 3; bool b = !0; bool c = 2 && 3; bool d = 0 || 7; return a + b + c + d - 1; }"
+check 1 "bool is_nonzero(int x) { return x; } int main() { return is_nonzero(100); }"
+check 2 "int count(bool a, bool b) { return a + b; } int main() { return count(4, 9); }"
+check 7 "int main() { bool b = 17; if (b) return 7; return 0; }"
+check 2 "struct S { bool a; bool b; char c; }; int main() { struct S s; s.a = 3; s.b = 0; s.c = 1; return s.a + s.b + s.c + sizeof(struct S) - 3; }"
+check 61 "struct S { _Bool a : 1; _Bool b : 1; int c : 6; }; int main() { struct S s; s.c = 0; s.a = 4; s.b = 0; s.c = 5; return s.a + s.b * 2 + s.c * 4 + sizeof(s) * 10; }"
+check 101 "struct S { bool f : 1; }; int main() { struct S s; s.f = 0; s.f += 2; int x = s.f; s.f--; int y = s.f; s.f--; return x * 100 + y * 10 + s.f; }"
+check 7 "struct S { _Bool f : 1; unsigned char pad : 7; }; int main() { struct S s; char *p = &s; *p = 0; s.f = 256; int r = *p; s.f = 0.25; return r + *p * 2 + sizeof(s) * 4; }"
+check 6 "struct S { _Bool f : 1; }; int main() { struct S s; int *q = 0; s.f = q; int a = s.f; s.f = &a; return a + s.f * 2 + (s.f = 7) * 4; }"
+check 3 "struct S { _Bool f : 1; unsigned n : 3; }; struct S g = {0.5, 9}; int main() { return g.f + g.n * 2; }"
+
+# const and volatile
+check 3 "int main() { const int x = 3; return x; }"
+check 7 "int main() { int const x = 7; const int *p = &x; return *p; }"
+check 5 "int main() { int a = 1; int b = 5; const int *p = &a; p = &b; return *p; }"
+check 9 "int main() { int a = 1; int *const p = &a; *p = 9; return a; }"
+check 6 "int sum(const int *p, int n) { int s = 0; for (int i = 0; i < n; i++) s = s + p[i]; return s; } int main() { int a[3]; a[0] = 1; a[1] = 2; a[2] = 3; return sum(a, 3); }"
+check 4 "struct S { const int a; int b; }; int main() { struct S s; s.b = 4; return s.b; }"
+check 12 "typedef const char *str; int len(str s) { int n = 0; while (*s++) n++; return n; } int main() { return len(\"hello, world\"); }"
+check 10 "int main() { volatile int x = 3; x = x + 7; return x; }"
+check 2 "int main() { volatile char c[2]; c[0] = 1; c[1] = 1; return c[0] + c[1]; }"
+check 8 "int main() { const volatile int x = 8; const volatile int *p = &x; return *p; }"
+check 4 "int main() { return sizeof(const int) + sizeof(volatile char) - 1; }"
+check 3 "int main() { const double d = 1.5; return d * 2; }"
+
+# multi-dimensional arrays and array parameters
+check 5 "int main() { int a[3][4]; a[1][2] = 5; return a[1][2]; }"
+check 64 "int main() { int a[3][4]; return sizeof(a[0]) + sizeof(a) + sizeof(a[0][0]) - 4; }"
+check 73 "int main() { int a[2][3][4]; a[1][2][3] = 9; int *p = &a[1][2][3]; return *p + sizeof(a[1]) + sizeof(a[1][2]); }"
+check 8 "int main() { int a[3][4]; int (*p)[4] = a; p[2][3] = 7; p = p + 1; return a[2][3] + (p - a); }"
+check 5 "int main() { int a[3][4]; a[1][0] = 5; return **(a + 1); }"
+check 11 "int main() { int a[3][4]; int *q = a[1]; q[2] = 3; return a[1][2] + (&a[2][0] - &a[0][0]); }"
+check 56 "int main() { int a[3][4]; int (*p)[3][4] = &a; (*p)[2][2] = 8; return a[2][2] + sizeof(*p); }"
+check 78 "int main() { int a[3][4]; for (int i = 0; i < 3; i++) for (int j = 0; j < 4; j++) a[i][j] = i * 4 + j; int s = 0; for (int i = 0; i < 3; i++) for (int j = 0; j < 4; j++) s += a[i][j]; return s + a[2][1] + 3; }"
+check 14 "int f(int m[][4]) { return m[1][2] + sizeof(m); } int main() { int a[3][4]; a[1][2] = 6; return f(a); }"
+check 24 "int f(int m[3][4]) { return sizeof(m) + sizeof(m[0]); } int main() { int a[3][4]; return f(a); }"
+check 7 "int sum(int n, int a[]) { int s = 0; for (int i = 0; i < n; i++) s += a[i]; return s; } int main() { int a[3]; a[0] = 1; a[1] = 2; a[2] = 4; return sum(3, a); }"
+check 65 "int f(char *argv[]) { return argv[1][0]; } int main() { char *v[2]; v[1] = \"A\"; return f(v); }"
+check 100 "int main(int argc, char *argv[]) { return argv[1][1]; }" "" "xd"
+check 3 "int f(int (*fp)(int a[])) { int x[2]; x[1] = 3; return fp(x); } int g(int *p) { return p[1]; } int main() { return f(g); }"
+
+# global variables
+check 3 "int g; int main() { g = 3; return g; }"
+check 0 "int g; int main() { return g; }"
+check 7 "int g; int set(int x) { g = x; return 0; } int main() { set(7); return g; }"
+check 5 "char c; int x; int main() { c = 2; x = 3; return c + x; }"
+check 12 "int g; int main() { int *p = &g; *p = 12; return g; }"
+check 6 "int a[3]; int main() { a[0] = 1; a[1] = 2; a[2] = 3; return a[0] + a[1] + a[2]; }"
+check 0 "int a[100]; int main() { int s = 0; for (int i = 0; i < 100; i++) s = s + a[i]; return s; }"
+check 9 "int a[3]; int sum(int *p) { return p[0] + p[1] + p[2]; } int main() { a[1] = 9; return sum(a); }"
+check 11 "struct P { int x; char c; int *y; }; struct P g; int main() { g.x = 4; g.c = 3; g.y = &g.x; return g.x + g.c + *g.y; }"
+check 8 "struct P { int x; int y; }; struct P g; struct P *get() { return &g; } int main() { get()->y = 8; return g.y; }"
+check 42 "int *p; int x; int main() { p = &x; *p = 42; return x; }"
+check 3 "double d; int main() { d = 1.5; d = d * 2; return d; }"
+check 1 "int g; int main() { int g = 5; return &g != &g + 1; }"
+check 2 "int cnt; int inc() { cnt++; return cnt; } int main() { inc(); return inc(); }"
+
+# static initializers
+check 25 "int table[] = {1, 2, 3}; int main() { return sizeof(table) + table[0] * 10 + table[2]; }"
+check 99 "char *names[] = {\"ab\", \"cd\"}; int main() { return names[1][0]; }"
+check 81 "struct Cfg { int port; char *host; int verbose; }; struct Cfg cfg = {.port = 80}; int main() { return cfg.port + cfg.verbose + (cfg.host == 0); }"
+check 3 "int table[] = {1, 2, 3}; int *p = &table[2]; int main() { return *p; }"
+check 2 "int table[] = {1, 2, 3}; int *p = table + 1; int main() { return *p; }"
+check 36 "struct P { int x; int y; }; struct P ps[] = {{1, 2}, {3, 4}, 5, 6}; int main() { return sizeof(ps) / sizeof(ps[0]) * 10 + ps[2].y; }"
+check 49 "int m[2][3] = {1, 2, 3, 4, [1][1] = 9}; int main() { return m[1][0] * 10 + m[1][1]; }"
+check 61 "char s[] = \"hello\"; int main() { return sizeof(s) * 10 + (s[4] == 111); }"
+check 105 "char s[8] = \"hi\"; int main() { return s[1] + s[5]; }"
+check 42 "int f(int x) { return x * 2; } int (*fp)(int) = f; int main() { return fp(21); }"
+check 116 "struct S { int a : 3; int b : 5; char c; }; struct S s = {3, 7, 2}; int main() { return s.a * 100 + s.b * 10 + s.c; }"
+check 78 "int a[5] = {[3] = 7, 8}; int main() { return a[3] * 10 + a[4] + a[0]; }"
+check 66 "union U { int i; char c; }; union U u = {.c = 66}; int main() { return u.i; }"
+check 210 "struct A { int x; struct { int y; int z; }; int w; }; struct A a = {1, 2, 3, 4}; int main() { return a.x * 1000 + a.y * 100 + a.z * 10 + a.w; }"
+check 59 "float f = 2.5; _Bool b = 5; int i = 3.9; int main() { return f * 2 + b * 10 + i * 100; }"
+check 3 "int g = 3; int g; int main() { return g; }"
+check 98 "char *s = \"abc\" + 1; int main() { return *s; }"
+check 1 "int a[3] = {1, 2, 3}; int *q = a; int **pp = &q; int main() { return **pp; }"
+check 8 "struct P { int x; int y; }; struct L { struct P a; struct P b; int c; }; struct L l = {.b.x = 7, 8}; int main() { return l.b.y; }"
+check 96 "struct P { int x; int y; }; struct L { struct P a; struct P b; int c; }; struct L l = {.b.x = 7, 8, 9}; int main() { return l.b.x + l.b.y * 10 + l.c; }"
+check 56 "int m[2][3] = {[1][1] = 5, 6}; int main() { return m[1][1] * 10 + m[1][2] + m[0][1]; }"
+check 234 "struct P { int x; int y; }; struct L { struct P a; struct P b; int c; }; struct L arr[2] = {[1].b.y = 5, 6, [0].a = {1, 2}, 3}; int main() { return arr[1].b.y * 100 + arr[1].c * 10 + arr[0].a.y + arr[0].b.x * 1000; }"
+check 55 "struct S { int a[3]; int b; } s = {.a[1] = 5, 6, 7}; int main() { return s.a[1] * 100 + s.a[2] * 10 + s.b; }"
+check 2 "union U { struct { int x; int y; } p; int n; } u = {.p.x = 1, 2}; int main() { return u.p.y; }"
+
+# brace initializers for locals
+check 25 "int main() { int a[] = {1, 2, 3}; return sizeof(a) + a[0] * 10 + a[2]; }"
+check 3 "int main() { int a[5] = {1, 2}; return a[0] + a[1] + a[2] + a[3] + a[4]; }"
+check 9 "int main() { int x = 9; int a[100] = {x}; int s = 0; for (int i = 0; i < 100; i++) s = s + a[i]; return s; }"
+check 45 "struct P { int x; int y; char c; }; int main() { struct P p = {.y = 4, 5}; return p.x * 100 + p.y * 10 + p.c; }"
+check 242 "int main() { char s[] = \"abc\"; return sizeof(s) * 100 + s[1]; }"
+check 104 "int main() { char s[10] = \"hi\"; return s[0] + s[9]; }"
+check 27 "struct P { int x; int y; }; int main() { struct P ps[2] = {{1, 2}, 3, 4}; return ps[0].y * 10 + ps[1].x + ps[1].y; }"
+check 60 "int main() { int m[2][2] = {[1] = {5, 6}}; return m[1][1] * 10 + m[0][0] + m[0][1]; }"
+check 7 "struct S { int a : 3; int b : 4; }; int main() { struct S s = {.b = -3}; return s.b + 10 + s.a; }"
+check 7 "int main() { int x = {7}; return x; }"
+check 4 "int main() { double d[] = {1.5, 2.5}; return d[0] + d[1]; }"
+check 4 "int main() { int s = 0; for (int a[3] = {1, 2, 3}; s < 1; s++) s = a[2]; return s; }"
+check 13 "int main() { int x = 3; int a[] = {x, x + 1, x * 2}; return a[0] + a[1] + a[2]; }"
+check 6 "int g = 5; int main() { struct { int x; int *p; } s = {1, &g}; return *s.p + s.x; }"
+check 0 "int f() { int a[4] = {1, 2, 3, 4}; return a[3]; } int g() { int b[4] = {0}; return b[3]; } int main() { f(); return g(); }"
+check 8 "struct P { int x; int y; }; struct L { struct P a; struct P b; int c; }; int main() { struct L l = {.b.x = 7, 8}; return l.b.y + l.a.x + l.c; }"
+check 96 "struct P { int x; int y; }; struct L { struct P a; struct P b; int c; }; int main() { struct L l = {.b.x = 7, 8, 9}; return l.b.x + l.b.y * 10 + l.c; }"
+check 56 "int main() { int m[2][3] = {[1][1] = 5, 6}; return m[1][1] * 10 + m[1][2] + m[0][1]; }"
+check 27 "struct P { int x; int y; }; struct L { struct P a; struct P b; int c; }; int main() { struct L l = {.a.y = 1, .b.y = 2, 3, .a.x = 4}; return l.a.x * 1000 + l.a.y * 100 + l.b.y * 10 + l.c; }"
+check 96 "struct P { int x; int y; }; struct L { struct P a; struct P b; int c; }; int main() { int n = 7; struct L l = {.b = n, n + 1, 9}; return l.b.x + l.b.y * 10 + l.c; }"
+
+# static and extern
+check 3 "int count() { static int n; n = n + 1; return n; } int main() { count(); count(); return count(); }"
+check 12 "int next() { static int n = 10; return n++; } int main() { next(); next(); return next(); }"
+check 21 "int f() { static int n = 1; return n++; } int g() { static int n = 20; return n++; } int main() { f(); return f() + g() - 1; }"
+check 5 "static int twice(int x) { return x * 2; } static int g = 1; int main() { return twice(2) + g; }"
+check 5 "extern int g; int main() { return g; } int g = 5;"
+check 9 "extern int a[]; int f() { return a[2]; } int a[3] = {1, 2, 9}; int main() { return f(); }"
+check 7 "int main() { extern int g; return g; } int g = 7;"
+check 4 "static int f(); int f() { return 4; } int main() { return f(); }"
+check 2 "int *p() { static int a[2] = {1, 2}; return a; } int main() { return p()[1]; }"
+check 6 "int main() { static int x = 1; { static int x = 5; x++; return x; } }"
+check 3 "extern int x; int main() { return 3; }"
+check 2 "int main() { extern int y; return 2; }"
+check 5 "extern int x[]; int main() { return sizeof(x[0]) + 1; }"
+check_error "extern int x; int main() { return x; }"
+check_error "int main() { extern int y; return y; }"
+check_error "extern int x; int *p = &x; int main() { return 4; }"
+
+# compound literals
+check 3 "struct P { int x; int y; }; int main() { struct P p = (struct P){1, 2}; return p.x + p.y; }"
+check 6 "int main() { int *p = (int[]){1, 2, 3}; return p[0] + p[1] + p[2]; }"
+check 12 "int main() { return sizeof((int[]){1, 2, 3}); }"
+check 2 "int main() { return (int[]){1, 2, 3}[1]; }"
+check 20 "struct P { int x; int y; }; int main() { return (struct P){.y = 20}.y + (struct P){.y = 20}.x; }"
+check 13 "struct Opt { int verbose; int level; }; int run(struct Opt o) { return o.verbose * 10 + o.level; } int main() { return run((struct Opt){1, 3}); }"
+check 7 "struct Opt { int a; int b; }; int get(struct Opt *o) { return o->a + o->b; } int main() { return get(&(struct Opt){.b = 7}); }"
+check 5 "int main() { int *p = &(int){4}; *p = *p + 1; return *p; }"
+check 30 "int main() { int s = 0; for (int i = 0; i < 3; i++) { int *p = (int[]){i, 10}; s = s + p[1]; p[1] = 0; } return s; }"
+check 6 "int *p = (int[]){1, 2, 3}; int main() { return p[0] + p[1] + p[2]; }"
+check 9 "struct P { int x; int y; }; struct P *q = &(struct P){4, 5}; int main() { return q->x + q->y; }"
+check 11 "int main() { int x = 5; int *p = (int[]){x, x + 1}; return p[0] + p[1]; }"
+
+# variable-length arrays and alloca
+check 45 "int sum(int n) { int a[n]; for (int i = 0; i < n; i++) a[i] = i; int s = 0; for (int i = 0; i < n; i++) s = s + a[i]; return s; } int main() { return sum(10); }"
+check 40 "int f(int n) { int a[n]; return sizeof(a); } int main() { return f(10); }"
+check 24 "int f(int n) { char a[n + 1][3]; return sizeof a + sizeof(a[0]) * 2; } int main() { return f(5) - 0; }"
+check 12 "int f(int n) { return sizeof(int[n]); } int main() { return f(3); }"
+check 34 "int trace(int n) { int m[n][n]; for (int i = 0; i < n; i++) for (int j = 0; j < n; j++) m[i][j] = i * n + j; int t = 0; for (int i = 0; i < n; i++) t = t + m[i][i]; return t + sizeof(m) / sizeof(m[0]); } int main() { return trace(4); }"
+check 7 "int f(int n) { int a[n]; int (*p)[n] = &a; (*p)[n - 1] = 7; return a[n - 1]; } int main() { return f(3); }"
+check 100 "int f(int n) { int s = 0; for (int i = 0; i < 100000; i++) { int a[n]; a[0] = 1; s = s + a[0]; } return s / 1000; } int main() { return f(1000); }"
+check 9 "int main() { int n = 3; typedef int T[n]; n = 100; T a; return sizeof(a) - 3; }"
+check 11 "int g(int x) { return x; } int main() { int n = 2; int a[n]; a[0] = 5; a[1] = 6; return g(a[0] + a[1]); }"
+check 6 "int main() { int *p = __builtin_alloca(3 * sizeof(int)); p[0] = 1; p[1] = 2; p[2] = 3; return p[0] + p[1] + p[2]; }"
+check 15 "int main() { int x = 10; int y = x + (*(int *)__builtin_alloca(4) = 5); return y; }"
+check 3 "int f(int n) { int a[n]; int b[n * 2]; a[n - 1] = 1; b[2 * n - 1] = 2; return a[n - 1] + b[2 * n - 1]; } int main() { return f(4); }"
+check 9 "int f(int n, int a[n]) { return a[0] + a[n - 1]; } int main() { int a[3] = {4, 0, 5}; return f(3, a); }"
+check 15 "double trace(int n, double m[n][n]) { double t = 0; for (int i = 0; i < n; i++) t = t + m[i][i]; return t; } int main() { double m[3][3] = {{1, 2, 3}, {4, 5, 6}, {7, 8, 9}}; return trace(3, m); }"
+check 20 "int f(int n, int m[][n]) { return sizeof(m[0]) + sizeof(m); } int main() { int a[2][3]; return f(3, a); }"
+check 14 "int f(int n, int m[n][n]) { if (n == 1) return m[0][0]; return m[n - 1][n - 1] + f(1, (void *)m); } int main() { int a[2][2] = {{4, 0}, {0, 10}}; return f(2, a); }"
+check 4 "int f(int n, int m[n][n]); int main() { int a[2][2] = {{1, 2}, {3, 4}}; return f(2, a); } int f(int n, int m[n][n]) { return m[1][1]; }"
+check 3 "int f(int, int [][2]); int main() { int a[2][2] = {{1, 2}, {3, 4}}; return f(2, a); } int f(int n, int m[n][n]) { return m[1][0]; }"
+
+# bitwise and shift operators
+check 8 "int main() { int a = 12; int b = 10; return a & b; }"
+check 14 "int main() { int a = 12; int b = 10; return a | b; }"
+check 6 "int main() { int a = 12; int b = 10; return a ^ b; }"
+check 13 "int main() { int a = 12; return ~a + 26; }"
+check 7 "int main() { return ~~7 + ~-1; }"
+check 64 "int main() { int s = 6; return 1 << s; }"
+check 252 "int main() { return -16 >> 2; }"
+check 24 "int main() { return 1 + 2 << 3; }"
+check 3 "int main() { return 1 | 2 ^ 3 & 4; }"
+check 0 "int main() { return 12 & 4 == 4; }"
+check 1 "int main() { return 1 < 2 << 1; }"
+check 8 "int main() { int x = 5; x <<= 2; x >>= 1; x &= 6; x |= 9; x ^= 3; return x; }"
+check 9 "int main() { int a[2]; a[0] = 1; a[1] = 2; int i = 0; a[i++] |= 8; return a[0] * i; }"
+check 18 "struct S { int a : 4; int b : 3; }; int main() { struct S s; s.a = 5; s.b = 6; s.a |= 2; s.b ^= 7; s.a <<= 1; return s.b * 16 - s.a; }"
+check 1 "int main() { _Bool t = 0; t |= 4; return t; }"
+check 36 "enum { F = 1 << 3, G = F | 1 }; int a[G ^ 8]; int main() { return sizeof a * G; }"
+check 19 "int g = (1 << 4) | 3; int main() { return g; }"
+check 15 "int main() { int a[4] = {1, 2, 4, 8}; char c = 1; unsigned char uc = 2; unsigned u = 3; _Bool b = 1; return a[c & 1] + a[uc] + a[u] + a[b - 1]; }"
+check 15 "int main() { int a[4] = {1, 2, 4, 8}; char c = 1; unsigned char uc = 2; unsigned u = 3; _Bool b = 1; return *(a + c) + *(uc + a) + *(a + u) + *(a + b - 1); }"
+check 7 "int main() { int a[4] = {1, 2, 4, 8}; int *p = a + 3; unsigned u = 2; char c = 1; _Bool b = 1; p -= u; p += c; return *(p - b) + *(p + b - c) + (a + 3 - u == a + 1); }"
+check 7 "int main() { int a[3] = {1, 7, 9}; int *p = a + 2; int n = -1; return *(p + n); }"
+
+# conditional and comma operators
+check 3 "int main() { int a = 3; int b = 4; return a < b ? a : b; }"
+check 4 "int main() { int a = 3; int b = 4; return a > b ? a : b; }"
+check 2 "int main() { int a = 1; int b = 0; return a ? b ? 1 : 2 : 3; }"
+check 3 "int main() { return 0 ? 1 : 0 ? 2 : 3; }"
+check 20 "int main() { double d = 1 ? 2 : 1.5; return d * 10; }"
+check 5 "int main() { int a = 5; int *p = a ? &a : 0; return *p; }"
+check 1 "int main() { int a = 0; int *p = a ? &a : 0; return p == 0; }"
+check 7 "int main() { int a = 7; int b = 4; void *vp = &a; const int *cp = &b; return *(const int *)(b ? vp : cp); }"
+check 2 "int main() { double z = -0.0; return z ? 1 : 2; }"
+check 12 "struct P { int x; int y; }; struct P mk(int v) { struct P p; p.x = v; p.y = v * 2; return p; } int main() { int a = 1; struct P q = a ? mk(6) : mk(7); return q.y; }"
+check 12 "int a[1 ? 3 : 5]; int main() { return sizeof a; }"
+check 1 "int main() { int x = 5; int *p = &x; p = 0; return p == 0; }"
+check 30 "int main() { int i; int j; int s = 0; for (i = 0, j = 10; i < j; i++, j--) s = s + j - i; return s; }"
+check 8 "int main() { int a = 3; return (a = 7, a + 1); }"
+check 5 "int f(int a, int b) { return a + b; } int main() { return f((1, 2), 3); }"
+check 2 "int main() { int x = (1, 2); return x; }"
+
+# compound assignment operators
+check 7 "int calls; int f() { calls++; return 1; } int main() { int a[3]; a[0] = 1; a[1] = 3; a[2] = 5; a[f()] *= 2; return a[1] + calls; }"
+check 16 "struct P { int x; int y; }; int main() { struct P s; struct P *p = &s; s.x = 100; int k = 7; p->x /= k; p->x %= 4; return s.x * 8; }"
+check 4 "int main() { int x = 3; x *= 1.5; return x; }"
+check 253 "int main() { int x = -7; x /= 2; return x; }"
+check 255 "int main() { int x = -7; x %= 3; return x; }"
+check 15 "int main() { double d = 3; d *= 2.5; d /= 0.5; return d; }"
+check 6 "int main() { float f = 2; f *= 3; return f; }"
+check 255 "int main() { int x = -3; x += 1.5; return x; }"
+check 8 "int main() { int a[3]; a[0] = 1; a[1] = 3; a[2] = 5; int *q = a; q += 2; int r = *q; q -= 1; return r + *q; }"
+check 30 "int main() { char c = 10; c *= 3; return c; }"
+check 1 "int main() { _Bool b = 1; b += 0.5; _Bool z = 1; z *= 0; return b + z; }"
+check 18 "struct B { int a : 6; }; int main() { struct B b; b.a = 3; b.a *= 3; b.a *= 2.0; return b.a; }"
+check 14 "int main() { int x = 7; return x *= 2; }"
+
+# break, continue and do-while
+check 5 'int main() { int i = 0; while (1) { if (i == 5) break; i++; } return i; }'
+check 25 'int main() { int i; int s = 0; for (i = 0; i < 10; i++) { if (i % 2 == 0) continue; s += i; } return s; }'
+check 10 'int main() { int i = 0; int n = 0; while (i < 10) { i++; if (i > 100) continue; n++; } return n; }'
+check 1 'int main() { int i = 0; do { i++; } while (0); return i; }'
+check 10 'int main() { int i = 0; do i++; while (i < 10); return i; }'
+check 18 'int main() { int i = 0; int s = 0; do { i++; if (i == 3) continue; if (i == 7) break; s += i; } while (i < 10); return s; }'
+check 3 'int main() { int i = 0; for (;;) { if (++i == 3) break; } return i; }'
+check 12 'int main() { int i; int j; int n = 0; for (i = 0; i < 4; i++) { for (j = 0; j < 10; j++) { if (j == 3) break; n++; } continue; n = 100; } return n; }'
+check 45 'int main() { int i; int s = 0; for (i = 0; i < 10; i++) { int n = i + 1; int a[n]; a[0] = i; if (i % 2) continue; s += a[0]; if (i == 8) break; } for (i = 0; i < 1000; i++) { int n = 1000; char a[n]; a[0] = 1; if (a[0]) continue; } while (1) { int n = 100; char b[n]; { char c[n]; b[0] = 25; c[0] = b[0]; s += c[0]; break; } } return s; }'
+check 4 'int main() { int i = 0; int k = 0; while (i < 3) { i++; do { k++; break; } while (1); } return i + k - 2; }'
+
+# switch statements
+check 20 'int main() { int x = 2; switch (x) { case 1: return 10; case 2: return 20; case 3: return 30; } return 0; }'
+check 7 'int main() { int x = 5; switch (x) { case 1: return 10; default: return 7; case 2: return 20; } return 0; }'
+check 3 'int main() { int x = 9; int r = 3; switch (x) { case 1: r = 10; } return r; }'
+check 6 'int main() { int r = 0; switch (1) { case 1: r += 1; case 2: r += 2; case 3: r += 3; break; case 4: r += 100; } return r; }'
+check 42 'int main() { int r = 0; switch (3) { default: r = 40; case 1: r += 2; break; case 2: r = 5; } return r; }'
+check 12 'int main() { int x = 1; int y = 2; switch (x) { case 1: switch (y) { case 1: return 11; case 2: return 12; } return 13; case 2: return 20; } return 0; }'
+check 9 'int main() { int i; int s = 0; for (i = 0; i < 10; i++) { switch (i % 3) { case 0: continue; case 1: s += i; break; default: break; } s += 0; } return s - 3; }'
+check 3 'int main() { int x = -2; switch (x) { case -3: return 1; case -2: return 3; case 0: return 5; } return 0; }'
+check 98 "int main() { char c = 'b'; switch (c) { case 'a': return 97; case 'b': return 98; } return 0; }"
+check 15 'int f(int op) { switch (op) { case 0: return 10; case 1: return 11; case 2: return 12; case 3: return 13; case 5: return 15; case 6: return 16; case 7: return 17; default: return 99; } } int main() { return f(0) + f(3) + f(4) + f(7) - f(-1) + f(8) - f(100) + f(6) + f(5) - 56; }'
+check 55 'int main() { int pc = 0; int acc = 0; int code[8] = {1, 10, 2, 5, 3, 4, 0, 0}; while (1) { switch (code[pc]) { case 0: return acc; case 1: acc += code[pc + 1]; pc += 2; break; case 2: acc *= code[pc + 1]; pc += 2; break; case 3: acc += code[pc + 1] + 1; pc += 2; break; case 4: return 1; } } }'
+check 8 'int main() { int n = 8; int count = 0; int i = 0; switch (n % 4) { case 0: do { count++; case 3: count++; case 2: count++; case 1: count++; } while (++i < n / 4); } return count; }'
+check 4 'int main() { int r = 0; switch (2) { case 1: { case 2: r = 4; } } return r; }'
+
+# goto and labeled statements
+check 3 'int main() { int i = 0; loop: i++; if (i < 3) goto loop; return i; }'
+check 7 'int main() { goto end; return 1; end: return 7; }'
+check 10 'int f(int x) { int r = 0; if (x < 0) goto fail; r = x * 2; goto cleanup; fail: r = 1; cleanup: return r; } int main() { return f(5) + f(-1) - 1; }'
+check 8 'int main() { int i; int j; for (i = 0; i < 10; i++) { for (j = 0; j < 10; j++) { if (i * j == 6) goto out; } } out: return i + j + 1; }'
+check 5 'typedef int T; int main() { T x = 5; goto T; x = 1; T: return x; }'
+check 8 'int main() { int i = 0; while (1) { int n = 1000; char a[n]; a[0] = 8; if (++i == 3000) goto done; } done: return 8; }'
+check 9 'int main() { int i = 0; again: { int n = 1000; char a[n]; a[n - 1] = 9; i++; if (i < 3000) goto again; return a[n - 1]; } }'
+check 3 'int main() { int n = 4; { int a[n]; int i = 0; inner: a[i] = i; i++; if (i < n) goto inner; return a[3]; } }'
+
+# null statements
+check 3 'int main() { ; ; return 3; }'
+check 2 'int main() { int i = 0; { goto end; i = 5; end: ; } return i + 2; }'
+check 6 'int f(int n) { int i = 0; for (;;) { if (i == n) goto out; i++; } out: ; return i; } int main() { return f(6); }'
+check 5 'int main() { int i = 0; for (; i < 5; i++) ; return i; }'
+check 4 'int main() { int i = 0; for (;;) if (++i > 3) break; else ; return i; }'
+check 3 'int main() { int i = 9; while (i-- > 4) ; return i; }'
+check 4 'int main() { int x = 0; if (x) ; else x = 4; return x; }'
+check 1 'int main() { int x = 0; switch (x) { case 0: ; } do ; while (0); return 1; }'
+
+# variadic functions and stdarg
+check 0 'int main() { printf("%d %d %d %d %d %d %d %d", 1, 2, 3, 4, 5, 6, 7, 8); return 0; }' "1 2 3 4 5 6 7 8"
+check 0 'int main() { printf("[%s|%c|%d|%%]", "ab", 65, -2147483647 - 1); return 0; }' "[ab|A|-2147483648|%]"
+check 45 'int sum(int n, ...) { va_list ap; int s = 0; int i; va_start(ap, n); for (i = 0; i < n; i++) s += va_arg(ap, int); va_end(ap); return s; } int main() { return sum(9, 1, 2, 3, 4, 5, 6, 7, 8, 9); }'
+check 36 'int sum(int n, ...) { va_list ap; double s = 0; int i; va_start(ap, n); for (i = 0; i < n; i++) s += va_arg(ap, double); va_end(ap); return s; } int main() { return sum(10, 1.5, 2.5, 3.0, 4.0, 5.0, 6.0, 1.0, 2.0, 5.0, 6.0); }'
+check 110 'int f(int a, ...) { va_list ap; va_start(ap, a); int *p = va_arg(ap, int *); double d = va_arg(ap, double); char *s = va_arg(ap, char *); va_end(ap); return *p + d + s[1]; } int main() { int x = 10; return f(0, &x, 2.5, "ab"); }'
+check 12 'struct P { int x; int y; }; int f(int n, ...) { va_list ap; va_start(ap, n); struct P p = va_arg(ap, struct P); struct P q = va_arg(ap, struct P); va_end(ap); return p.x + p.y + q.x + q.y; } int main() { struct P a = {1, 2}; struct P b = {4, 5}; return f(2, a, b); }'
+check 66 'struct B { int a; int b; int c; int d; int e; int f; }; int f(int n, ...) { va_list ap; va_start(ap, n); struct B s = va_arg(ap, struct B); va_end(ap); return s.a + s.b + s.c + s.d + s.e + s.f; } int main() { struct B b = {10, 20, 30, 1, 2, 3}; return f(1, b); }'
+check 12 'int f(int n, ...) { va_list ap; va_list aq; int s = 0; va_start(ap, n); va_copy(aq, ap); s += va_arg(ap, int); s += va_arg(aq, int); s += va_arg(ap, int); va_end(aq); va_end(ap); return s; } int main() { return f(2, 5, 2); }'
+check 15 'int vsum(int n, va_list ap) { int s = 0; while (n--) s += va_arg(ap, int); return s; } int sum(int n, ...) { va_list ap; va_start(ap, n); int r = vsum(n, ap); va_end(ap); return r; } int main() { return sum(5, 1, 2, 3, 4, 5); }'
+check 6 'int sum(int n, ...) { va_list ap; int s = 0; __builtin_va_start(ap, n); while (n--) s += __builtin_va_arg(ap, int); __builtin_va_end(ap); return s; } int main() { int (*f)(int, ...) = sum; return f(3, 1, 2, 3); }'
+check 0 'int main() { int (*p)(const char *, ...) = printf; p("%d-%s", 7, "x"); return 0; }' "7-x"
+check_error 'int sum(int a, int n, ...) { va_list ap; va_start(ap, a); return 0; } int main() { return 0; }'
+check_error 'int sum(int n, ...) { va_list ap; { int n = 1; va_start(ap, n); } return 0; } int main() { return 0; }'
+check_error 'int sum(int n, ...) { va_list ap; va_start(ap, n + 1); return 0; } int main() { return 0; }'
+
+# parameters passed on the stack and rsp alignment at calls
+check 251 'int f(int a, int b, int c, int d, int e, int g, int h, int i) { return a + b + c + d + e + g + h * 10 + i * 20; } int main() { return f(1, 2, 3, 4, 5, 6, 7, 8); }'
+check 29 'double f(double a, double b, double c, double d, double e, double g, double h, double i, double j, double k) { return j + k * 2; } int main() { return f(1, 2, 3, 4, 5, 6, 7, 8, 9, 10); }'
+check 45 "double sum(double a, double b, double c, double d, double e, double f, double g, double h, double i, double j) { return a + b + c + d + e + f + g + h + i + j; } int main() { return sum(0, 1, 2, 3, 4, 5, 6, 7, 8, 9); }"
+check 37 'int f(int a, double x, int b, int c, int d, int e, int g, char h, int *p, double y) { return a + b + c + d + e + g + h + *p + x + y; } int main() { int n = 8; return f(1, 0.5, 2, 3, 4, 5, 6, 7, &n, 0.5); }'
+check 55 'int fib(int a, int b, int c, int d, int e, int f, int n, int acc) { if (n == 0) return acc; return fib(a, b, c, d, e, f, n - 1, acc + n); } int main() { return fib(0, 0, 0, 0, 0, 0, 10, 0); }'
+check 21 'struct S { int a; int b; int c; int d; int e; }; int f(int a, int b, int c, int d, int e, struct S s, int g, int h) { return s.a + s.e + g + h + e; } int main() { struct S s = {1, 2, 3, 4, 5}; return f(0, 0, 0, 0, 3, s, 5, 7); }'
+check 36 'int f(int a, int b, int c, int d, int e, int g, int h, int i) { return a + b + c + d + e + g + h + i; } int main() { int (*p)(int, int, int, int, int, int, int, int) = f; return p(1, 2, 3, 4, 5, 6, 7, 8); }'
+check 8 'int al() { char *x; return (int)&x & 15; } int main() { return al(); }'
+check 17 'int al() { char *x; return (int)&x & 15; } int main() { int a; return al() + 1 + al(); }'
+check 17 'int al() { char *x; return (int)&x & 15; } int g(int a, int b, int c, int d, int e, int f, int h, int i) { return al() + h + i; } int main() { char c; return 1 + g(1, 2, 3, 4, 5, 6, al(), 0); }'
+check 8 'int al() { char *x; return (int)&x & 15; } int g(int a, int b, int c, int d, int e, int f, int h) { return al() + h; } int main() { return g(1, 2, 3, 4, 5, 6, 0); }'
+check 24 'int al() { char *x; return (int)&x & 15; } int main() { int n = 3; char a[n]; int r = al(); return r + 2 * al(); }'
+check 22 'int al() { char *x; return (int)&x & 15; } struct S { int a; int b; int c; int d; int e; }; int g(struct S s, int x) { return al() + s.e + x; } int main() { struct S s = {1, 2, 3, 4, 5}; return 1 + g(s, al()); }'
+
+# assignments, returns, arguments and initializers between struct and non-struct types
+check_error "struct T { int a; }; int main() { int x; struct T t; x = t; return 0; }"
+check_error "struct T { int a; }; int main() { int x = 1; struct T t; t = x; return 0; }"
+check_error "struct T { int a; }; int f(struct T *p) { return *p; } int main() { return 0; }"
+check_error "struct T { int a; }; struct T f() { return 1; } int main() { return 0; }"
+check_error "struct T { int a; }; struct U { int a; }; int main() { struct T t; struct U u; t = u; return 0; }"
+check_error "struct T { int a; }; int f(int x) { return x; } int main() { struct T t; return f(t); }"
+check_error "struct T { int a; }; int main() { struct T t; int x = t; return x; }"
+check 3 "struct T { int a; }; struct T f() { struct T t; t.a = 3; return t; } int main() { const struct T c = f(); struct T d; d = c; return d.a; }"
+
 wait_jobs
 if [ $fail_count -gt 0 ]; then
   echo "$fail_count tests failed"